@@ -0,0 +1,5 @@
+fn main() {
+    // check the example's queries against the schema it's run against
+    println!("cargo:rustc-env=SCYLLAX_SCHEMA=../init.cql");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -1,9 +1,12 @@
 //! Example
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use example::entities::post::{
-    model::{LikeData, UpsertPost},
-    queries::{GetPostById, PostQueries},
+    model::{
+        Attachment, AttachmentKind, GetPost, GetPostCommentsBefore, LikeData, UpsertIndexedPost,
+        UpsertPost, UpsertPostComment,
+    },
+    queries::{GetPostsByTitle, PostQueries},
 };
 use scylla::frame::value::{CqlTimestamp, CqlTimeuuid};
 use scyllax::{executor::create_session, util::v1_uuid};
@@ -34,17 +37,62 @@ async fn main() -> anyhow::Result<()> {
     let insert_body = UpsertPost {
         id: post_id,
         title: MaybeUnset::Set("Hello, World!".to_string()),
-        likes: MaybeUnset::Set(Some(Json(vec![LikeData {
+        likes: Patch::Set(Json(vec![LikeData {
             user_id: Uuid::new_v4(),
             created_at: now(),
-        }]))),
+        }])),
         created_at: MaybeUnset::Set(CqlTimestamp(now())),
     };
     eprintln!("inserting post: {insert_body:#?}");
     executor.execute_write(insert_body).await?;
 
-    let post = executor.execute_read(GetPostById { id: post_id }).await?;
+    let post = executor.execute_read(GetPost { id: post_id }).await?;
     println!("Post: {post:#?}");
 
+    for body in ["First!", "Second!"] {
+        let comment =
+            UpsertPostComment::new(post_id, CqlTimeuuid::from(v1_uuid())).body(body.to_string());
+        executor.execute_write(comment).await?;
+    }
+
+    let attachment = Attachment {
+        name: "hello.txt".to_string(),
+        url: "https://scyllax.local/hello.txt".to_string(),
+        size: Some(13),
+        kind: Some(AttachmentKind::File),
+    };
+    let comment = UpsertPostComment::new(post_id, CqlTimeuuid::from(v1_uuid()))
+        .body("With an attachment".to_string())
+        .attachments(Append(vec![attachment]))
+        .metadata(Put(HashMap::from([(
+            "client".to_string(),
+            "example".to_string(),
+        )])));
+    executor.execute_write(comment).await?;
+
+    // the newest comments come first, as `id` is clustered in descending order
+    let comments = executor
+        .execute_read(GetPostCommentsBefore {
+            post_id,
+            id: CqlTimeuuid::from(v1_uuid()),
+            rowlimit: 10,
+        })
+        .await?;
+    println!("Comments: {comments:#?}");
+
+    // written to both `post` and `post_by_title` in one batch
+    let title = "Hello, Lookup!".to_string();
+    let indexed = UpsertIndexedPost::new(CqlTimeuuid::from(v1_uuid()), title.clone())
+        .created_at(CqlTimestamp(now()));
+    executor.execute_write(indexed).await?;
+
+    let posts = executor
+        .execute_read(GetPostsByTitle {
+            title,
+            rowlimit: 10,
+        })
+        .await?;
+    println!("Posts by title: {posts:#?}");
+
     Ok(())
 }
@@ -0,0 +1,22 @@
+//! Prints the `create type` and `create table` statements of the example's entities.
+//!
+//! ```console
+//! cargo run -p example --bin schema | scyllax-cli migrate generate create tables
+//! ```
+use example::entities::{
+    post::model::{Attachment, PostCommentEntity},
+    PersonEntity, PersonLoginEntity, PostEntity,
+};
+use scyllax::prelude::*;
+
+fn main() {
+    let statements = [
+        Attachment::create_type_cql(),
+        PersonEntity::create_table_cql("person"),
+        PersonLoginEntity::create_table_cql("person_login"),
+        PostEntity::create_table_cql("post"),
+        PostCommentEntity::create_table_cql("post_comment"),
+    ];
+
+    println!("{}", statements.join("\n\n"));
+}
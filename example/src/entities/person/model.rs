@@ -10,7 +10,7 @@ pub struct PersonData {
 }
 
 /// Represents the kind of person
-#[int_enum]
+#[int_enum(fallback = Unknown)]
 pub enum PersonKind {
     /// The person is a staff member
     Staff = 0,
@@ -18,12 +18,15 @@ pub enum PersonKind {
     Parent = 1,
     /// The person is a student
     Student = 2,
+    /// A kind added by a newer version
+    Unknown(i32),
 }
 
 /// Represents a person in the database
-#[entity]
+#[entity(ddl)]
 #[upsert_query(table = "person", name = UpsertPerson)]
 #[upsert_query(table = "person", name = UpsertPersonWithTTL, ttl)]
+#[insert_query(table = "person", name = InsertPerson, if_not_exists)]
 pub struct PersonEntity {
     /// The id of the person
     #[entity(primary_key)]
@@ -42,6 +45,17 @@ pub struct PersonEntity {
     pub created_at: CqlTimestamp,
 }
 
+/// The creation date of a [`PersonEntity`]
+#[derive(Clone, Debug, PartialEq, FromRow, ValueList, PartialEntity)]
+#[partial(of = PersonEntity)]
+pub struct PersonCreatedAt {
+    /// The id of the person
+    pub id: CqlTimeuuid,
+    /// The date the person was created
+    #[partial(rename = "createdAt")]
+    pub created_at: CqlTimestamp,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -66,4 +80,19 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_partial_keys() {
+        assert_eq!(
+            PersonCreatedAt::keys(),
+            vec![r#""id""#.to_string(), r#""createdAt""#.to_string()]
+        );
+        assert_eq!(PersonCreatedAt::pks(), vec![r#""id""#.to_string()]);
+    }
+
+    #[test]
+    fn test_person_kind_fallback() {
+        assert_eq!(PersonKind::try_from(1), Ok(PersonKind::Parent));
+        assert_eq!(PersonKind::try_from(7), Ok(PersonKind::Unknown(7)));
+    }
 }
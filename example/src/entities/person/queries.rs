@@ -1,4 +1,4 @@
-use super::model::{UpsertPerson, UpsertPersonWithTTL};
+use super::model::{InsertPerson, UpsertPerson, UpsertPersonWithTTL};
 use scylla::{frame::value::CqlTimeuuid, SerializeRow};
 use scyllax::prelude::*;
 use value::CqlTimestamp;
@@ -9,9 +9,15 @@ create_query_collection!(
         GetPersonById,
         GetPeopleByIds,
         GetPersonByEmail,
-        GetPeopleCreatedBefore
+        GetPeopleCreatedBefore,
+        GetPersonCreatedAtById
     ],
-    [DeletePersonById, UpsertPerson, UpsertPersonWithTTL]
+    [
+        DeletePersonById,
+        UpsertPerson,
+        UpsertPersonWithTTL,
+        InsertPerson
+    ]
 );
 
 #[inline]
@@ -67,6 +73,17 @@ pub struct GetPersonByEmail {
     pub email: String,
 }
 
+/// Get the creation date of a [`super::model::PersonEntity`] by its [`uuid::Uuid`]
+#[derive(Debug, Clone, PartialEq, SerializeRow, ReadQuery)]
+#[read_query(
+    query = "select * from person where id = :id limit 1",
+    return_type = "super::model::PersonCreatedAt"
+)]
+pub struct GetPersonCreatedAtById {
+    /// The [`uuid::Uuid`] of the [`super::model::PersonEntity`] to get
+    pub id: CqlTimeuuid,
+}
+
 /// Get a [`super::model::PersonEntity`] by its [`uuid::Uuid`]
 #[write_query(query = "delete from person where id = :id")]
 pub struct DeletePersonById {
@@ -116,6 +133,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_get_person_created_at_by_id() {
+        assert_eq!(
+            GetPersonCreatedAtById::query(),
+            r#"select "id", "createdAt" from person where id = :id limit 1"#
+        );
+    }
+
+    #[test]
+    fn test_insert_person() {
+        assert_eq!(
+            InsertPerson::query(),
+            r#"insert into person ("id", "email", "age", "data", "kind", "createdAt") values (:id, :email, :age, :data, :kind, :created_at) if not exists;"#
+        );
+    }
+
     #[test]
     fn test_delete_person_by_id() {
         let _query = DeletePersonById {
@@ -2,7 +2,7 @@ use scylla::frame::value::CqlTimeuuid;
 use scyllax::prelude::*;
 
 /// Represents a person in the database
-#[entity]
+#[entity(ddl)]
 #[upsert_query(table = "person_login", name = UpsertPersonLogin)]
 pub struct PersonLoginEntity {
     /// The id of the person
@@ -1,6 +1,7 @@
 use scylla::frame::value::{CqlTimestamp, CqlTimeuuid};
 use scyllax::{json::Json, prelude::*};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -9,9 +10,14 @@ pub struct LikeData {
     pub created_at: i64,
 }
 
-/// Represents a post in the database
-#[entity]
-#[upsert_query(table = "post", name = UpsertPost)]
+/// Represents a post in the database, also written to the `post_by_title` lookup table
+#[entity(table = "post", crud, ddl)]
+#[upsert_query(
+    tables = ["post", "post_by_title"],
+    keys(post_by_title(title, id)),
+    name = UpsertIndexedPost,
+    delete = DeleteIndexedPost
+)]
 pub struct PostEntity {
     /// The id of the post
     #[entity(primary_key)]
@@ -24,6 +30,60 @@ pub struct PostEntity {
     pub created_at: CqlTimestamp,
 }
 
+/// The kind of a file attached to a comment
+#[text_enum]
+pub enum AttachmentKind {
+    /// An image, shown inline
+    #[text_enum(rename = "image")]
+    Image,
+    /// Any other file
+    #[text_enum(rename = "file")]
+    File,
+    /// A kind added by a newer version
+    Unknown(String),
+}
+
+/// A file attached to a comment, stored as the `attachment` user defined type
+#[udt(name = "attachment")]
+pub struct Attachment {
+    /// The name of the file
+    pub name: String,
+    /// The url of the file
+    pub url: String,
+    /// The size of the file in bytes
+    pub size: Option<i64>,
+    /// The kind of the file
+    pub kind: Option<AttachmentKind>,
+}
+
+/// Represents a comment on a post, stored in the partition of the post
+#[entity(ddl)]
+#[upsert_query(table = "post_comment", name = UpsertPostComment)]
+#[slice_query(
+    table = "post_comment",
+    name = GetPostCommentsBefore,
+    column = id,
+    range = "lt",
+    limit
+)]
+pub struct PostCommentEntity {
+    /// The id of the post
+    #[entity(partition_key)]
+    pub post_id: CqlTimeuuid,
+    /// The id of the comment, newest first
+    #[entity(clustering_key(order = "desc"))]
+    pub id: CqlTimeuuid,
+    /// The body of the comment
+    pub body: String,
+    /// The files attached to the comment, upserts attach more files
+    #[entity(collection = "append")]
+    pub attachments: Option<Vec<Attachment>>,
+    /// Extra data about the comment, eg: the client it was posted from.
+    /// Upserts put their entries, leaving the others as is.
+    #[entity(collection = "put")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -46,4 +106,51 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_comment_keys() {
+        assert_eq!(
+            PostCommentEntity::partition_keys(),
+            vec![r#""post_id""#.to_string()]
+        );
+        assert_eq!(
+            PostCommentEntity::clustering_keys(),
+            vec![(r#""id""#.to_string(), ClusteringOrder::Desc)]
+        );
+        assert_eq!(
+            PostCommentEntity::pks(),
+            vec![r#""post_id""#.to_string(), r#""id""#.to_string()]
+        );
+    }
+
+    #[test]
+    fn test_create_table_cql() {
+        assert_eq!(
+            PostEntity::create_table_cql("post"),
+            "create table if not exists post (\n\t\"id\" timeuuid,\n\t\"title\" text,\n\t\"likes\" text,\n\t\"created_at\" timestamp,\n\tprimary key (\"id\")\n);"
+        );
+        assert_eq!(
+            PostCommentEntity::create_table_cql("post_comment"),
+            "create table if not exists post_comment (\n\t\"post_id\" timeuuid,\n\t\"id\" timeuuid,\n\t\"body\" text,\n\t\"attachments\" list<frozen<attachment>>,\n\t\"metadata\" map<text, text>,\n\tprimary key (\"post_id\", \"id\")\n) with clustering order by (\"id\" desc);"
+        );
+    }
+
+    #[test]
+    fn test_create_type_cql() {
+        assert_eq!(Attachment::type_name(), "attachment");
+        assert_eq!(
+            Attachment::create_type_cql(),
+            "create type if not exists attachment (\n\t\"name\" text,\n\t\"url\" text,\n\t\"size\" bigint,\n\t\"kind\" text\n);"
+        );
+    }
+
+    #[test]
+    fn test_attachment_kind() {
+        assert_eq!("image".parse(), Ok(AttachmentKind::Image));
+        assert_eq!(AttachmentKind::File.as_str(), "file");
+
+        let unknown = "video".parse::<AttachmentKind>().unwrap();
+        assert_eq!(unknown, AttachmentKind::Unknown("video".to_string()));
+        assert_eq!(unknown.to_string(), "video");
+    }
 }
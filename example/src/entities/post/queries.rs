@@ -1,24 +1,74 @@
-use super::model::UpsertPost;
-use scylla::{frame::value::CqlTimeuuid, SerializeRow};
 use scyllax::prelude::*;
 
-create_query_collection!(PostQueries, [GetPostById], [DeletePostById, UpsertPost]);
+use super::model::{
+    DeleteIndexedPost, GetPostCommentsBefore, UpsertIndexedPost, UpsertPostComment,
+};
 
-/// Get a [`super::model::PostEntity`] by its [`uuid::Uuid`]
+/// Get [`super::model::PostEntity`]s by their title, from the `post_by_title` lookup table
 #[derive(Debug, Clone, PartialEq, SerializeRow, ReadQuery)]
 #[read_query(
-    query = "select * from post where id = :id limit 1",
-    return_type = "super::model::PostEntity"
+    query = r#"select * from post_by_title where "title" = :title limit :rowlimit"#,
+    return_type = "Vec<super::model::PostEntity>"
 )]
-pub struct GetPostById {
-    /// The [`uuid::Uuid`] of the [`super::model::PostEntity`] to get
+pub struct GetPostsByTitle {
+    /// The title of the posts
     #[read_query(coalesce_shard_key)]
-    pub id: CqlTimeuuid,
+    pub title: String,
+    /// The maximum number of posts to get
+    pub rowlimit: i32,
 }
 
-/// Get a [`super::model::PostEntity`] by its [`uuid::Uuid`]
-#[write_query(query = "delete from post where id = :id")]
-pub struct DeletePostById {
-    /// The [`uuid::Uuid`] of the [`super::model::PostEntity`] to get
-    pub id: CqlTimeuuid,
+// `GetPost`, `DeletePost` and `UpsertPost` are generated by `#[entity(table = "post", crud)]`
+create_query_collection!(
+    PostQueries,
+    [GetPostCommentsBefore, GetPostsByTitle],
+    [UpsertPostComment, UpsertIndexedPost, DeleteIndexedPost],
+    crud = [super::model::PostEntity]
+);
+
+#[cfg(test)]
+mod test {
+    use super::super::model::{DeletePost, GetPost};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_crud_queries() {
+        assert_eq!(
+            GetPost::query(),
+            r#"select * from post where "id" = :id limit 1"#
+        );
+        assert_eq!(DeletePost::query(), r#"delete from post where "id" = :id"#);
+    }
+
+    #[test]
+    fn test_lookup_table_queries() {
+        assert_eq!(
+            UpsertIndexedPost::query(),
+            concat!(
+                r#"begin batch update post set "title" = :title, "likes" = :likes, "created_at" = :created_at where "id" = :id; "#,
+                r#"update post_by_title set "likes" = :likes, "created_at" = :created_at where "title" = :title and "id" = :id; apply batch;"#
+            )
+        );
+        assert_eq!(
+            DeleteIndexedPost::query(),
+            r#"begin batch delete from post where "id" = :id; delete from post_by_title where "title" = :title and "id" = :id; apply batch;"#
+        );
+    }
+
+    #[test]
+    fn test_collection_query() {
+        assert_eq!(
+            UpsertPostComment::query(),
+            r#"update post_comment set "body" = :body, "attachments" = "attachments" + :attachments, "metadata" = "metadata" + :metadata where "post_id" = :post_id and "id" = :id;"#
+        );
+    }
+
+    #[test]
+    fn test_slice_query() {
+        assert_eq!(
+            GetPostCommentsBefore::query(),
+            r#"select "post_id", "id", "body", "attachments", "metadata" from post_comment where "post_id" = :post_id and "id" < :id limit :rowlimit"#
+        );
+    }
 }
@@ -3,7 +3,7 @@ use std::{str::FromStr, sync::Arc};
 
 use example::entities::{
     person::{
-        model::{PersonData, PersonKind, UpsertPerson, UpsertPersonWithTTL},
+        model::{InsertPerson, PersonData, PersonKind, UpsertPerson, UpsertPersonWithTTL},
         queries::{
             DeletePersonById, GetPeopleByIds, GetPeopleCreatedBefore, GetPersonByEmail,
             GetPersonById, PersonQueries,
@@ -49,10 +49,10 @@ async fn main() -> anyhow::Result<()> {
     let query = UpsertPerson {
         id: upsert_id,
         email: "foo21@scyllax.local".to_string().into(),
-        age: MaybeUnset::Set(Some(21)),
-        data: MaybeUnset::Set(Some(PersonData {
+        age: Patch::Set(21),
+        data: Patch::Set(PersonData {
             stripe_id: Some("stripe_id".to_string()),
-        })),
+        }),
         kind: MaybeUnset::Set(PersonKind::Parent),
         created_at: MaybeUnset::Unset,
     };
@@ -67,10 +67,10 @@ async fn main() -> anyhow::Result<()> {
     let query = UpsertPersonWithTTL {
         id: upsert_ttl_id,
         email: "foo42@scyllax.local".to_string().into(),
-        age: MaybeUnset::Set(Some(42)),
-        data: MaybeUnset::Set(Some(PersonData {
+        age: Patch::Set(42),
+        data: Patch::Set(PersonData {
             stripe_id: Some("stripe_id".to_string()),
-        })),
+        }),
         kind: MaybeUnset::Set(PersonKind::Parent),
         created_at: MaybeUnset::Unset,
 
@@ -80,16 +80,30 @@ async fn main() -> anyhow::Result<()> {
     let res = executor.execute_write(query).await?;
     tracing::info!("UpsertPersonWithTTL returned: {:?}", res);
 
+    let insert_id = CqlTimeuuid::from(v1_uuid());
+    let query = InsertPerson {
+        id: insert_id,
+        email: "foo63@scyllax.local".to_string(),
+        age: None,
+        data: None,
+        kind: PersonKind::Student,
+        created_at: CqlTimestamp::from(OffsetDateTime::now_utc()),
+    };
+    let res = executor.execute_write(query.clone()).await?;
+    assert!(res.applied(), "InsertPerson wasn't applied");
+    let res = executor.execute_write(query).await?;
+    assert!(!res.applied(), "InsertPerson was applied twice");
+    executor
+        .execute_write(DeletePersonById { id: insert_id })
+        .await?;
+
     let old_user_id = v1_uuid();
     let one_year_ago = OffsetDateTime::now_utc() - Duration::days(365);
-    let query = UpsertPerson {
-        id: CqlTimeuuid::from(old_user_id),
-        email: MaybeUnset::Set("foo55@scyllax.local".to_string()),
-        age: MaybeUnset::Set(Some(55)),
-        data: MaybeUnset::Unset,
-        kind: MaybeUnset::Set(PersonKind::Staff),
-        created_at: MaybeUnset::Set(CqlTimestamp::from(one_year_ago)),
-    };
+    let query = UpsertPerson::new(CqlTimeuuid::from(old_user_id))
+        .email("foo55@scyllax.local".to_string())
+        .age(55)
+        .kind(PersonKind::Staff)
+        .created_at(CqlTimestamp::from(one_year_ago));
     executor.execute_write(query).await?;
 
     let get_old = executor
@@ -105,6 +119,19 @@ async fn main() -> anyhow::Result<()> {
         "Old user not found"
     );
 
+    // only the age changed, so only the age is written
+    let old_user = get_old
+        .into_iter()
+        .find(|p| p.id == CqlTimeuuid::from(old_user_id))
+        .unwrap();
+    let birthday = PersonEntity {
+        age: Some(56),
+        ..old_user.clone()
+    };
+    executor
+        .execute_write(UpsertPerson::from_diff(&old_user, &birthday))
+        .await?;
+
     executor
         .execute_write(DeletePersonById {
             id: CqlTimeuuid::from(old_user_id),
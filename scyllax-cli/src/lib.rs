@@ -17,6 +17,11 @@ pub async fn run(opt: Opt) -> Result<()> {
                 source,
                 description,
             } => migrate::add(&source, &description).await?,
+            MigrateCommand::Generate {
+                description,
+                input,
+                source,
+            } => migrate::generate(&source, &description, input.as_deref()).await?,
             MigrateCommand::Init { connect_opts } => migrate::init(connect_opts).await?,
             MigrateCommand::Run {
                 source,
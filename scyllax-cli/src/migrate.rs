@@ -1,19 +1,21 @@
 use anyhow::Context;
 use console::style;
 use scylla::query::Query;
-use scyllax::executor::create_session;
-use std::fs::{self, File};
+use scyllax::{executor::create_session, prelude::EntityDdl};
+use scyllax_parser::create_table::{parse_create_materialized_view, parse_create_table};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
 use time::format_description;
 
 use crate::{
     migrator::{DownMigration, MigrationFolder, MigrationMode, Migrator, UpMigration},
-    model::{create_migration_executor, GetLatestVersion},
+    model::{create_migration_executor, GetLatestVersion, MigrationEntity},
     opt::ConnectOpts,
 };
 
 fn create_file<K: MigrationMode>(migration_source: &str) -> anyhow::Result<()> {
-    use std::path::PathBuf;
-
     let mut path = PathBuf::new();
     path.push(migration_source);
     path.push(K::name());
@@ -27,7 +29,8 @@ fn create_file<K: MigrationMode>(migration_source: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub async fn add(migration_source: &str, description: &str) -> anyhow::Result<()> {
+/// Creates a new migration folder, returning its path.
+fn create_folder(migration_source: &str, description: &str) -> anyhow::Result<PathBuf> {
     fs::create_dir_all(migration_source).context("Unable to create migrations directory")?;
 
     let format = format_description::parse("[year][month][day][hour][minute][second]")?;
@@ -36,11 +39,17 @@ pub async fn add(migration_source: &str, description: &str) -> anyhow::Result<()
     let folder = format!("{}_{}", ts, description.replace(' ', "_"));
     println!("Creating {}", style(&folder).cyan());
     // create folder
-    let mut path = std::path::PathBuf::new();
+    let mut path = PathBuf::new();
     path.push(migration_source);
     path.push(&folder);
     fs::create_dir_all(&path).context("Unable to create migration directory")?;
 
+    Ok(path)
+}
+
+pub async fn add(migration_source: &str, description: &str) -> anyhow::Result<()> {
+    let path = create_folder(migration_source, description)?;
+
     create_file::<UpMigration>(&path.to_string_lossy())?;
     create_file::<DownMigration>(&path.to_string_lossy())?;
 
@@ -49,6 +58,91 @@ pub async fn add(migration_source: &str, description: &str) -> anyhow::Result<()
     Ok(())
 }
 
+/// Creates a new migration from `create table`, `create materialized view` and `create type` statements.
+/// The down migration drops what the up migration creates, in reverse order.
+pub async fn generate(
+    migration_source: &str,
+    description: &str,
+    input: Option<&Path>,
+) -> anyhow::Result<()> {
+    let statements = match input {
+        Some(input) => fs::read_to_string(input)
+            .with_context(|| format!("Failed to read {}", input.display()))?,
+        None => std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?,
+    };
+
+    let mut up = vec![];
+    let mut down = vec![];
+    for statement in statements.split(';').map(str::trim) {
+        if statement.is_empty() {
+            continue;
+        }
+
+        let statement = format!("{statement};");
+        down.push(drop_statement(&statement)?);
+        up.push(statement);
+    }
+    if up.is_empty() {
+        anyhow::bail!("No statements to generate a migration from");
+    }
+    down.reverse();
+
+    let path = create_folder(migration_source, description)?;
+    // statements are split by blank lines when the migration is run
+    for (name, statements) in [(UpMigration::name(), up), (DownMigration::name(), down)] {
+        println!("Creating {}", style(name).cyan());
+        fs::write(path.join(name), statements.join("\n\n") + "\n")
+            .context("Failed to create migration file")?;
+    }
+
+    println!("Migration created successfully");
+
+    Ok(())
+}
+
+/// The statement dropping what a `create table`, `create materialized view` or `create type` statement creates.
+fn drop_statement(statement: &str) -> anyhow::Result<String> {
+    let qualified = |keyspace: Option<String>, name: String| match keyspace {
+        Some(keyspace) => format!("{keyspace}.{name}"),
+        None => name,
+    };
+
+    if let Ok((_, table)) = parse_create_table(statement) {
+        Ok(format!(
+            "drop table if exists {};",
+            qualified(table.keyspace, table.table)
+        ))
+    } else if let Ok((_, view)) = parse_create_materialized_view(statement) {
+        Ok(format!(
+            "drop materialized view if exists {};",
+            qualified(view.keyspace, view.view)
+        ))
+    } else if let Some(name) = create_type_name(statement) {
+        Ok(format!("drop type if exists {name};"))
+    } else {
+        anyhow::bail!(
+            "Only `create table`, `create materialized view` and `create type` statements can be generated into a migration, found:\n{statement}"
+        )
+    }
+}
+
+/// The name of the type a `create type` statement creates, eg: `create type if not exists address (...)`
+fn create_type_name(statement: &str) -> Option<&str> {
+    let mut words = statement.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("create") || !words.next()?.eq_ignore_ascii_case("type")
+    {
+        return None;
+    }
+
+    let mut name = words.next()?;
+    if name.eq_ignore_ascii_case("if") {
+        // skip `not exists`
+        name = words.nth(2)?;
+    }
+
+    name.split('(').next().filter(|name| !name.is_empty())
+}
+
 /// Runs all pending migrations.
 pub async fn run(
     migration_source: &str,
@@ -120,21 +214,11 @@ pub async fn revert(migration_source: &str, connect_opts: ConnectOpts) -> anyhow
 /// Creates the `scyllax_migrations` table.
 pub async fn init(connect_opts: ConnectOpts) -> anyhow::Result<()> {
     let create_keyspace = r#"create keyspace if not exists scyllax_migrations with replication = { 'class': 'NetworkTopologyStrategy', 'datacenter1': 1 };"#;
-    let create_table = r#"
-create table if not exists scyllax_migrations.migration (
-    bucket int,
-	version bigint,
-	description text,
-	installed_on timestamp,
-	success boolean,
-	checksum blob,
-	execution_time bigint,
-	primary key (bucket, version)
-);"#;
+    let create_table = MigrationEntity::create_table_cql("scyllax_migrations.migration");
 
     let session = create_session(connect_opts.scylla_nodes.split(','), None::<&str>).await?;
 
-    for query in [create_keyspace, create_table] {
+    for query in [create_keyspace, create_table.as_str()] {
         let prepared_query = Query::new(query);
         session.query(prepared_query, ()).await?;
     }
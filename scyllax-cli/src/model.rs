@@ -20,12 +20,12 @@ pub async fn create_migration_executor(
     Ok(Arc::new(executor))
 }
 
-#[entity]
+#[entity(ddl)]
 #[upsert_query(table = "migration", name = UpsertMigration)]
 pub struct MigrationEntity {
-    #[entity(primary_key)]
+    #[entity(clustering_key)]
     pub version: i64,
-    #[entity(primary_key)]
+    #[entity(partition_key)]
     pub bucket: i32,
     pub description: String,
     pub installed_on: CqlTimestamp,
@@ -1,4 +1,7 @@
-use std::ops::{Deref, Not};
+use std::{
+    ops::{Deref, Not},
+    path::PathBuf,
+};
 
 use clap::{Args, Parser};
 use clap_complete::Shell;
@@ -42,6 +45,23 @@ pub enum MigrateCommand {
         source: Source,
     },
 
+    /// Create a new migration from `create table`, `create materialized view` and `create type` statements,
+    /// eg: the output of `EntityDdl::create_table_cql` or `UdtExt::create_type_cql`. The down migration drops them.
+    ///
+    /// The statements are read from `--input`, or stdin.
+    ///
+    /// Example: cargo run --bin schema | scyllax-cli mig generate create post tables
+    Generate {
+        description: String,
+
+        /// The file to read the statements from, instead of stdin
+        #[clap(long)]
+        input: Option<PathBuf>,
+
+        #[clap(flatten)]
+        source: Source,
+    },
+
     /// Creates the `scyllax_migrations` keyspace.
     Init {
         #[clap(flatten)]
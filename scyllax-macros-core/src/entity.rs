@@ -1,6 +1,11 @@
-use darling::{ast, util, FromDeriveInput, FromField};
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use darling::{
+    ast,
+    ast::NestedMeta,
+    util::{self, Override},
+    FromDeriveInput, FromField, FromMeta,
+};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
 use syn::{spanned::Spanned, DeriveInput, Ident, Type};
 
 #[derive(Debug, PartialEq, FromField)]
@@ -17,8 +22,94 @@ pub struct EntityDeriveColumn {
     pub counter: bool,
     #[darling(default)]
     pub primary_key: bool,
+    /// Part of the partition key
+    #[darling(default)]
+    pub partition_key: bool,
+    /// Part of the clustering key, eg: `clustering_key` or `clustering_key(order = "desc")`
+    #[darling(default)]
+    pub clustering_key: Option<Override<ClusteringKey>>,
+    /// The column is shared by every row of a partition
+    #[darling(default)]
+    pub static_column: bool,
     #[darling(default)]
     pub rename: Option<String>,
+    /// How upsert queries update the collection, instead of overwriting it
+    #[darling(default)]
+    pub collection: Option<CollectionOp>,
+}
+
+/// An in place update of a list, set or map column, rendered by upsert queries
+#[derive(Debug, Clone, Copy, PartialEq, FromMeta)]
+#[darling(rename_all = "snake_case")]
+pub enum CollectionOp {
+    /// `col = col + :value` on lists and sets
+    Append,
+    /// `col = :value + col` on lists
+    Prepend,
+    /// `col = col - :value` on lists and sets
+    Remove,
+    /// `col = col + :value` on maps
+    Put,
+    /// `col = col - :value` on maps
+    RemoveKeys,
+}
+
+/// The options of a clustering key
+#[derive(Debug, Clone, Default, PartialEq, FromMeta)]
+pub struct ClusteringKey {
+    #[darling(default)]
+    pub order: ClusteringOrder,
+}
+
+/// The order rows are sorted in by a clustering key
+#[derive(Debug, Clone, Copy, Default, PartialEq, FromMeta)]
+#[darling(rename_all = "snake_case")]
+pub enum ClusteringOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl ToTokens for ClusteringOrder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            ClusteringOrder::Asc => quote!(scyllax::entity::ClusteringOrder::Asc),
+            ClusteringOrder::Desc => quote!(scyllax::entity::ClusteringOrder::Desc),
+        });
+    }
+}
+
+impl CollectionOp {
+    /// The name of the operation, as written in the attribute
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollectionOp::Append => "append",
+            CollectionOp::Prepend => "prepend",
+            CollectionOp::Remove => "remove",
+            CollectionOp::Put => "put",
+            CollectionOp::RemoveKeys => "remove_keys",
+        }
+    }
+
+    /// The columns the operation can be used on
+    pub fn columns(&self) -> &'static str {
+        match self {
+            CollectionOp::Append | CollectionOp::Remove => "list or set",
+            CollectionOp::Prepend => "list",
+            CollectionOp::Put | CollectionOp::RemoveKeys => "map",
+        }
+    }
+
+    /// Whether the operation can be used on a field of type `ty`
+    pub fn supports(&self, ty: &Type) -> bool {
+        match self {
+            CollectionOp::Append | CollectionOp::Remove => {
+                element_type(ty, &["Vec", "HashSet", "BTreeSet"]).is_some()
+            }
+            CollectionOp::Prepend => element_type(ty, &["Vec"]).is_some(),
+            CollectionOp::Put | CollectionOp::RemoveKeys => map_types(ty).is_some(),
+        }
+    }
 }
 
 impl EntityDeriveColumn {
@@ -29,7 +120,11 @@ impl EntityDeriveColumn {
             ty,
             counter,
             primary_key,
+            partition_key,
+            clustering_key,
+            static_column,
             rename,
+            collection,
             ..
         } = self;
 
@@ -45,9 +140,50 @@ impl EntityDeriveColumn {
 
             counter,
             primary_key,
+            partition_key,
+            clustering_key,
+            static_column,
             rename,
+            collection,
         })
     }
+
+    /// Whether the column is part of the primary key
+    pub fn is_primary_key(&self) -> bool {
+        self.primary_key || self.partition_key || self.clustering_key.is_some()
+    }
+}
+
+/// Splits the primary key of an entity into its partition keys and its clustering keys.
+///
+/// Without `partition_key` fields, the first `primary_key` field is the partition key.
+/// The other `primary_key` fields are ascending clustering keys, like `primary key (a, b)` in CQL.
+pub(crate) fn key_roles<'a>(
+    fields: &[&'a EntityDeriveColumn],
+) -> (
+    Vec<&'a EntityDeriveColumn>,
+    Vec<(&'a EntityDeriveColumn, ClusteringOrder)>,
+) {
+    let has_partition_key = fields.iter().any(|f| f.partition_key);
+    let mut partition_keys = Vec::new();
+    let mut clustering_keys = Vec::new();
+
+    for field in fields {
+        if field.partition_key {
+            partition_keys.push(*field);
+        } else if let Some(clustering_key) = &field.clustering_key {
+            let order = clustering_key.clone().unwrap_or_default().order;
+            clustering_keys.push((*field, order));
+        } else if field.primary_key {
+            if !has_partition_key && partition_keys.is_empty() {
+                partition_keys.push(*field);
+            } else {
+                clustering_keys.push((*field, ClusteringOrder::Asc));
+            }
+        }
+    }
+
+    (partition_keys, clustering_keys)
 }
 
 #[derive(Debug, PartialEq, FromDeriveInput)]
@@ -99,6 +235,83 @@ impl ToTokens for EntityDerive {
             }
         }
 
+        // validate collection operations
+        for field in fields.iter() {
+            let Some(op) = field.collection else {
+                continue;
+            };
+
+            let error = if field.counter || field.is_primary_key() {
+                Some(format!(
+                    "`{}` can't be used on counter or primary key fields",
+                    op.name()
+                ))
+            } else if !op.supports(&field.ty) {
+                Some(format!(
+                    "`{}` can only be used on {} fields",
+                    op.name(),
+                    op.columns()
+                ))
+            } else {
+                None
+            };
+
+            if let Some(error) = error {
+                tokens.extend(syn::Error::new(field.ident.span(), error).to_compile_error());
+                return;
+            }
+        }
+
+        // validate key roles
+        if let Some(field) = fields
+            .iter()
+            .find(|f| f.partition_key && f.clustering_key.is_some())
+        {
+            tokens.extend(
+                syn::Error::new(
+                    field.ident.span(),
+                    "A field can't be both a partition key and a clustering key",
+                )
+                .to_compile_error(),
+            );
+            return;
+        }
+
+        if let Some(field) = fields
+            .iter()
+            .find(|f| f.static_column && f.is_primary_key())
+        {
+            tokens.extend(
+                syn::Error::new(field.ident.span(), "Primary key columns can't be static")
+                    .to_compile_error(),
+            );
+            return;
+        }
+
+        let (partition_keys, clustering_keys) = key_roles(&fields);
+        if let Some(field) = fields.iter().find(|f| f.static_column) {
+            if clustering_keys.is_empty() {
+                tokens.extend(
+                    syn::Error::new(
+                        field.ident.span(),
+                        "Static columns need a table with clustering keys",
+                    )
+                    .to_compile_error(),
+                );
+                return;
+            }
+        }
+        if partition_keys.is_empty() && !clustering_keys.is_empty() {
+            tokens.extend(
+                syn::Error::new(
+                    ident.span(),
+                    "Entities with clustering keys need a `partition_key` field",
+                )
+                .to_compile_error(),
+            );
+            return;
+        }
+
         let keys: Vec<TokenStream> = fields
             .iter()
             .map(|f| {
@@ -107,15 +320,35 @@ impl ToTokens for EntityDerive {
             })
             .collect();
 
-        let primary_keys: Vec<TokenStream> = fields
+        let partition_keys: Vec<TokenStream> = partition_keys
             .iter()
-            .filter(|f| f.primary_key)
             .map(|f| {
                 let name = &f.name;
                 quote!(#name.to_string())
             })
             .collect();
 
+        let clustering_keys: Vec<TokenStream> = clustering_keys
+            .iter()
+            .map(|(f, order)| {
+                let name = &f.name;
+                quote!((#name.to_string(), #order))
+            })
+            .collect();
+
+        // the column name of every field, used by `PartialEntity` to map and check its fields
+        let columns: Vec<TokenStream> = fields
+            .iter()
+            .map(|f| {
+                let name = &f.name;
+                let column = column_const(f.ident.as_ref().unwrap());
+                quote!(pub const #column: &'static str = #name;)
+            })
+            .collect();
+
+        // the column names in order, used by read queries to check the columns they select
+        let names = fields.iter().map(|f| &f.name);
+
         let spat = quote! {
             impl scyllax::prelude::EntityExt<#ident> for #ident {
                 fn keys() -> Vec<String> {
@@ -123,15 +356,91 @@ impl ToTokens for EntityDerive {
                 }
 
                 fn pks() -> Vec<String> {
-                    vec![#(#primary_keys),*]
+                    let mut pks = Self::partition_keys();
+                    pks.extend(Self::clustering_keys().into_iter().map(|(key, _)| key));
+                    pks
+                }
+
+                fn partition_keys() -> Vec<String> {
+                    vec![#(#partition_keys),*]
+                }
+
+                fn clustering_keys() -> Vec<(String, scyllax::entity::ClusteringOrder)> {
+                    vec![#(#clustering_keys),*]
                 }
             }
+
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            impl #ident {
+                #(#columns)*
+                pub const __scyllax_columns: &'static [&'static str] = &[#(#names),*];
+            }
         };
 
         tokens.extend(spat);
     }
 }
 
+/// Strips an `Option` from a type, eg: `Option<Vec<String>>` -> `Vec<String>`
+pub(crate) fn unwrap_option(ty: &Type) -> &Type {
+    option_inner(ty).unwrap_or(ty)
+}
+
+/// Gets the inner type of an `Option`, eg: `Option<i32>` -> `i32`
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    match type_arguments(ty, &["Option"]).as_deref() {
+        Some([inner]) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Gets the key and value types of a map type, eg: `HashMap<String, i32>` -> `(String, i32)`
+pub(crate) fn map_types(ty: &Type) -> Option<(&Type, &Type)> {
+    match type_arguments(unwrap_option(ty), &["HashMap", "BTreeMap"]).as_deref() {
+        Some([key, value]) => Some((key, value)),
+        _ => None,
+    }
+}
+
+/// Gets the element type of a list or set type, eg: `Vec<String>` -> `String`
+fn element_type<'a>(ty: &'a Type, names: &[&str]) -> Option<&'a Type> {
+    match type_arguments(unwrap_option(ty), names).as_deref() {
+        Some([element]) => Some(element),
+        _ => None,
+    }
+}
+
+/// Gets the type arguments of a type, if the last segment of its path is one of `names`
+fn type_arguments<'a>(ty: &'a Type, names: &[&str]) -> Option<Vec<&'a Type>> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if !names.iter().any(|name| segment.ident == name) {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    Some(
+        arguments
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+/// The hidden associated const holding the column name of an entity's field
+pub(crate) fn column_const(field: &Ident) -> Ident {
+    format_ident!("__scyllax_column_{}", field)
+}
+
 /// Attribute expand
 /// Just adds the dervie macro to the struct.
 pub fn expand(input: TokenStream) -> TokenStream {
@@ -146,8 +455,114 @@ pub fn expand(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Derive expand of `EntityDdl`
+/// Renders the `create table` statement from the column types, the keys are taken from `EntityExt`.
+pub fn expand_ddl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = match syn::parse2(input) {
+        Ok(it) => it,
+        Err(e) => return e.to_compile_error(),
+    };
+    let entity = match EntityDerive::from_derive_input(&input) {
+        Ok(e) => e,
+        Err(e) => return e.write_errors(),
+    };
+
+    ddl_impl(&entity)
+}
+
+fn ddl_impl(entity: &EntityDerive) -> TokenStream {
+    let ident = &entity.ident;
+    let fields = entity
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Should never be enum")
+        .fields;
+
+    let columns: Vec<TokenStream> = fields
+        .iter()
+        .map(|f| {
+            let name = &f.name;
+            let ty = &f.ty;
+            let column = quote! {
+                scyllax::ddl::Column::new(#name, <#ty as scyllax::prelude::CqlType>::cql_type())
+            };
+
+            match f.static_column {
+                true => quote!(#column.static_column()),
+                false => column,
+            }
+        })
+        .collect();
+
+    quote! {
+        impl scyllax::prelude::EntityDdl<#ident> for #ident {
+            fn create_table_cql(table: &str) -> String {
+                use scyllax::prelude::EntityExt;
+
+                scyllax::ddl::create_table_cql(
+                    table,
+                    &[#(#columns),*],
+                    &Self::partition_keys(),
+                    &Self::clustering_keys(),
+                )
+            }
+        }
+    }
+}
+
+/// The options of the shorthand attribute
+#[derive(Debug, Default, FromMeta)]
+pub struct EntityOptions {
+    /// The table of the entity, required by `crud`
+    pub table: Option<String>,
+    /// Generates the get, delete and upsert queries by primary key
+    #[darling(default)]
+    pub crud: bool,
+    /// Derives `EntityDdl`, rendering the `create table` statement of the entity
+    #[darling(default)]
+    pub ddl: bool,
+}
+
 /// Expands the shorthand attribute
-pub fn expand_attr(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn expand_attr(args: TokenStream, input: TokenStream) -> TokenStream {
+    let attr_args = match NestedMeta::parse_meta_list(args) {
+        Ok(args) => args,
+        Err(e) => return darling::Error::from(e).write_errors(),
+    };
+    let options = match EntityOptions::from_list(&attr_args) {
+        Ok(o) => o,
+        Err(e) => return e.write_errors(),
+    };
+
+    let ddl = options.ddl.then(|| quote!(scyllax::prelude::EntityDdl));
+    let input = match options {
+        EntityOptions {
+            table: Some(table),
+            crud: true,
+            ..
+        } => {
+            let entity = match syn::parse2(input.clone())
+                .map_err(darling::Error::from)
+                .and_then(|input: DeriveInput| EntityDerive::from_derive_input(&input))
+            {
+                Ok(entity) => entity,
+                Err(e) => return e.write_errors(),
+            };
+
+            crate::queries::crud::expand(&table, input, &entity)
+        }
+        EntityOptions {
+            table: None,
+            crud: true,
+            ..
+        } => {
+            return syn::Error::new(Span::call_site(), "`crud` requires a `table`")
+                .to_compile_error()
+        }
+        _ => input,
+    };
+
     quote! {
         #[derive(
             Clone,
@@ -156,8 +571,99 @@ pub fn expand_attr(_args: TokenStream, input: TokenStream) -> TokenStream {
             scylla::SerializeRow,
             scylla_reexports::FromRow,
             scylla_reexports::ValueList,
-            scyllax::prelude::Entity
+            scyllax::prelude::Entity,
+            #ddl
         )]
         #input
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_entity(input: TokenStream) -> String {
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        EntityDerive::from_derive_input(&input)
+            .unwrap()
+            .into_token_stream()
+            .to_string()
+    }
+
+    #[test]
+    fn test_collection_types() {
+        let expanded = expand_entity(quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: uuid::Uuid,
+                #[entity(collection = "append")]
+                pub email: String,
+            }
+        });
+        assert!(expanded.contains("`append` can only be used on list or set fields"));
+
+        let expanded = expand_entity(quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: uuid::Uuid,
+                #[entity(collection = "prepend")]
+                pub tags: HashSet<String>,
+            }
+        });
+        assert!(expanded.contains("`prepend` can only be used on list fields"));
+
+        let expanded = expand_entity(quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: uuid::Uuid,
+                #[entity(collection = "put")]
+                pub tags: Vec<String>,
+            }
+        });
+        assert!(expanded.contains("`put` can only be used on map fields"));
+
+        let expanded = expand_entity(quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: uuid::Uuid,
+                #[entity(collection = "remove")]
+                pub tags: Option<BTreeSet<String>>,
+                #[entity(collection = "prepend")]
+                pub history: Vec<String>,
+                #[entity(collection = "put")]
+                pub attrs: HashMap<String, i32>,
+            }
+        });
+        assert!(!expanded.contains("compile_error"));
+    }
+
+    #[test]
+    fn test_ddl() {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PostCommentEntity {
+                #[entity(partition_key)]
+                pub post_id: uuid::Uuid,
+                #[entity(clustering_key)]
+                pub id: uuid::Uuid,
+                pub body: Email,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+
+        // the column types are only needed by `EntityDdl`
+        assert!(!entity.to_token_stream().to_string().contains("CqlType"));
+
+        let body = quote! {
+            scyllax::ddl::Column::new("\"body\"", <Email as scyllax::prelude::CqlType>::cql_type())
+        };
+        let ddl = ddl_impl(&entity).to_string();
+        assert!(ddl.contains("impl scyllax :: prelude :: EntityDdl < PostCommentEntity >"));
+        assert!(ddl.contains(&body.to_string()));
+
+        let expanded = expand_attr(quote!(ddl), input.to_token_stream()).to_string();
+        assert!(expanded.contains("scyllax :: prelude :: EntityDdl"));
+        let expanded = expand_attr(quote!(), input.to_token_stream()).to_string();
+        assert!(!expanded.contains("EntityDdl"));
+    }
+}
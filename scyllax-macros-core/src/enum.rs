@@ -1,10 +1,46 @@
+use darling::{ast::NestedMeta, FromMeta};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use syn::ItemEnum;
 
-pub fn expand_attr(_args: TokenStream, input: TokenStream) -> TokenStream {
+/// The options of `#[int_enum(...)]`
+#[derive(Debug, Default, FromMeta)]
+pub struct IntEnumOptions {
+    /// The variant holding the values without a variant, eg: `Unknown(i32)`
+    pub fallback: Option<syn::Ident>,
+    /// The matching prost enumeration, converted to and from with the `grpc` feature
+    pub proto: Option<syn::Path>,
+}
+
+impl IntEnumOptions {
+    /// Reads the options from the `#[int_enum(...)]` attribute of the enum, if any
+    fn from_attrs(attrs: &[syn::Attribute]) -> darling::Result<Self> {
+        match attrs.iter().find(|attr| attr.path().is_ident("int_enum")) {
+            Some(attr) => Self::from_meta(&attr.meta),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+pub fn expand_attr(args: TokenStream, input: TokenStream) -> TokenStream {
+    let attr_args = match NestedMeta::parse_meta_list(args.clone()) {
+        Ok(args) => args,
+        Err(e) => return darling::Error::from(e).write_errors(),
+    };
+    let options = match IntEnumOptions::from_list(&attr_args) {
+        Ok(o) => o,
+        Err(e) => return e.write_errors(),
+    };
+
+    // the fallback variant holds a value, so the discriminants need an explicit representation
+    let repr = options.fallback.map(|_| quote!(#[repr(i32)]));
+    // the options are read by the derive
+    let options = (!args.is_empty()).then(|| quote!(#[int_enum(#args)]));
+
     quote! {
         #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, scyllax::prelude::IntEnum)]
+        #repr
+        #options
         #input
     }
 }
@@ -64,8 +100,39 @@ pub fn expand(input: TokenStream) -> TokenStream {
         Err(e) => return e.to_compile_error(),
     };
     let ident = &input.ident;
+    let enum_name = ident.to_string();
+    let options = match IntEnumOptions::from_attrs(&input.attrs) {
+        Ok(o) => o,
+        Err(e) => return e.write_errors(),
+    };
+
+    let fallback = match &options.fallback {
+        Some(fallback) => match input.variants.iter().find(|v| &v.ident == fallback) {
+            Some(variant) if is_fallback(variant) => Some(&variant.ident),
+            Some(variant) => {
+                return syn::Error::new_spanned(
+                    variant.into_token_stream(),
+                    "The fallback variant must hold the unknown value, for example `Unknown(i32)`",
+                )
+                .into_compile_error();
+            }
+            None => {
+                return syn::Error::new_spanned(
+                    fallback,
+                    format!("`{fallback}` isn't a variant of {ident}"),
+                )
+                .into_compile_error();
+            }
+        },
+        None => None,
+    };
+    let variants = input
+        .variants
+        .iter()
+        .filter(|v| Some(&v.ident) != fallback)
+        .collect::<Vec<_>>();
 
-    for variant in input.variants.iter() {
+    for variant in variants.iter() {
         match &variant.discriminant {
             Some(_) => (),
             None => {
@@ -78,7 +145,7 @@ pub fn expand(input: TokenStream) -> TokenStream {
         }
     }
 
-    let try_from_fields = input.variants.iter().map(|variant| {
+    let try_from_fields = variants.iter().map(|variant| {
         let variant_ident = &variant.ident;
         let variant_value = match &variant.discriminant {
             Some((_, expr)) => expr,
@@ -89,21 +156,26 @@ pub fn expand(input: TokenStream) -> TokenStream {
         }
     });
 
+    let try_from_fallback = match fallback {
+        Some(fallback) => quote!(value => Ok(#ident::#fallback(value)),),
+        None => quote!(_ => Err(scyllax::prelude::InvalidEnumValue::new(#enum_name, value)),),
+    };
+
     let try_from = quote! {
         impl std::convert::TryFrom<i32> for #ident {
-            type Error = Box<dyn std::error::Error>;
+            type Error = scyllax::prelude::InvalidEnumValue;
 
             #[doc = "Converts integer values to enum values"]
             fn try_from(value: i32) -> Result<Self, Self::Error> {
                 match value {
                     #(#try_from_fields)*
-                    _ => Err(anyhow::anyhow!("Invalid #ident").into()),
+                    #try_from_fallback
                 }
             }
         }
     };
 
-    let to_int_fields = input.variants.iter().map(|variant| {
+    let to_int_fields = variants.iter().map(|variant| {
         let variant_ident = &variant.ident;
         let variant_value = match &variant.discriminant {
             Some((_, expr)) => expr,
@@ -114,12 +186,15 @@ pub fn expand(input: TokenStream) -> TokenStream {
         }
     });
 
+    let to_int_fallback = fallback.map(|fallback| quote!(Self::#fallback(value) => *value,));
+
     let to_int = quote! {
         impl #ident {
             #[doc = "Converts enum values to integer value"]
             fn to_int(&self) -> i32 {
                 match self {
                     #(#to_int_fields)*
+                    #to_int_fallback
                 }
             }
         }
@@ -156,6 +231,21 @@ pub fn expand(input: TokenStream) -> TokenStream {
                 <i32 as scylla::serialize::value::SerializeCql>::serialize(&self.to_int(), typ, writer)
             }
         }
+
+        impl scyllax::prelude::CqlType for #ident {
+            fn cql_type() -> String {
+                "int".to_string()
+            }
+        }
+    };
+
+    let proto = match &options.proto {
+        Some(proto) if cfg!(feature = "grpc") => impl_proto(ident, proto, fallback.is_some()),
+        Some(proto) => {
+            return syn::Error::new_spanned(proto, "`proto` needs the `grpc` feature of scyllax")
+                .into_compile_error()
+        }
+        None => quote! {},
     };
 
     quote! {
@@ -164,5 +254,167 @@ pub fn expand(input: TokenStream) -> TokenStream {
         #to_int
 
         #scylla
+
+        #proto
+    }
+}
+
+/// Whether a variant can hold the unknown values, like `Unknown(i32)`
+fn is_fallback(variant: &syn::Variant) -> bool {
+    matches!(&variant.fields, syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+}
+
+/// Implements the conversions to and from the matching prost enumeration, through their `i32` values.
+/// With a fallback, every prost value can be converted, but unknown values can't be converted back.
+fn impl_proto(ident: &syn::Ident, proto: &syn::Path, has_fallback: bool) -> TokenStream {
+    let proto_name = proto.segments.last().unwrap().ident.to_string();
+
+    let from_proto = if has_fallback {
+        quote! {
+            impl From<#proto> for #ident {
+                fn from(value: #proto) -> Self {
+                    Self::try_from(value as i32).expect("the fallback holds every value")
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl TryFrom<#proto> for #ident {
+                type Error = scyllax::prelude::InvalidEnumValue;
+
+                fn try_from(value: #proto) -> Result<Self, Self::Error> {
+                    Self::try_from(value as i32)
+                }
+            }
+        }
+    };
+
+    quote! {
+        #from_proto
+
+        impl TryFrom<#ident> for #proto {
+            type Error = scyllax::prelude::InvalidEnumValue;
+
+            fn try_from(value: #ident) -> Result<Self, Self::Error> {
+                let value = value.to_int();
+                #proto::try_from(value)
+                    .map_err(|_| scyllax::prelude::InvalidEnumValue::new(#proto_name, value))
+            }
+        }
+
+        impl From<#ident> for i32 {
+            #[doc = "The value of the enum, as stored in prost messages"]
+            fn from(value: #ident) -> Self {
+                value.to_int()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_error() {
+        let expanded = expand(quote! {
+            pub enum PersonKind {
+                Staff = 0,
+                Parent = 1,
+            }
+        })
+        .to_string();
+
+        let error = quote! {
+            _ => Err(scyllax::prelude::InvalidEnumValue::new("PersonKind", value)),
+        };
+        assert!(expanded.contains(&error.to_string()));
+    }
+
+    #[test]
+    fn test_fallback() {
+        let expanded = expand_attr(
+            quote!(fallback = Unknown),
+            quote! {
+                pub enum PersonKind {
+                    Staff = 0,
+                    Unknown(i32),
+                }
+            },
+        );
+        let expected = quote! {
+            #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, scyllax::prelude::IntEnum)]
+            #[repr(i32)]
+            #[int_enum(fallback = Unknown)]
+            pub enum PersonKind {
+                Staff = 0,
+                Unknown(i32),
+            }
+        };
+        assert_eq!(expanded.to_string(), expected.to_string());
+
+        let expanded = expand(expected).to_string();
+        let try_from = quote! {
+            match value {
+                0 => Ok(PersonKind::Staff),
+                value => Ok(PersonKind::Unknown(value)),
+            }
+        };
+        assert!(expanded.contains(&try_from.to_string()));
+        let to_int = quote! {
+            match self {
+                Self::Staff => 0,
+                Self::Unknown(value) => *value,
+            }
+        };
+        assert!(expanded.contains(&to_int.to_string()));
+    }
+
+    #[test]
+    fn test_fallback_errors() {
+        let expanded = expand(quote! {
+            #[int_enum(fallback = Other)]
+            pub enum PersonKind {
+                Staff = 0,
+                Unknown(i32),
+            }
+        })
+        .to_string();
+        assert!(expanded.contains("`Other` isn't a variant of PersonKind"));
+
+        let expanded = expand(quote! {
+            #[int_enum(fallback = Unknown)]
+            pub enum PersonKind {
+                Staff = 0,
+                Unknown,
+            }
+        })
+        .to_string();
+        assert!(expanded.contains(
+            "The fallback variant must hold the unknown value, for example `Unknown(i32)`"
+        ));
+    }
+
+    #[test]
+    fn test_proto() {
+        let ident = syn::parse_quote!(PersonKind);
+        let proto = syn::parse_quote!(crate::proto::PersonKind);
+
+        let expanded = impl_proto(&ident, &proto, true).to_string();
+        let from_proto = quote! {
+            impl From<crate::proto::PersonKind> for PersonKind
+        };
+        assert!(expanded.contains(&from_proto.to_string()));
+        let to_proto = quote! {
+            crate::proto::PersonKind::try_from(value)
+                .map_err(|_| scyllax::prelude::InvalidEnumValue::new("PersonKind", value))
+        };
+        assert!(expanded.contains(&to_proto.to_string()));
+
+        let expanded = impl_proto(&ident, &proto, false).to_string();
+        let try_from_proto = quote! {
+            impl TryFrom<crate::proto::PersonKind> for PersonKind
+        };
+        assert!(expanded.contains(&try_from_proto.to_string()));
     }
 }
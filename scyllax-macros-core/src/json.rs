@@ -56,6 +56,12 @@ pub fn expand(input: TokenStream) -> TokenStream {
                 <String as scylla::serialize::value::SerializeCql>::serialize(&data, typ, writer)
             }
         }
+
+        impl scyllax::prelude::CqlType for #ident {
+            fn cql_type() -> String {
+                "text".to_string()
+            }
+        }
     };
 
     expanded
@@ -4,5 +4,9 @@
 pub mod entity;
 pub mod r#enum;
 pub mod json;
+pub mod partial;
 pub mod prepare;
 pub mod queries;
+pub mod schema;
+pub mod text_enum;
+pub mod udt;
@@ -0,0 +1,222 @@
+//! `PartialEntity` derive: a struct reading a subset of an entity's columns.
+use darling::{ast, util, FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{spanned::Spanned, DeriveInput, Ident, Type};
+
+use crate::entity::column_const;
+
+#[derive(Debug, PartialEq, FromField)]
+#[darling(attributes(partial))]
+pub struct PartialEntityColumn {
+    pub ident: Option<Ident>,
+    pub ty: Type,
+    /// Must match the rename of the entity's field, if it has one.
+    #[darling(default)]
+    pub rename: Option<String>,
+}
+
+#[derive(Debug, PartialEq, FromDeriveInput)]
+#[darling(attributes(partial), supports(struct_named))]
+pub struct PartialEntityDerive {
+    pub ident: Ident,
+    pub data: ast::Data<util::Ignored, PartialEntityColumn>,
+    /// The entity this is a partial of
+    pub of: syn::Path,
+}
+
+impl ToTokens for PartialEntityDerive {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let PartialEntityDerive {
+            ref ident,
+            ref data,
+            ref of,
+        } = *self;
+
+        let fields = data
+            .as_ref()
+            .take_struct()
+            .expect("Should never be enum")
+            .fields;
+
+        // the column names come from the entity, so renames only have to be written once
+        let keys: Vec<TokenStream> = fields
+            .iter()
+            .map(|f| {
+                let column = column_const(f.ident.as_ref().unwrap());
+                quote_spanned!(f.ident.span()=> #of::#column.to_string())
+            })
+            .collect();
+
+        let columns: Vec<TokenStream> = fields
+            .iter()
+            .map(|f| {
+                let column = column_const(f.ident.as_ref().unwrap());
+                quote_spanned!(f.ident.span()=> #of::#column)
+            })
+            .collect();
+
+        // assigning every field to the entity checks that it has the field, with the same type
+        let assignments: Vec<TokenStream> = fields
+            .iter()
+            .map(|f| {
+                let field = f.ident.as_ref().unwrap();
+                quote_spanned!(f.ty.span()=> entity.#field = partial.#field;)
+            })
+            .collect();
+
+        let renames: Vec<TokenStream> = fields
+            .iter()
+            .filter_map(|f| {
+                let field = f.ident.as_ref().unwrap();
+                let rename = f.rename.as_ref()?;
+                let name = format!(r##""{rename}""##);
+                let column = column_const(field);
+                let message = format!(
+                    "Field `{field}` is renamed to `{rename}`, which doesn't match the rename of `{}::{field}`",
+                    of.to_token_stream().to_string().replace(' ', ""),
+                );
+
+                Some(quote_spanned! {f.ident.span()=>
+                    assert!(scyllax::entity::__column_eq(#of::#column, #name), #message);
+                })
+            })
+            .collect();
+
+        tokens.extend(quote! {
+            impl scyllax::prelude::EntityExt<#ident> for #ident {
+                fn keys() -> Vec<String> {
+                    vec![#(#keys),*]
+                }
+
+                fn pks() -> Vec<String> {
+                    let keys = Self::keys();
+                    <#of as scyllax::prelude::EntityExt<#of>>::pks()
+                        .into_iter()
+                        .filter(|pk| keys.contains(pk))
+                        .collect()
+                }
+
+                fn partition_keys() -> Vec<String> {
+                    let keys = Self::keys();
+                    <#of as scyllax::prelude::EntityExt<#of>>::partition_keys()
+                        .into_iter()
+                        .filter(|pk| keys.contains(pk))
+                        .collect()
+                }
+
+                fn clustering_keys() -> Vec<(String, scyllax::entity::ClusteringOrder)> {
+                    let keys = Self::keys();
+                    <#of as scyllax::prelude::EntityExt<#of>>::clustering_keys()
+                        .into_iter()
+                        .filter(|(ck, _)| keys.contains(ck))
+                        .collect()
+                }
+            }
+
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            impl #ident {
+                pub const __scyllax_columns: &'static [&'static str] = &[#(#columns),*];
+            }
+
+            const _: () = {
+                #[allow(dead_code)]
+                fn check(mut entity: #of, partial: #ident) {
+                    #(#assignments)*
+                }
+
+                #(#renames)*
+            };
+        });
+    }
+}
+
+/// Derive expand
+pub fn expand(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = match syn::parse2(input.clone()) {
+        Ok(it) => it,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    match PartialEntityDerive::from_derive_input(&input) {
+        Ok(e) => e.into_token_stream(),
+        Err(e) => e.write_errors(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_partial_entity() {
+        let input = quote! {
+            #[partial(of = PersonEntity)]
+            pub struct PersonEmail {
+                pub id: uuid::Uuid,
+                #[partial(rename = "emailAddress")]
+                pub email: String,
+            }
+        };
+
+        let expected = quote! {
+            impl scyllax::prelude::EntityExt<PersonEmail> for PersonEmail {
+                fn keys() -> Vec<String> {
+                    vec![
+                        PersonEntity::__scyllax_column_id.to_string(),
+                        PersonEntity::__scyllax_column_email.to_string()
+                    ]
+                }
+
+                fn pks() -> Vec<String> {
+                    let keys = Self::keys();
+                    <PersonEntity as scyllax::prelude::EntityExt<PersonEntity>>::pks()
+                        .into_iter()
+                        .filter(|pk| keys.contains(pk))
+                        .collect()
+                }
+
+                fn partition_keys() -> Vec<String> {
+                    let keys = Self::keys();
+                    <PersonEntity as scyllax::prelude::EntityExt<PersonEntity>>::partition_keys()
+                        .into_iter()
+                        .filter(|pk| keys.contains(pk))
+                        .collect()
+                }
+
+                fn clustering_keys() -> Vec<(String, scyllax::entity::ClusteringOrder)> {
+                    let keys = Self::keys();
+                    <PersonEntity as scyllax::prelude::EntityExt<PersonEntity>>::clustering_keys()
+                        .into_iter()
+                        .filter(|(ck, _)| keys.contains(ck))
+                        .collect()
+                }
+            }
+
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            impl PersonEmail {
+                pub const __scyllax_columns: &'static [&'static str] = &[
+                    PersonEntity::__scyllax_column_id,
+                    PersonEntity::__scyllax_column_email
+                ];
+            }
+
+            const _: () = {
+                #[allow(dead_code)]
+                fn check(mut entity: PersonEntity, partial: PersonEmail) {
+                    entity.id = partial.id;
+                    entity.email = partial.email;
+                }
+
+                assert!(
+                    scyllax::entity::__column_eq(PersonEntity::__scyllax_column_email, "\"emailAddress\""),
+                    "Field `email` is renamed to `emailAddress`, which doesn't match the rename of `PersonEntity::email`"
+                );
+            };
+        };
+
+        assert_eq!(expand(input).to_string(), expected.to_string());
+    }
+}
@@ -7,48 +7,71 @@ use syn::{
     ExprArray,
 };
 
+use crate::queries::crud::crud_names;
+
 // // prepare_queries!(PersonQueries, [GetPersonById, GetPeopleByIds, DeletePersonById, ...]);
 /// Options for the `prepare_queries!` macro.
 pub struct PrepareQueriesInput {
     /// The name of the struct to generate.
     pub name: syn::Ident,
     /// The queries to attach to the struct.
-    pub read_queries: Vec<syn::Ident>,
+    pub read_queries: Vec<syn::Path>,
     /// Write queries to attach to the struct.
-    pub write_queries: Vec<syn::Ident>,
+    pub write_queries: Vec<syn::Path>,
+}
+
+/// Parses an array of paths, eg: `[GetPersonById, super::DeletePersonById]`
+fn parse_paths(input: ParseStream<'_>) -> syn::Result<Vec<syn::Path>> {
+    input
+        .parse::<ExprArray>()?
+        .elems
+        .iter()
+        .map(|expr| {
+            if let syn::Expr::Path(path) = expr {
+                Ok(path.path.clone())
+            } else {
+                Err(syn::Error::new_spanned(expr, "expected an identifier"))
+            }
+        })
+        .collect()
 }
 
 impl Parse for PrepareQueriesInput {
     fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
         let name = input.parse()?;
         input.parse::<syn::Token![,]>()?;
+        let mut read_queries = parse_paths(input)?;
+        input.parse::<syn::Token![,]>()?;
+        let mut write_queries = parse_paths(input)?;
 
-        let read_queries = input
-            .parse::<ExprArray>()?
-            .elems
-            .iter()
-            .map(|expr| {
-                if let syn::Expr::Path(path) = expr {
-                    Ok(path.path.get_ident().unwrap().clone())
-                } else {
-                    Err(syn::Error::new_spanned(expr, "expected an identifier"))
-                }
-            })
-            .collect::<syn::Result<Vec<_>>>()?;
+        // `crud = [PersonEntity]` adds the queries generated by `#[entity(table = "...", crud)]`
+        if input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let key = input.parse::<syn::Ident>()?;
+            if key != "crud" {
+                return Err(syn::Error::new_spanned(key, "expected `crud`"));
+            }
+            input.parse::<syn::Token![=]>()?;
 
-        input.parse::<syn::Token![,]>()?;
-        let write_queries = input
-            .parse::<ExprArray>()?
-            .elems
-            .iter()
-            .map(|expr| {
-                if let syn::Expr::Path(path) = expr {
-                    Ok(path.path.get_ident().unwrap().clone())
-                } else {
-                    Err(syn::Error::new_spanned(expr, "expected an identifier"))
-                }
-            })
-            .collect::<syn::Result<Vec<_>>>()?;
+            for entity in parse_paths(input)? {
+                let mut segments = entity.segments.clone();
+                let last = segments.pop().unwrap().into_value();
+                let [get, delete, upsert] = crud_names(&last.ident);
+                let path = |ident: syn::Ident| {
+                    let mut path = syn::Path {
+                        leading_colon: entity.leading_colon,
+                        segments: segments.clone(),
+                    };
+                    path.segments.push(ident.into());
+                    path
+                };
+
+                read_queries.push(path(get));
+                write_queries.push(path(delete));
+                write_queries.push(path(upsert));
+            }
+
+            input.parse::<Option<syn::Token![,]>>()?;
+        }
 
         Ok(Self {
             name,
@@ -58,6 +81,12 @@ impl Parse for PrepareQueriesInput {
     }
 }
 
+/// The name of the collection's field for a query, eg: `super::GetPersonById` -> `get_person_by_id`
+fn field_name(query: &syn::Path, suffix: &str) -> syn::Ident {
+    let ident = &query.segments.last().unwrap().ident;
+    format_ident!("{}{}", ident.to_string().to_case(Case::Snake), suffix)
+}
+
 // prepare_queries!(PersonQueries, [GetPersonById, GetPeopleByIds, DeletePersonById, ...]);
 // creates a struct like this:
 // pub struct PersonQueries {
@@ -74,13 +103,15 @@ pub fn expand(input: TokenStream) -> TokenStream {
     };
     let read_queries = args.read_queries;
     let write_queries = args.write_queries;
-    let queries: Vec<&proc_macro2::Ident> =
-        read_queries.iter().chain(write_queries.iter()).collect();
+    let queries: Vec<&syn::Path> = read_queries.iter().chain(write_queries.iter()).collect();
     let name = args.name;
 
     let prepared_statements = queries.iter().map(|field| {
-        let doc = format!("The prepared statement for `{}`.", field);
-        let prop = format_ident!("{}", field.to_string().to_case(Case::Snake));
+        let doc = format!(
+            "The prepared statement for `{}`.",
+            field.to_token_stream().to_string().replace(" ", "")
+        );
+        let prop = field_name(field, "");
         quote! {
             #[allow(non_snake_case)]
             #[doc = #doc]
@@ -89,7 +120,7 @@ pub fn expand(input: TokenStream) -> TokenStream {
     });
 
     let get_prepared_statements = queries.iter().map(|field| {
-        let prop = format_ident!("{}", field.to_string().to_case(Case::Snake));
+        let prop = field_name(field, "");
         quote! {
             impl scyllax::prelude::GetPreparedStatement<#field> for #name {
                 #[doc = "Get a prepared statement."]
@@ -101,16 +132,18 @@ pub fn expand(input: TokenStream) -> TokenStream {
     });
 
     let prepares = queries.iter().map(|field| {
-        let prop = format_ident!("{}", field.to_string().to_case(Case::Snake));
+        let prop = field_name(field, "");
         quote! {
-            #prop: scyllax::prelude::prepare_query(&session, #field::query(), stringify!(#field)).await?,
+            #prop: scyllax::prelude::prepare_query::<#field>(&session).await?,
         }
     });
 
     let coalescing_senders = read_queries.iter().map(|field| {
-        let doc = format!("The coalescing read task for `{}`.", field);
-        let prop =
-            format_ident!("{}_task", field.to_string().to_case(Case::Snake)).to_token_stream();
+        let doc = format!(
+            "The coalescing read task for `{}`.",
+            field.to_token_stream().to_string().replace(" ", "")
+        );
+        let prop = field_name(field, "_task");
         quote! {
             #[allow(non_snake_case)]
             #[doc = #doc]
@@ -118,8 +151,21 @@ pub fn expand(input: TokenStream) -> TokenStream {
         }
     });
 
+    let query_runners = read_queries.iter().map(|field| {
+        let doc = format!(
+            "The query runner executing the coalesced reads of `{}`.",
+            field.to_token_stream().to_string().replace(" ", "")
+        );
+        let prop = field_name(field, "_runner");
+        quote! {
+            #[allow(non_snake_case)]
+            #[doc = #doc]
+            pub #prop: Option<std::sync::Arc<tokio::task::JoinHandle<()>>>,
+        }
+    });
+
     let get_coalescing_senders = read_queries.iter().map(|field| {
-        let prop = format_ident!("{}_task", field.to_string().to_case(Case::Snake)).to_token_stream();
+        let prop = field_name(field, "_task");
         quote! {
             impl scyllax::prelude::GetCoalescingSender<#field> for #name {
                 #[doc = "Get a task."]
@@ -131,18 +177,19 @@ pub fn expand(input: TokenStream) -> TokenStream {
     });
 
     let create_empty_senders = read_queries.iter().map(|field| {
-        let prop =
-            format_ident!("{}_task", field.to_string().to_case(Case::Snake)).to_token_stream();
+        let prop = field_name(field, "_task");
+        let runner = field_name(field, "_runner");
         quote! {
             #prop: None,
+            #runner: None,
         }
     });
 
     let create_senders = read_queries.iter().map(|field| {
-        let prop =
-            format_ident!("{}_task", field.to_string().to_case(Case::Snake)).to_token_stream();
+        let prop = field_name(field, "_task");
+        let runner = field_name(field, "_runner");
         quote! {
-            self.#prop = {
+            (self.#prop, self.#runner) = {
                 let (task_transmitter, task_receiver) = mpsc::channel(1024);
                 let (queryrunner_transmitter, queryrunner_receiver) = mpsc::channel(8);
 
@@ -152,15 +199,71 @@ pub fn expand(input: TokenStream) -> TokenStream {
                 });
 
                 let ex = executor.clone();
-                tokio::spawn(async move {
+                let runner = tokio::spawn(async move {
                     ex.read_query_runner::<#field>(queryrunner_receiver).await;
                 });
 
-                Some(task_transmitter)
+                (Some(task_transmitter), Some(std::sync::Arc::new(runner)))
             };
         }
     });
 
+    let read_descriptions = read_queries.iter().map(|field| {
+        let task = field_name(field, "_task");
+        quote! {
+            scyllax::health::QueryDescription {
+                query_type: std::any::type_name::<#field>(),
+                statement: <#field as scyllax::prelude::Query>::query(),
+                kind: scyllax::prelude::QueryKind::Read,
+                coalesce: <#field as scyllax::prelude::ReadQuery>::coalesce(),
+                queue_capacity: self.#task.as_ref().map(|task| task.max_capacity()),
+            },
+        }
+    });
+
+    let write_descriptions = write_queries.iter().map(|field| {
+        quote! {
+            scyllax::health::QueryDescription {
+                query_type: std::any::type_name::<#field>(),
+                statement: <#field as scyllax::prelude::Query>::query(),
+                kind: scyllax::prelude::QueryKind::Write,
+                coalesce: false,
+                queue_capacity: None,
+            },
+        }
+    });
+
+    let read_statuses = read_queries.iter().map(|field| {
+        let prop = field_name(field, "");
+        let task = field_name(field, "_task");
+        let runner = field_name(field, "_runner");
+        quote! {
+            scyllax::health::QueryStatus {
+                query_type: std::any::type_name::<#field>(),
+                kind: scyllax::prelude::QueryKind::Read,
+                prepared: !self.#prop.get_id().is_empty(),
+                task_alive: Some(
+                    self.#task.as_ref().is_some_and(|task| !task.is_closed())
+                        && self.#runner.as_ref().is_some_and(|runner| !runner.is_finished()),
+                ),
+                queued: self.#task.as_ref().map(|task| task.max_capacity() - task.capacity()),
+            },
+        }
+    });
+
+    let write_statuses = write_queries.iter().map(|field| {
+        let prop = field_name(field, "");
+        quote! {
+            scyllax::health::QueryStatus {
+                query_type: std::any::type_name::<#field>(),
+                kind: scyllax::prelude::QueryKind::Write,
+                prepared: !self.#prop.get_id().is_empty(),
+                task_alive: None,
+                queued: None,
+            },
+        }
+    });
+
     quote! {
         #[doc = "A collection of prepared statements."]
         #[allow(non_snake_case)]
@@ -168,6 +271,7 @@ pub fn expand(input: TokenStream) -> TokenStream {
         pub struct #name {
             #(#prepared_statements)*
             #(#coalescing_senders)*
+            #(#query_runners)*
         }
 
         #[scyllax::prelude::async_trait]
@@ -186,9 +290,70 @@ pub fn expand(input: TokenStream) -> TokenStream {
 
                 self
             }
+
+            fn describe(&self) -> Vec<scyllax::health::QueryDescription> {
+                vec![
+                    #(#read_descriptions)*
+                    #(#write_descriptions)*
+                ]
+            }
+
+            fn status(&self) -> Vec<scyllax::health::QueryStatus> {
+                vec![
+                    #(#read_statuses)*
+                    #(#write_statuses)*
+                ]
+            }
         }
 
         #(#get_prepared_statements)*
         #(#get_coalescing_senders)*
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_crud() {
+        let input: PrepareQueriesInput = syn::parse_quote!(
+            PostQueries,
+            [GetPostByTitle],
+            [],
+            crud = [super::model::PostEntity]
+        );
+
+        let paths = |paths: &[syn::Path]| {
+            paths
+                .iter()
+                .map(|p| p.to_token_stream().to_string().replace(' ', ""))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            paths(&input.read_queries),
+            ["GetPostByTitle", "super::model::GetPost"]
+        );
+        assert_eq!(
+            paths(&input.write_queries),
+            ["super::model::DeletePost", "super::model::UpsertPost"]
+        );
+    }
+
+    #[test]
+    fn test_read_status() {
+        let expanded = expand(quote!(PersonQueries, [GetPersonById], [])).to_string();
+
+        let prepared = quote!(prepared: !self.get_person_by_id.get_id().is_empty(),);
+        assert!(expanded.contains(&prepared.to_string()));
+
+        let task_alive = quote! {
+            task_alive: Some(
+                self.get_person_by_id_task.as_ref().is_some_and(|task| !task.is_closed())
+                    && self.get_person_by_id_runner.as_ref().is_some_and(|runner| !runner.is_finished()),
+            ),
+        };
+        assert!(expanded.contains(&task_alive.to_string()));
+    }
+}
@@ -0,0 +1,165 @@
+//! Primary key queries generated by `#[entity(table = "...", crud)]`
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::entity::{EntityDerive, EntityDeriveColumn};
+
+/// The names of the get, delete and upsert queries generated for an entity.
+/// The `Entity` suffix is dropped, eg: `PersonEntity` -> `GetPerson`, `DeletePerson`, `UpsertPerson`
+pub fn crud_names(entity: &Ident) -> [Ident; 3] {
+    let name = entity.to_string();
+    let base = name.strip_suffix("Entity").unwrap_or(&name);
+
+    [
+        format_ident!("Get{}", base, span = entity.span()),
+        format_ident!("Delete{}", base, span = entity.span()),
+        format_ident!("Upsert{}", base, span = entity.span()),
+    ]
+}
+
+/// Generates the get and delete queries of an entity, by its full primary key,
+/// and adds the upsert query to the entity.
+pub(crate) fn expand(table: &str, input: TokenStream, entity: &EntityDerive) -> TokenStream {
+    let entity_ident = &entity.ident;
+    let [get, delete, upsert] = crud_names(entity_ident);
+    let fields = entity
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Should never be enum")
+        .fields;
+    let primary_keys: Vec<&&EntityDeriveColumn> =
+        fields.iter().filter(|f| f.is_primary_key()).collect();
+
+    if primary_keys.is_empty() {
+        return syn::Error::new_spanned(entity_ident, "`crud` entities need a primary key")
+            .to_compile_error();
+    }
+
+    let conditions = primary_keys
+        .iter()
+        .map(|f| {
+            format!(
+                "{} = :{}",
+                f.name.as_ref().unwrap(),
+                f.ident.as_ref().unwrap()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" and ");
+    let get_query = format!("select * from {table} where {conditions} limit 1");
+    let delete_query = format!("delete from {table} where {conditions}");
+    let return_type = entity_ident.to_string();
+
+    let key_fields = primary_keys
+        .iter()
+        .map(|f| {
+            let ident = &f.ident;
+            let ty = &f.ty;
+            let comment = format!("The {} of the {}", ident.as_ref().unwrap(), entity_ident);
+
+            (comment, quote!(pub #ident: #ty))
+        })
+        .collect::<Vec<_>>();
+    let get_fields = key_fields.iter().map(|(comment, field)| {
+        quote! {
+            #[doc = #comment]
+            #[read_query(coalesce_shard_key)]
+            #field
+        }
+    });
+    let delete_fields = key_fields.iter().map(|(comment, field)| {
+        quote! {
+            #[doc = #comment]
+            #field
+        }
+    });
+
+    let get_docs = format!("Gets a [`{entity_ident}`] by its primary key");
+    let delete_docs = format!("Deletes a [`{entity_ident}`] by its primary key");
+
+    quote! {
+        #[scyllax::prelude::upsert_query(table = #table, name = #upsert)]
+        #input
+
+        #[doc = #get_docs]
+        #[derive(Debug, Clone, PartialEq, scylla::SerializeRow, scyllax::prelude::ReadQuery)]
+        #[read_query(query = #get_query, return_type = #return_type)]
+        pub struct #get {
+            #(#get_fields,)*
+        }
+
+        #[doc = #delete_docs]
+        #[scyllax::prelude::write_query(query = #delete_query)]
+        pub struct #delete {
+            #(#delete_fields,)*
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use darling::FromDeriveInput;
+
+    #[test]
+    fn test_crud_names() {
+        let names = crud_names(&format_ident!("PersonEntity"));
+        assert_eq!(
+            names.map(|name| name.to_string()),
+            ["GetPerson", "DeletePerson", "UpsertPerson"]
+        );
+
+        let names = crud_names(&format_ident!("Post"));
+        assert_eq!(
+            names.map(|name| name.to_string()),
+            ["GetPost", "DeletePost", "UpsertPost"]
+        );
+    }
+
+    #[test]
+    fn test_crud_queries() {
+        let input = quote! {
+            pub struct PersonLoginEntity {
+                #[entity(primary_key)]
+                pub id: Uuid,
+                #[entity(primary_key, rename = "personId")]
+                pub person_id: Uuid,
+                pub count: i64,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&syn::parse2(input.clone()).unwrap()).unwrap();
+        let expanded = expand("person_login", input, &entity).to_string();
+
+        let get = quote! {
+            #[read_query(
+                query = "select * from person_login where \"id\" = :id and \"personId\" = :person_id limit 1",
+                return_type = "PersonLoginEntity"
+            )]
+            pub struct GetPersonLogin {
+                #[doc = "The id of the PersonLoginEntity"]
+                #[read_query(coalesce_shard_key)]
+                pub id: Uuid,
+                #[doc = "The person_id of the PersonLoginEntity"]
+                #[read_query(coalesce_shard_key)]
+                pub person_id: Uuid,
+            }
+        };
+        assert!(expanded.contains(&get.to_string()));
+
+        let delete = quote! {
+            #[scyllax::prelude::write_query(
+                query = "delete from person_login where \"id\" = :id and \"personId\" = :person_id"
+            )]
+            pub struct DeletePersonLogin
+        };
+        assert!(expanded.contains(&delete.to_string()));
+
+        let conditions = r#""id" = :id and "personId" = :person_id"#;
+        let select = format!("select * from person_login where {conditions} limit 1");
+        assert_eq!(scyllax_parser::select::parse_select(&select).unwrap().0, "");
+        let delete = format!("delete from person_login where {conditions}");
+        assert_eq!(scyllax_parser::parse_write_query(&delete).unwrap().0, "");
+    }
+}
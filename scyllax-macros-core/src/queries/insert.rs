@@ -0,0 +1,260 @@
+use darling::{ast::NestedMeta, FromDeriveInput, FromMeta};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use super::using_clause;
+use crate::{
+    entity::{EntityDerive, EntityDeriveColumn},
+    schema,
+};
+
+#[derive(FromMeta)]
+pub(crate) struct InsertQueryOptions {
+    pub name: syn::Ident,
+    pub table: String,
+    pub if_not_exists: Option<bool>,
+    pub ttl: Option<bool>,
+    pub timestamp: Option<bool>,
+}
+
+/// Attribute expand
+/// Keeps the entity as is and generates the insert struct next to it.
+pub fn expand(args: TokenStream, input: TokenStream) -> TokenStream {
+    let attr_args = match NestedMeta::parse_meta_list(args.clone()) {
+        Ok(args) => args,
+        Err(e) => return darling::Error::from(e).write_errors(),
+    };
+
+    let args = match InsertQueryOptions::from_list(&attr_args) {
+        Ok(o) => o,
+        Err(e) => return e.write_errors(),
+    };
+
+    let input: DeriveInput = match syn::parse2(input.clone()) {
+        Ok(it) => it,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let entity = match EntityDerive::from_derive_input(&input) {
+        Ok(e) => e,
+        Err(e) => return e.write_errors(),
+    };
+
+    insert_impl(&input, &args, &entity)
+}
+
+/// Create the implementation for the insert query
+pub(crate) fn insert_impl(
+    input: &DeriveInput,
+    opt: &InsertQueryOptions,
+    entity: &EntityDerive,
+) -> TokenStream {
+    let insert_struct = &opt.name;
+    let insert_table = &opt.table;
+    let struct_ident = &input.ident;
+    let keys = entity
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Should never be enum")
+        .fields;
+
+    // counters can only be incremented, never inserted
+    if let Some(counter) = keys.iter().find(|f| f.counter) {
+        return syn::Error::new_spanned(
+            &counter.ident,
+            "Entities with counter columns can't be inserted, use `upsert_query` instead",
+        )
+        .to_compile_error();
+    }
+
+    // the server rejects custom timestamps for conditional updates when the query is prepared
+    if opt.if_not_exists.unwrap_or(false) && opt.timestamp.unwrap_or(false) {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`if_not_exists` and `timestamp` can't be combined, lightweight transactions can't have custom timestamps",
+        )
+        .to_compile_error();
+    }
+
+    let schema_check = match schema::check(|schema| schema.check_upsert(insert_table, &keys)) {
+        Ok(tokens) => tokens,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let fields = keys
+        .iter()
+        .map(|f| {
+            let ident = &f.ident;
+            let ty = &f.ty;
+            let comment = format!("The {} of the {}", ident.as_ref().unwrap(), struct_ident);
+
+            quote! {
+                #[doc = #comment]
+                pub #ident: #ty
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ttl = if opt.ttl.unwrap_or(false) {
+        quote! {
+            #[doc = "The ttl of the row in seconds"]
+            pub set_ttl: i32,
+        }
+    } else {
+        quote! {}
+    };
+
+    let timestamp = if opt.timestamp.unwrap_or(false) {
+        quote! {
+            #[doc = "The write timestamp of the row in microseconds since the unix epoch"]
+            pub set_timestamp: i64,
+        }
+    } else {
+        quote! {}
+    };
+
+    let docs = format!(
+        "Inserts a {} into the `{}` table",
+        struct_ident, insert_table
+    );
+    let expanded_insert_struct = quote! {
+        #[doc = #docs]
+        #[derive(Debug, Clone, scylla::SerializeRow)]
+        pub struct #insert_struct {
+            #(#fields,)*
+            #ttl
+            #timestamp
+        }
+    };
+
+    let query = build_query(opt, &keys);
+
+    let mut bind_names = keys
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap().to_string())
+        .collect::<Vec<_>>();
+    if opt.ttl.unwrap_or(false) {
+        bind_names.push("set_ttl".to_string());
+    }
+    if opt.timestamp.unwrap_or(false) {
+        bind_names.push("set_timestamp".to_string());
+    }
+
+    quote! {
+        #input
+
+        #expanded_insert_struct
+
+        #[scyllax::prelude::async_trait]
+        impl scyllax::prelude::Query for #insert_struct {
+            fn query() -> String {
+                #query.to_string()
+            }
+
+            fn bind_names() -> &'static [&'static str] {
+                &[#(#bind_names),*]
+            }
+        }
+
+        impl scyllax::prelude::WriteQuery for #insert_struct {}
+
+        #schema_check
+    }
+}
+
+fn build_query(args: &InsertQueryOptions, keys: &[&EntityDeriveColumn]) -> String {
+    let table = &args.table;
+    let cols = keys
+        .iter()
+        .map(|f| f.name.clone().unwrap())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let named_var = keys
+        .iter()
+        .map(|f| format!(":{}", f.ident.as_ref().unwrap()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut query = format!("insert into {table} ({cols}) values ({named_var})");
+
+    if args.if_not_exists.unwrap_or(false) {
+        query.push_str(" if not exists");
+    }
+
+    query.push_str(&using_clause(
+        args.ttl.unwrap_or(false),
+        args.timestamp.unwrap_or(false),
+    ));
+
+    query.push(';');
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person() -> DeriveInput {
+        syn::parse2(quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: uuid::Uuid,
+                pub email: String,
+                #[entity(rename = "createdAt")]
+                pub created_at: i64,
+            }
+        })
+        .unwrap()
+    }
+
+    fn options(attr: TokenStream) -> InsertQueryOptions {
+        InsertQueryOptions::from_list(&NestedMeta::parse_meta_list(attr).unwrap()).unwrap()
+    }
+
+    fn build(attr: TokenStream) -> String {
+        let input = person();
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let keys = entity.data.as_ref().take_struct().unwrap().fields;
+
+        build_query(&options(attr), &keys)
+    }
+
+    #[test]
+    fn test_insert() {
+        assert_eq!(
+            build(quote!(table = "person", name = InsertPerson)),
+            "insert into person (\"id\", \"email\", \"createdAt\") values (:id, :email, :created_at);",
+        );
+    }
+
+    #[test]
+    fn test_insert_options() {
+        assert_eq!(
+            build(quote!(table = "person", name = InsertPerson, if_not_exists, ttl)),
+            "insert into person (\"id\", \"email\", \"createdAt\") values (:id, :email, :created_at) if not exists using ttl :set_ttl;",
+        );
+        assert_eq!(
+            build(quote!(table = "person", name = InsertPerson, ttl, timestamp)),
+            "insert into person (\"id\", \"email\", \"createdAt\") values (:id, :email, :created_at) using ttl :set_ttl and timestamp :set_timestamp;",
+        );
+    }
+
+    #[test]
+    fn test_insert_if_not_exists_timestamp() {
+        let input = person();
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let opt = options(quote!(
+            table = "person",
+            name = InsertPerson,
+            if_not_exists,
+            timestamp
+        ));
+
+        assert!(insert_impl(&input, &opt, &entity).to_string().contains(
+            "`if_not_exists` and `timestamp` can't be combined, lightweight transactions can't have custom timestamps"
+        ));
+    }
+}
@@ -2,15 +2,98 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::ItemStruct;
 
+pub mod crud;
+pub mod insert;
 pub mod read;
+pub mod slice;
 pub mod upsert;
 pub mod write;
 
+/// The admission limits set in a query's attributes
+#[derive(Debug, Default, PartialEq)]
+pub struct AdmissionArgs {
+    /// The number of executions allowed per second
+    pub rate_limit: Option<u32>,
+    /// The number of executions allowed in a burst, defaults to `rate_limit`
+    pub burst: Option<u32>,
+    /// The number of executions allowed in flight at once
+    pub max_in_flight: Option<usize>,
+    /// Whether executions over the limits are rejected instead of waiting
+    pub reject_when_limited: bool,
+}
+
+impl AdmissionArgs {
+    /// Implements `Query::admission`, if any limits are set.
+    fn impl_admission(&self) -> TokenStream {
+        if self.rate_limit.is_none() && self.max_in_flight.is_none() {
+            return quote!();
+        }
+        if [self.rate_limit, self.burst].contains(&Some(0)) || self.max_in_flight == Some(0) {
+            return syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`rate_limit`, `burst` and `max_in_flight` must be greater than 0",
+            )
+            .to_compile_error();
+        }
+
+        let rate_limit = match self.rate_limit {
+            Some(per_second) => {
+                let burst = self.burst.unwrap_or(per_second);
+                quote! {
+                    Some(scyllax::admission::RateLimit {
+                        per_second: #per_second,
+                        burst: #burst,
+                    })
+                }
+            }
+            None => quote!(None),
+        };
+        let max_in_flight = match self.max_in_flight {
+            Some(max) => quote!(Some(#max)),
+            None => quote!(None),
+        };
+        let policy = if self.reject_when_limited {
+            quote!(scyllax::admission::AdmissionPolicy::Reject)
+        } else {
+            quote!(scyllax::admission::AdmissionPolicy::Wait)
+        };
+
+        quote! {
+            fn admission() -> Option<scyllax::admission::AdmissionLimits> {
+                Some(scyllax::admission::AdmissionLimits {
+                    rate_limit: #rate_limit,
+                    max_in_flight: #max_in_flight,
+                    policy: #policy,
+                })
+            }
+        }
+    }
+}
+
+/// Renders the `using` clause of a generated write query, binding `:set_ttl` and `:set_timestamp`.
+/// Returns an empty string if neither is enabled.
+pub(crate) fn using_clause(ttl: bool, timestamp: bool) -> String {
+    let options = [
+        (ttl, "ttl :set_ttl"),
+        (timestamp, "timestamp :set_timestamp"),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, option)| option)
+    .collect::<Vec<_>>();
+
+    match options.is_empty() {
+        true => String::new(),
+        false => format!(" using {}", options.join(" and ")),
+    }
+}
+
 /// Implements the [`Query`] trait for a struct.
 pub fn impl_generic_query(
     input: &ItemStruct,
     query: String,
     inner_entity_type: Option<&syn::Type>,
+    admission: &AdmissionArgs,
 ) -> TokenStream {
     let struct_ident = &input.ident;
 
@@ -19,6 +102,11 @@ pub fn impl_generic_query(
     } else {
         quote!(#query.to_string())
     };
+    let bind_names = input
+        .fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(|i| i.to_string()));
+    let admission = admission.impl_admission();
 
     quote! {
         #[scyllax::prelude::async_trait]
@@ -26,6 +114,31 @@ pub fn impl_generic_query(
             fn query() -> String {
                 #query
             }
+
+            fn bind_names() -> &'static [&'static str] {
+                &[#(#bind_names),*]
+            }
+
+            #admission
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_admission_zero() {
+        let admission = AdmissionArgs {
+            rate_limit: Some(10),
+            burst: Some(0),
+            ..Default::default()
+        };
+
+        assert!(admission
+            .impl_admission()
+            .to_string()
+            .contains("`rate_limit`, `burst` and `max_in_flight` must be greater than 0"));
+    }
+}
@@ -1,10 +1,13 @@
 use darling::{ast, FromDeriveInput, FromField};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
-use scyllax_parser::{select::parse_select, SelectQuery, Value, Variable};
+use scyllax_parser::{select::parse_select, Column, SelectQuery, Value, Variable};
 use syn::{DeriveInput, Ident, ItemStruct, Type};
 
-use crate::queries::impl_generic_query;
+use crate::{
+    queries::{impl_generic_query, AdmissionArgs},
+    schema,
+};
 
 #[derive(Debug, PartialEq, FromField)]
 #[darling(attributes(read_query))]
@@ -26,9 +29,25 @@ pub struct ReadQueryDerive {
     pub query: Option<String>,
     #[darling(default)]
     pub query_nocheck: Option<String>,
-    pub return_type: syn::Type,
+    #[darling(default)]
+    pub return_type: Option<syn::Type>,
+    /// Whether the query returns if any rows match, instead of the rows
+    #[darling(default)]
+    pub exists: bool,
     #[darling(default)]
     pub disable_coalescing: bool,
+    /// The number of executions allowed per second
+    #[darling(default)]
+    pub rate_limit: Option<u32>,
+    /// The number of executions allowed in a burst
+    #[darling(default)]
+    pub burst: Option<u32>,
+    /// The number of executions allowed in flight at once
+    #[darling(default)]
+    pub max_in_flight: Option<usize>,
+    /// Whether executions over the limits are rejected instead of waiting
+    #[darling(default)]
+    pub reject_when_limited: bool,
 }
 
 pub fn expand(input: TokenStream) -> TokenStream {
@@ -51,116 +70,116 @@ pub fn expand(input: TokenStream) -> TokenStream {
         return syn::Error::new_spanned(input, "Either query or query_nocheck must be specified")
             .to_compile_error();
     }
-    let return_type = args.return_type;
     let struct_ident = args.ident;
     let r#struct = syn::parse2::<ItemStruct>(input.clone()).unwrap();
 
+    let return_type = match (args.return_type, args.exists) {
+        (Some(_), true) => {
+            return syn::Error::new_spanned(
+                input,
+                "`exists` queries return a bool, remove `return_type`",
+            )
+            .to_compile_error()
+        }
+        (None, false) => {
+            return syn::Error::new_spanned(input, "return_type must be specified")
+                .to_compile_error()
+        }
+        (return_type, _) => return_type.unwrap_or_else(|| syn::parse_quote!(bool)),
+    };
+
     // trimmed return_type
     // eg: Vec<OrgEntity> -> OrgEntity
     // eg: OrgEntity -> OrgEntity
-    let inner_entity_type = if let syn::Type::Path(path) = return_type.clone() {
-        let last_segment = path.path.segments.last().unwrap();
-        let ident = &last_segment.ident;
-
-        if ident == "Vec" {
-            let args = &last_segment.arguments;
-            if let syn::PathArguments::AngleBracketed(args) = args {
-                let args = &args.args;
-                if args.len() != 1 {
-                    return syn::Error::new_spanned(
-                        return_type,
-                        "return_type must be a path with one generic argument",
-                    )
-                    .to_compile_error();
-                }
-
-                if let syn::GenericArgument::Type(ty) = args.first().unwrap() {
-                    ty.clone()
-                } else {
-                    return syn::Error::new_spanned(
-                        return_type,
-                        "return_type must be a path with one generic argument",
-                    )
-                    .to_compile_error();
-                }
-            } else {
-                return syn::Error::new_spanned(
-                    return_type,
-                    "return_type must be a path with one generic argument",
-                )
-                .to_compile_error();
-            }
-        } else {
-            return_type.clone()
-        }
-    } else {
-        return syn::Error::new_spanned(return_type, "return_type must be a path")
-            .to_compile_error();
+    let (vec_response, inner_type) = match split_vec(&return_type) {
+        Ok(split) => split,
+        Err(e) => return e.to_compile_error(),
     };
 
-    // if return_type is a Vec, return type is Vec<return_type>
-    // if return_type is not a Vec, return type is Option<return_type>
-    let impl_return_type = if let syn::Type::Path(path) = return_type.clone() {
-        let last_segment = path.path.segments.last().unwrap();
-        let ident = &last_segment.ident;
+    // query parsing
+    let (query, parsed) = if let Some(query) = args.query {
+        let parsed = match parse_query(&r#struct, &query, vec_response) {
+            Ok(parsed) => parsed,
+            Err(e) => return e.to_compile_error(),
+        };
 
-        if ident == "Vec" {
-            quote! {
-                #return_type
-            }
-        } else {
-            quote! {
-                Option<#return_type>
-            }
-        }
+        (query, Some(parsed))
+    } else if let Some(query) = args.query_nocheck {
+        (query, None)
     } else {
-        return syn::Error::new_spanned(return_type, "return_type must be a path")
-            .to_compile_error();
+        unreachable!()
     };
 
-    // if return_type is a Vec, we need to use the macro scyllax:match_rows!(res, return_type)
-    // if return_type is not a Vec, we need to use the macro scyllax:match_row!(res, return_type)
-    // eg: Vec<OrgEntity> -> scyllax:match_rows!(res, OrgEntity)
-    // eg: OrgEntity -> scyllax:match_row!(res, OrgEntity)
-    let (vec_response, parser) = if let syn::Type::Path(path) = return_type.clone() {
-        let last_segment = path.path.segments.last().unwrap();
-        let ident = &last_segment.ident;
-
-        if ident == "Vec" {
-            (
-                true,
-                quote! {
-                    scyllax::match_rows!(res, #inner_entity_type)
-                },
-            )
-        } else {
-            (
-                false,
-                quote! {
-                    scyllax::match_row!(res, #inner_entity_type)
-                },
-            )
+    let projection = match Projection::new(args.exists, parsed.as_ref(), &inner_type) {
+        Ok(projection) => projection,
+        Err(e) => return e.to_compile_error(),
+    };
+    let struct_check = match (&projection, &parsed, &inner_type) {
+        (Projection::Row, Some(parsed), Type::Path(_)) => {
+            match check_struct_columns(parsed, &inner_type) {
+                Ok(tokens) => tokens,
+                Err(e) => return e.to_compile_error(),
+            }
         }
-    } else {
-        return syn::Error::new_spanned(return_type, "return_type must be a path")
-            .to_compile_error();
+        _ => quote! {},
     };
 
-    // query parsing
-    let query = if let Some(query) = args.query {
-        match parse_query(&r#struct, &query, vec_response) {
-            Ok(_) => (),
+    let schema_check = match &parsed {
+        Some(parsed) => match schema::check(|schema| {
+            schema.check_read(&r#struct, &query, parsed, &projection.types(&inner_type))
+        }) {
+            Ok(tokens) => tokens,
             Err(e) => return e.to_compile_error(),
-        };
+        },
+        None => quote! {},
+    };
 
-        query
-    } else if let Some(query) = args.query_nocheck {
-        query
-    } else {
-        unreachable!()
+    // the output of the query, and how it's parsed from the response:
+    // - `exists` queries return whether there are rows
+    // - Vec return types return every row
+    // - queries that only select aggregates always return one row
+    // - everything else returns the first row, if there is one
+    let single_row = parsed
+        .as_ref()
+        .is_some_and(|parsed| parsed.columns.iter().all(Column::is_aggregate));
+    let (impl_return_type, parser) = match projection {
+        Projection::Exists => (quote!(bool), quote!(scyllax::match_exists!(res))),
+        Projection::Entity | Projection::Row if vec_response => (
+            quote!(#return_type),
+            quote!(scyllax::match_rows!(res, #inner_type)),
+        ),
+        Projection::Scalar if vec_response => (
+            quote!(#return_type),
+            quote!(scyllax::match_scalars!(res, #inner_type)),
+        ),
+        Projection::Entity | Projection::Row if single_row => (
+            quote!(#return_type),
+            quote!(scyllax::match_row!(res, #inner_type)?
+                .ok_or(scyllax::prelude::ScyllaxError::NoRowsFound)),
+        ),
+        Projection::Scalar if single_row => (
+            quote!(#return_type),
+            quote!(scyllax::match_scalar!(res, #inner_type)?
+                .ok_or(scyllax::prelude::ScyllaxError::NoRowsFound)),
+        ),
+        Projection::Entity | Projection::Row => (
+            quote!(Option<#return_type>),
+            quote!(scyllax::match_row!(res, #inner_type)),
+        ),
+        Projection::Scalar => (
+            quote!(Option<#return_type>),
+            quote!(scyllax::match_scalar!(res, #inner_type)),
+        ),
     };
 
-    let impl_query = impl_generic_query(&r#struct, query, Some(&inner_entity_type));
+    let admission = AdmissionArgs {
+        rate_limit: args.rate_limit,
+        burst: args.burst,
+        max_in_flight: args.max_in_flight,
+        reject_when_limited: args.reject_when_limited,
+    };
+    let entity_type = matches!(projection, Projection::Entity).then_some(&inner_type);
+    let impl_query = impl_generic_query(&r#struct, query, entity_type, &admission);
 
     let shard_keys = fields
         .iter()
@@ -205,6 +224,10 @@ pub fn expand(input: TokenStream) -> TokenStream {
     quote! {
         #impl_query
 
+        #schema_check
+
+        #struct_check
+
         impl std::hash::Hash for #struct_ident {
             fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
                 #(#shard_key)*
@@ -226,6 +249,142 @@ pub fn expand(input: TokenStream) -> TokenStream {
     }
 }
 
+/// How the rows of a read query are returned
+enum Projection {
+    /// `select *`, returning the entity
+    Entity,
+    /// A single selected column, returning its value, or a struct with one field.
+    /// Which one is decided by the type, see `scyllax::rows::__Column`.
+    Scalar,
+    /// Several selected columns or a tuple, returning a tuple or a partial struct
+    Row,
+    /// Whether any rows match
+    Exists,
+}
+
+impl Projection {
+    fn new(
+        exists: bool,
+        parsed: Option<&SelectQuery>,
+        inner_type: &Type,
+    ) -> Result<Self, syn::Error> {
+        if exists {
+            return Ok(Projection::Exists);
+        }
+
+        let Some(parsed) = parsed else {
+            return Ok(Projection::Entity);
+        };
+
+        match (parsed.columns.as_slice(), inner_type) {
+            ([Column::Asterisk], _) => Ok(Projection::Entity),
+            (columns, Type::Tuple(tuple)) if tuple.elems.len() != columns.len() => {
+                Err(syn::Error::new_spanned(
+                    inner_type,
+                    format!(
+                        "return_type has {} elements, but the query selects {} columns",
+                        tuple.elems.len(),
+                        columns.len()
+                    ),
+                ))
+            }
+            (_, Type::Tuple(_)) => Ok(Projection::Row),
+            ([_], _) => Ok(Projection::Scalar),
+            _ => Ok(Projection::Row),
+        }
+    }
+
+    /// The types each selected column is read as, when they're known
+    fn types<'a>(&self, inner_type: &'a Type) -> Vec<&'a Type> {
+        match (self, inner_type) {
+            (Projection::Scalar, _) => vec![inner_type],
+            (Projection::Row, Type::Tuple(tuple)) => tuple.elems.iter().collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Checks that the fields of a struct read from several columns are the selected columns, in order,
+/// since rows are read into structs by position.
+/// The struct has to derive `Entity` or `PartialEntity`, which list its columns.
+fn check_struct_columns(
+    parsed: &SelectQuery,
+    inner_type: &Type,
+) -> Result<TokenStream, syn::Error> {
+    let columns = parsed
+        .columns
+        .iter()
+        .map(|column| {
+            column_name(column).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    inner_type,
+                    format!(
+                        "`{}` needs an alias to be read into a struct, eg: `{} as total`",
+                        schema::selector(column),
+                        schema::selector(column)
+                    ),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let message = format!(
+        "The fields of `{}` must be the selected columns, in order: {}",
+        inner_type.to_token_stream().to_string().replace(' ', ""),
+        columns.join(", ")
+    );
+
+    Ok(quote! {
+        const _: () = assert!(
+            scyllax::entity::__columns_eq(<#inner_type>::__scyllax_columns, &[#(#columns),*]),
+            #message
+        );
+    })
+}
+
+/// The name a selected column is returned as, quoted like the column names of entities.
+/// Unquoted names are case insensitive, so they're lowercased.
+fn column_name(column: &Column) -> Option<String> {
+    let name = match column {
+        Column::Identifier(name) | Column::Alias { alias: name, .. } => name,
+        _ => return None,
+    };
+
+    if name.starts_with('"') && name.ends_with('"') && name.len() > 1 {
+        Some(name.clone())
+    } else {
+        Some(format!(r#""{}""#, name.to_lowercase()))
+    }
+}
+
+/// Splits a return type into whether it's a Vec, and the type of its rows
+/// - eg: `Vec<OrgEntity>` -> `(true, OrgEntity)`
+/// - eg: `OrgEntity` -> `(false, OrgEntity)`
+fn split_vec(return_type: &Type) -> Result<(bool, Type), syn::Error> {
+    let Type::Path(path) = return_type else {
+        return Ok((false, return_type.clone()));
+    };
+    let last_segment = path.path.segments.last().unwrap();
+    if last_segment.ident != "Vec" {
+        return Ok((false, return_type.clone()));
+    }
+
+    match &last_segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first().unwrap() {
+                syn::GenericArgument::Type(ty) => Ok((true, ty.clone())),
+                _ => Err(syn::Error::new_spanned(
+                    return_type,
+                    "return_type must be a path with one generic argument",
+                )),
+            }
+        }
+        _ => Err(syn::Error::new_spanned(
+            return_type,
+            "return_type must be a path with one generic argument",
+        )),
+    }
+}
+
 fn parse_query(
     input: &ItemStruct,
     query: &String,
@@ -244,7 +403,7 @@ fn parse_query(
     if !rest.is_empty() {
         return Err(syn::Error::new_spanned(
             query.into_token_stream(),
-            format!("Failed to parse query, stopped at: {:#?}.\nThe parser's still in development... If you're positive it's valid, rename `query` to `query_nockeck`.", rest),
+            format!("Failed to parse query, stopped at: {:#?}.\nThe parser's still in development... If you're positive it's valid, rename `query` to `query_nocheck`.", rest),
         ));
     }
 
@@ -324,3 +483,90 @@ fn parse_query(
 
     Ok(parsed)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn projection(query: &str, return_type: &str) -> Result<(bool, &'static str), String> {
+        let parsed = parse_select(query).unwrap().1;
+        let (vec, inner_type) = split_vec(&syn::parse_str(return_type).unwrap()).unwrap();
+
+        Projection::new(false, Some(&parsed), &inner_type)
+            .map(|projection| {
+                let kind = match projection {
+                    Projection::Entity => "entity",
+                    Projection::Scalar => "scalar",
+                    Projection::Row => "row",
+                    Projection::Exists => "exists",
+                };
+
+                (vec, kind)
+            })
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_projection() {
+        assert_eq!(
+            projection("select * from person", "Vec<PersonEntity>"),
+            Ok((true, "entity"))
+        );
+        assert_eq!(
+            projection("select count(*) from person", "i64"),
+            Ok((false, "scalar"))
+        );
+        assert_eq!(
+            projection("select id, email from person", "Vec<(Uuid, String)>"),
+            Ok((true, "row"))
+        );
+        assert_eq!(
+            projection("select id, email from person", "PersonEmail"),
+            Ok((false, "row"))
+        );
+        assert_eq!(
+            projection("select id, email, age from person", "(Uuid, String)"),
+            Err("return_type has 2 elements, but the query selects 3 columns".to_string())
+        );
+        // one element tuples are rows, not scalars
+        assert_eq!(
+            projection("select email from person", "Vec<(String,)>"),
+            Ok((true, "row"))
+        );
+        assert_eq!(
+            projection("select coords from place", "(f64, f64)"),
+            Err("return_type has 2 elements, but the query selects 1 columns".to_string())
+        );
+    }
+
+    #[test]
+    fn test_struct_columns() {
+        let check = |query: &str| {
+            let parsed = parse_select(query).unwrap().1;
+            check_struct_columns(&parsed, &syn::parse_quote!(PersonEmail))
+                .map(|tokens| tokens.to_string())
+                .map_err(|e| e.to_string())
+        };
+
+        let expected = quote! {
+            const _: () = assert!(
+                scyllax::entity::__columns_eq(
+                    <PersonEmail>::__scyllax_columns,
+                    &["\"id\"", "\"createdAt\"", "\"email\""]
+                ),
+                "The fields of `PersonEmail` must be the selected columns, in order: \"id\", \"createdAt\", \"email\""
+            );
+        };
+        assert_eq!(
+            check(r#"select id, "createdAt", email_address as Email from person"#),
+            Ok(expected.to_string())
+        );
+        assert_eq!(
+            check("select id, count(*) from person"),
+            Err(
+                "`count(*)` needs an alias to be read into a struct, eg: `count(*) as total`"
+                    .to_string()
+            )
+        );
+    }
+}
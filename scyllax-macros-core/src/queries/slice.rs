@@ -0,0 +1,274 @@
+//! Read queries over a range of a clustering key, generated by `#[slice_query(...)]`
+use darling::{ast::NestedMeta, FromDeriveInput, FromMeta};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Ident};
+
+use crate::entity::{key_roles, EntityDerive, EntityDeriveColumn};
+
+#[derive(FromMeta)]
+pub(crate) struct SliceQueryOptions {
+    pub name: syn::Ident,
+    pub table: String,
+    /// The clustering key field the range is on
+    pub column: syn::Ident,
+    pub range: SliceRange,
+    /// Adds a `rowlimit: i32` field
+    pub limit: Option<bool>,
+}
+
+/// The range of a slice query
+#[derive(Debug, Clone, Copy, PartialEq, FromMeta)]
+#[darling(rename_all = "snake_case")]
+pub(crate) enum SliceRange {
+    /// `col > :col`
+    Gt,
+    /// `col >= :col`
+    Gte,
+    /// `col < :col`
+    Lt,
+    /// `col <= :col`
+    Lte,
+    /// `col >= :col_start and col <= :col_end`
+    Between,
+}
+
+impl SliceRange {
+    /// The conditions of the range, and the fields they bind
+    fn conditions(&self, column: &EntityDeriveColumn) -> Vec<(String, Ident)> {
+        let name = column.name.as_ref().unwrap();
+        let ident = column.ident.as_ref().unwrap();
+        let condition =
+            |operator: &str, field: Ident| (format!("{name} {operator} :{field}"), field);
+
+        match self {
+            SliceRange::Gt => vec![condition(">", ident.clone())],
+            SliceRange::Gte => vec![condition(">=", ident.clone())],
+            SliceRange::Lt => vec![condition("<", ident.clone())],
+            SliceRange::Lte => vec![condition("<=", ident.clone())],
+            SliceRange::Between => vec![
+                condition(">=", format_ident!("{}_start", ident)),
+                condition("<=", format_ident!("{}_end", ident)),
+            ],
+        }
+    }
+}
+
+/// Attribute expand
+/// Keeps the entity as is and generates the slice query next to it.
+pub fn expand(args: TokenStream, input: TokenStream) -> TokenStream {
+    let attr_args = match NestedMeta::parse_meta_list(args.clone()) {
+        Ok(args) => args,
+        Err(e) => return darling::Error::from(e).write_errors(),
+    };
+
+    let args = match SliceQueryOptions::from_list(&attr_args) {
+        Ok(o) => o,
+        Err(e) => return e.write_errors(),
+    };
+
+    let input: DeriveInput = match syn::parse2(input.clone()) {
+        Ok(it) => it,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let entity = match EntityDerive::from_derive_input(&input) {
+        Ok(e) => e,
+        Err(e) => return e.write_errors(),
+    };
+
+    let slice = match slice_impl(&args, &entity) {
+        Ok(slice) => slice,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    quote! {
+        #input
+
+        #slice
+    }
+}
+
+/// Creates the slice query struct.
+/// The partition keys, and the clustering keys before `column`, are matched exactly.
+pub(crate) fn slice_impl(
+    opt: &SliceQueryOptions,
+    entity: &EntityDerive,
+) -> Result<TokenStream, syn::Error> {
+    let slice_struct = &opt.name;
+    let entity_ident = &entity.ident;
+    let fields = entity
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Should never be enum")
+        .fields;
+    let (partition_keys, clustering_keys) = key_roles(&fields);
+
+    let Some(position) = clustering_keys
+        .iter()
+        .position(|(f, _)| f.ident.as_ref() == Some(&opt.column))
+    else {
+        return Err(syn::Error::new_spanned(
+            &opt.column,
+            format!("`{}` isn't a clustering key of {entity_ident}", opt.column),
+        ));
+    };
+    let column = clustering_keys[position].0;
+
+    let keys = partition_keys
+        .iter()
+        .copied()
+        .chain(clustering_keys[..position].iter().map(|(f, _)| *f))
+        .collect::<Vec<_>>();
+    let range = opt.range.conditions(column);
+
+    let conditions = keys
+        .iter()
+        .map(|f| {
+            format!(
+                "{} = :{}",
+                f.name.as_ref().unwrap(),
+                f.ident.as_ref().unwrap()
+            )
+        })
+        .chain(range.iter().map(|(condition, _)| condition.clone()))
+        .collect::<Vec<_>>()
+        .join(" and ");
+
+    let table = &opt.table;
+    let mut query = format!("select * from {table} where {conditions}");
+    if opt.limit.unwrap_or(false) {
+        query.push_str(" limit :rowlimit");
+    }
+
+    let key_fields = keys.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        let comment = format!("The {} of the {}", ident.as_ref().unwrap(), entity_ident);
+
+        quote! {
+            #[doc = #comment]
+            pub #ident: #ty
+        }
+    });
+    let range_fields = range.iter().map(|(_, ident)| {
+        let ty = &column.ty;
+        let comment = format!("The {} bound of the range", ident);
+
+        quote! {
+            #[doc = #comment]
+            pub #ident: #ty
+        }
+    });
+    let limit = if opt.limit.unwrap_or(false) {
+        quote! {
+            #[doc = "The maximum number of rows to return"]
+            pub rowlimit: i32,
+        }
+    } else {
+        quote! {}
+    };
+
+    let docs = format!(
+        "Gets the [`{entity_ident}`]s of a partition, in a range of `{}`",
+        opt.column
+    );
+    let return_type = format!("Vec<{entity_ident}>");
+
+    Ok(quote! {
+        #[doc = #docs]
+        #[derive(Debug, Clone, PartialEq, scylla::SerializeRow, scyllax::prelude::ReadQuery)]
+        #[read_query(query = #query, return_type = #return_type)]
+        pub struct #slice_struct {
+            #(#key_fields,)*
+            #(#range_fields,)*
+            #limit
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn build(attr: TokenStream) -> Result<TokenStream, syn::Error> {
+        let input: DeriveInput = syn::parse2(quote! {
+            pub struct CommentEntity {
+                #[entity(partition_key)]
+                pub post_id: Uuid,
+                #[entity(clustering_key(order = "desc"), rename = "createdAt")]
+                pub created_at: i64,
+                #[entity(clustering_key)]
+                pub id: Uuid,
+                pub body: String,
+            }
+        })
+        .unwrap();
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let opt =
+            SliceQueryOptions::from_list(&NestedMeta::parse_meta_list(attr).unwrap()).unwrap();
+
+        slice_impl(&opt, &entity)
+    }
+
+    #[test]
+    fn test_slice_query() {
+        let expanded = build(quote! {
+            table = "comment", name = GetCommentsBefore, column = created_at, range = "lt", limit
+        })
+        .unwrap();
+
+        let expected = quote! {
+            #[doc = "Gets the [`CommentEntity`]s of a partition, in a range of `created_at`"]
+            #[derive(Debug, Clone, PartialEq, scylla::SerializeRow, scyllax::prelude::ReadQuery)]
+            #[read_query(
+                query = "select * from comment where \"post_id\" = :post_id and \"createdAt\" < :created_at limit :rowlimit",
+                return_type = "Vec<CommentEntity>"
+            )]
+            pub struct GetCommentsBefore {
+                #[doc = "The post_id of the CommentEntity"]
+                pub post_id: Uuid,
+                #[doc = "The created_at bound of the range"]
+                pub created_at: i64,
+                #[doc = "The maximum number of rows to return"]
+                pub rowlimit: i32,
+            }
+        };
+
+        assert_eq!(expanded.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_slice_between() {
+        let expanded = build(quote! {
+            table = "comment", name = GetCommentsBetween, column = id, range = "between"
+        })
+        .unwrap()
+        .to_string();
+
+        let query = "select * from comment where \"post_id\" = :post_id and \"createdAt\" = :created_at and \"id\" >= :id_start and \"id\" <= :id_end";
+        assert!(expanded.contains(&format!("{query:?}")));
+        assert!(expanded.contains(&quote!(pub id_start: Uuid).to_string()));
+        assert!(expanded.contains(&quote!(pub id_end: Uuid).to_string()));
+        assert_eq!(
+            scyllax_parser::select::parse_select(&format!("{query} limit :rowlimit"))
+                .unwrap()
+                .0,
+            ""
+        );
+    }
+
+    #[test]
+    fn test_slice_not_clustering_key() {
+        let error = build(quote! {
+            table = "comment", name = GetCommentsByPost, column = post_id, range = "gt"
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`post_id` isn't a clustering key of CommentEntity"
+        );
+    }
+}
@@ -1,15 +1,137 @@
-use darling::{ast::NestedMeta, FromDeriveInput, FromMeta};
+use std::collections::HashMap;
+
+use darling::{ast::NestedMeta, util::PathList, FromDeriveInput, FromMeta};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use syn::{DeriveInput, ItemStruct};
 
-use crate::entity::{EntityDerive, EntityDeriveColumn};
+use super::using_clause;
+use crate::{
+    entity::{
+        map_types, option_inner, unwrap_option, CollectionOp, EntityDerive, EntityDeriveColumn,
+    },
+    schema,
+};
 
 #[derive(FromMeta)]
 pub(crate) struct UpsertQueryOptions {
     pub name: syn::Ident,
-    pub table: String,
+    pub table: Option<String>,
+    /// The tables the entity is denormalized into, written in one logged batch
+    pub tables: Option<Vec<syn::LitStr>>,
+    /// The primary key of each table in `tables` that doesn't use the entity's
+    #[darling(default)]
+    pub keys: HashMap<String, PathList>,
+    /// Generates a query deleting the entity from every table
+    pub delete: Option<syn::Ident>,
     pub ttl: Option<bool>,
+    pub timestamp: Option<bool>,
+}
+
+/// A table written by an upsert, with its primary key
+struct Target<'a> {
+    table: String,
+    keys: Vec<&'a EntityDeriveColumn>,
+}
+
+impl UpsertQueryOptions {
+    /// Resolves the tables written by the upsert, and their primary keys
+    fn targets<'a>(
+        &self,
+        fields: &[&'a EntityDeriveColumn],
+    ) -> Result<Vec<Target<'a>>, syn::Error> {
+        let tables = match (&self.table, &self.tables) {
+            (Some(table), None) => vec![table.clone()],
+            (None, Some(tables)) if !tables.is_empty() => {
+                tables.iter().map(|table| table.value()).collect()
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &self.name,
+                    "Either `table` or `tables` must be set",
+                ))
+            }
+        };
+
+        if let Some(unknown) = self
+            .keys
+            .keys()
+            .find(|name| !tables.iter().any(|table| unquote(table) == name.as_str()))
+        {
+            return Err(syn::Error::new_spanned(
+                &self.name,
+                format!("`keys` has `{unknown}`, which isn't one of the `tables`"),
+            ));
+        }
+
+        if tables.len() > 1 && fields.iter().any(|f| f.counter) {
+            return Err(syn::Error::new_spanned(
+                &self.name,
+                "Entities with counter columns can't be written to multiple tables",
+            ));
+        }
+
+        // the server rejects custom timestamps for counter updates
+        if self.timestamp.unwrap_or(false) && fields.iter().any(|f| f.counter) {
+            return Err(syn::Error::new_spanned(
+                &self.name,
+                "Entities with counter columns can't be upserted with a custom `timestamp`",
+            ));
+        }
+
+        tables
+            .into_iter()
+            .map(|table| {
+                let keys = match self.keys.get(unquote(&table)) {
+                    Some(paths) => paths
+                        .iter()
+                        .map(|path| table_key(fields, &table, path))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => fields
+                        .iter()
+                        .copied()
+                        .filter(|f| f.is_primary_key())
+                        .collect(),
+                };
+
+                Ok(Target { table, keys })
+            })
+            .collect()
+    }
+}
+
+/// Strips the quotes of a table name, so it can be matched with the entries of `keys`
+fn unquote(table: &str) -> &str {
+    table.trim_matches('"')
+}
+
+/// Finds the field of a key in `keys`, which has to be a plain column
+fn table_key<'a>(
+    fields: &[&'a EntityDeriveColumn],
+    table: &str,
+    path: &syn::Path,
+) -> Result<&'a EntityDeriveColumn, syn::Error> {
+    let Some(field) = fields.iter().find(|f| {
+        path.get_ident()
+            .is_some_and(|ident| f.ident.as_ref() == Some(ident))
+    }) else {
+        return Err(syn::Error::new_spanned(
+            path,
+            format!("`{}` isn't a field of the entity", path.to_token_stream()),
+        ));
+    };
+
+    if field.counter || field.collection.is_some() || option_inner(&field.ty).is_some() {
+        return Err(syn::Error::new_spanned(
+            path,
+            format!(
+                "`{}` can't be a key of `{table}`, keys can't be optional, counters or collections",
+                path.to_token_stream()
+            ),
+        ));
+    }
+
+    Ok(field)
 }
 
 /// Attribute expand
@@ -45,7 +167,6 @@ pub(crate) fn upsert_impl(
     entity: &EntityDerive,
 ) -> TokenStream {
     let upsert_struct = &opt.name;
-    let upsert_table = &opt.table;
     let struct_ident = &input.ident;
     let keys = entity
         .data
@@ -53,8 +174,23 @@ pub(crate) fn upsert_impl(
         .take_struct()
         .expect("Should never be enum")
         .fields;
-    let primary_keys: Vec<&&EntityDeriveColumn> = keys.iter().filter(|f| f.primary_key).collect();
-    let counters: Vec<&&EntityDeriveColumn> = keys.iter().filter(|f| f.counter).collect();
+    let targets = match opt.targets(&keys) {
+        Ok(targets) => targets,
+        Err(e) => return e.to_compile_error(),
+    };
+    // the keys of every table have to be set, so they're all required
+    let (primary_keys, fields): (Vec<&EntityDeriveColumn>, Vec<&EntityDeriveColumn>) = keys
+        .iter()
+        .copied()
+        .partition(|f| targets.iter().any(|t| t.keys.contains(f)));
+    let schema_check = match schema::check(|schema| {
+        targets
+            .iter()
+            .try_for_each(|t| schema.check_upsert(&t.table, &keys))
+    }) {
+        Ok(tokens) => tokens,
+        Err(e) => return e.to_compile_error(),
+    };
 
     let input: ItemStruct = match syn::parse2(input.to_token_stream()) {
         Ok(it) => it,
@@ -75,17 +211,16 @@ pub(crate) fn upsert_impl(
         })
         .collect::<Vec<_>>();
 
-    let maybe_unset_fields = keys
+    let maybe_unset_fields = fields
         .iter()
-        .filter(|f| !primary_keys.contains(f))
         .map(|f| {
             let ident = &f.ident;
-            let ty = &f.ty;
             let comment = format!("The {} of the {}", ident.as_ref().unwrap(), struct_ident);
+            let ty = field_type(f);
 
             quote! {
                 #[doc = #comment]
-                pub #ident: scyllax::prelude::MaybeUnset<#ty>
+                pub #ident: #ty
             }
         })
         .collect::<Vec<_>>();
@@ -99,10 +234,26 @@ pub(crate) fn upsert_impl(
         quote! {}
     };
 
-    let docs = format!(
-        "Upserts a {} into the `{}` table",
-        struct_ident, upsert_table
-    );
+    let timestamp = if opt.timestamp.unwrap_or(false) {
+        quote! {
+            #[doc = "The write timestamp of the row in microseconds since the unix epoch"]
+            pub set_timestamp: i64,
+        }
+    } else {
+        quote! {}
+    };
+
+    let docs = match targets.as_slice() {
+        [target] => format!(
+            "Upserts a {} into the `{}` table",
+            struct_ident, target.table
+        ),
+        targets => format!(
+            "Upserts a {} into the {} tables, in a logged batch",
+            struct_ident,
+            table_list(targets)
+        ),
+    };
     let expanded_upsert_struct = quote! {
         #[doc = #docs]
         #[derive(Debug, Clone, scylla::SerializeRow)]
@@ -110,59 +261,387 @@ pub(crate) fn upsert_impl(
             #(#expanded_pks,)*
             #(#maybe_unset_fields,)*
             #ttl
+            #timestamp
         }
     };
 
-    // SET clauses
-    // expanded variables will loop over every field that isn't Pk
-    let set_clauses = keys
+    let statements = targets
         .iter()
-        // filter out pks
-        .filter(|f| !primary_keys.contains(f))
-        .map(|f| {
-            let ident = &f.ident.clone().unwrap();
-            let col = f.name.as_ref().unwrap();
-            let ident_string = ident.to_string();
+        .map(|target| {
+            // SET clauses
+            // every column that isn't a key of this table
+            let set_clauses = keys
+                .iter()
+                .filter(|f| !target.keys.contains(f))
+                .map(|f| set_clause(f))
+                .collect::<Vec<_>>();
 
-            if counters.contains(&f) {
-                format!("{col} = {col} + :{ident_string}")
-            } else {
-                format!("{col} = :{ident_string}")
-            }
+            // WHERE clauses
+            let where_clauses = target
+                .keys
+                .iter()
+                .map(|f| {
+                    let col = f.name.clone().unwrap();
+                    let named_var = f.ident.as_ref().unwrap().to_string();
+
+                    (col, named_var)
+                })
+                .collect::<Vec<_>>();
+
+            // if there are no set clauses, then we need to do an insert
+            // because we can't do an update with no set clauses
+            build_query(opt, &target.table, set_clauses, where_clauses)
         })
         .collect::<Vec<_>>();
+    let query = batch(statements);
 
-    // WHERE clauses
-    let where_clauses = keys
+    let mut bind_names = primary_keys
         .iter()
-        // filter out pks
-        .filter(|f| primary_keys.contains(f))
-        .map(|f| {
-            let ident = &f.ident.clone().unwrap();
-            let col = f.name.as_ref().unwrap();
-            let named_var = ident.to_string();
-
-            (col.clone(), named_var.clone())
-        })
+        .chain(fields.iter())
+        .map(|f| f.ident.as_ref().unwrap().to_string())
         .collect::<Vec<_>>();
+    if opt.ttl.unwrap_or(false) {
+        bind_names.push("set_ttl".to_string());
+    }
+    if opt.timestamp.unwrap_or(false) {
+        bind_names.push("set_timestamp".to_string());
+    }
 
-    // if there are no set clauses, then we need to do an insert
-    // because we can't do an update with no set clauses
-    let query = build_query(opt, upsert_table, set_clauses, where_clauses);
+    // changing a key of a table would leave the row of the old key behind in that table
+    let moved_keys = match targets.as_slice() {
+        [_] => Vec::new(),
+        targets => primary_keys
+            .iter()
+            .map(|f| {
+                let target = targets.iter().find(|t| t.keys.contains(f)).unwrap();
+                (*f, target.table.as_str())
+            })
+            .collect(),
+    };
+    let constructors = impl_constructors(opt, struct_ident, &primary_keys, &fields, &moved_keys);
+    let delete = opt
+        .delete
+        .as_ref()
+        .map(|delete| delete_impl(delete, struct_ident, &targets, &primary_keys));
 
     quote! {
         #input
 
         #expanded_upsert_struct
 
+        #constructors
+
         #[scyllax::prelude::async_trait]
         impl scyllax::prelude::Query for #upsert_struct {
             fn query() -> String {
                 #query.to_string()
             }
+
+            fn bind_names() -> &'static [&'static str] {
+                &[#(#bind_names),*]
+            }
         }
 
         impl scyllax::prelude::WriteQuery for #upsert_struct {}
+
+        #delete
+
+        #schema_check
+    }
+}
+
+/// Generates the query deleting the entity from every table, by their primary keys
+fn delete_impl(
+    delete: &syn::Ident,
+    struct_ident: &syn::Ident,
+    targets: &[Target],
+    primary_keys: &[&EntityDeriveColumn],
+) -> TokenStream {
+    let statements = targets
+        .iter()
+        .map(|target| {
+            let conditions = target
+                .keys
+                .iter()
+                .map(|f| {
+                    format!(
+                        "{} = :{}",
+                        f.name.as_ref().unwrap(),
+                        f.ident.as_ref().unwrap()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" and ");
+
+            format!("delete from {} where {conditions};", target.table)
+        })
+        .collect::<Vec<_>>();
+    let query = batch(statements);
+
+    let key_fields = primary_keys.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        let comment = format!("The {} of the {}", ident.as_ref().unwrap(), struct_ident);
+
+        quote! {
+            #[doc = #comment]
+            pub #ident: #ty
+        }
+    });
+    let docs = format!(
+        "Deletes a {struct_ident} from the {} tables",
+        table_list(targets)
+    );
+
+    // batches can't be checked by the parser
+    quote! {
+        #[doc = #docs]
+        #[scyllax::prelude::write_query(query_nocheck = #query)]
+        pub struct #delete {
+            #(#key_fields,)*
+        }
+    }
+}
+
+/// Wraps the statements of multiple tables in a logged batch
+fn batch(statements: Vec<String>) -> String {
+    match statements.len() {
+        1 => statements.into_iter().next().unwrap(),
+        _ => format!("begin batch {} apply batch;", statements.join(" ")),
+    }
+}
+
+/// Lists the tables of the targets for docs, eg: `` `person`, `person_by_email` ``
+fn table_list(targets: &[Target]) -> String {
+    targets
+        .iter()
+        .map(|target| format!("`{}`", target.table))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The type of a non primary key field in the upsert struct
+fn field_type(f: &EntityDeriveColumn) -> TokenStream {
+    match f.collection {
+        // nullable columns can be left as is, cleared or set
+        None => match option_inner(&f.ty) {
+            Some(inner) => quote!(scyllax::prelude::Patch<#inner>),
+            None => {
+                let ty = &f.ty;
+                quote!(scyllax::prelude::MaybeUnset<#ty>)
+            }
+        },
+        Some(CollectionOp::RemoveKeys) => {
+            let (key, _) = map_types(&f.ty).expect("validated by the entity");
+            quote!(scyllax::prelude::MaybeUnset<scyllax::prelude::RemoveKeys<Vec<#key>>>)
+        }
+        Some(op) => {
+            let collection = unwrap_option(&f.ty);
+            let wrapper = match op {
+                CollectionOp::Append => quote!(Append),
+                CollectionOp::Prepend => quote!(Prepend),
+                CollectionOp::Put => quote!(Put),
+                _ => quote!(Remove),
+            };
+            quote!(scyllax::prelude::MaybeUnset<scyllax::prelude::#wrapper<#collection>>)
+        }
+    }
+}
+
+/// Generates `new`, `from_entity`, `from_diff` and a setter for every non primary key field.
+/// The primary keys are the keys of every table the upsert writes to.
+/// `from_diff` rejects changes to `moved_keys`, each paired with a table it's a key of.
+fn impl_constructors(
+    opt: &UpsertQueryOptions,
+    struct_ident: &syn::Ident,
+    primary_keys: &[&EntityDeriveColumn],
+    fields: &[&EntityDeriveColumn],
+    moved_keys: &[(&EntityDeriveColumn, &str)],
+) -> TokenStream {
+    let upsert_struct = &opt.name;
+    let pk_idents = primary_keys
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let pk_types = primary_keys.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let field_idents = fields
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+
+    // the options of the `using` clause can't be left unset, so they're always passed in
+    let (using_params, using_idents): (Vec<_>, Vec<_>) = [
+        (opt.ttl, quote!(set_ttl: i32), quote!(set_ttl)),
+        (
+            opt.timestamp,
+            quote!(set_timestamp: i64),
+            quote!(set_timestamp),
+        ),
+    ]
+    .into_iter()
+    .filter(|(enabled, _, _)| enabled.unwrap_or(false))
+    .map(|(_, param, ident)| (param, ident))
+    .unzip();
+
+    let from_entity = fields
+        .iter()
+        .map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            match f.collection {
+                // counters are incremented by their value, so they can't be set from an entity
+                _ if f.counter => quote!(#ident: scyllax::prelude::MaybeUnset::Unset),
+                Some(_) => quote!(#ident: Default::default()),
+                None if option_inner(&f.ty).is_some() => {
+                    quote!(#ident: scyllax::prelude::Patch::from(entity.#ident))
+                }
+                None => quote!(#ident: scyllax::prelude::MaybeUnset::Set(entity.#ident)),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let from_diff = fields
+        .iter()
+        .map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            let value = match f.collection {
+                Some(_) => return quote!(#ident: Default::default()),
+                None if f.counter => quote! {
+                    scyllax::prelude::MaybeUnset::Set(
+                        scylla::frame::value::Counter(new.#ident.0 - old.#ident.0)
+                    )
+                },
+                None if option_inner(&f.ty).is_some() => {
+                    quote!(scyllax::prelude::Patch::from(new.#ident.clone()))
+                }
+                None => quote!(scyllax::prelude::MaybeUnset::Set(new.#ident.clone())),
+            };
+
+            quote! {
+                #ident: if old.#ident != new.#ident {
+                    #value
+                } else {
+                    Default::default()
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let setters = fields
+        .iter()
+        .map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            let ty = field_type(f);
+
+            let docs = format!("Sets the {ident}");
+            quote! {
+                #[doc = #docs]
+                pub fn #ident(mut self, #ident: impl Into<#ty>) -> Self {
+                    self.#ident = #ident.into();
+                    self
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let new_docs = format!(
+        "Creates an upsert of the {struct_ident} with the given primary key, leaving every other column as is"
+    );
+    let from_entity_docs = format!(
+        "Creates an upsert setting every column of the {struct_ident}. \
+        Counters and columns updated in place (`collection`) are left as is, \
+        increment counters with their setters or `from_diff`."
+    );
+    let from_diff_docs = "Creates an upsert setting only the columns that changed from `old` to `new`. \
+        The primary key is taken from `new`. \
+        Counters are incremented by their difference, columns updated in place (`collection`) are left as is.";
+    let from_diff = quote! {
+        Self {
+            #(#pk_idents: new.#pk_idents.clone(),)*
+            #(#from_diff,)*
+            #(#using_idents,)*
+        }
+    };
+    let from_diff = if moved_keys.is_empty() {
+        quote! {
+            #[doc = #from_diff_docs]
+            pub fn from_diff(old: &#struct_ident, new: &#struct_ident, #(#using_params),*) -> Self {
+                #from_diff
+            }
+        }
+    } else {
+        let from_diff_docs = format!(
+            "{from_diff_docs}\n\n\
+            Fails with [`scyllax::prelude::BuildUpsertQueryError::KeyChanged`] if a key of one of the tables changed, \
+            since the upsert would leave the row of the old key behind. Delete the old rows first."
+        );
+        let checks = moved_keys.iter().map(|(f, table)| {
+            let ident = f.ident.as_ref().unwrap();
+            let field = ident.to_string();
+
+            quote! {
+                if old.#ident != new.#ident {
+                    return Err(scyllax::prelude::BuildUpsertQueryError::KeyChanged {
+                        field: #field.to_string(),
+                        table: #table.to_string(),
+                    });
+                }
+            }
+        });
+
+        quote! {
+            #[doc = #from_diff_docs]
+            pub fn from_diff(
+                old: &#struct_ident,
+                new: &#struct_ident,
+                #(#using_params),*
+            ) -> Result<Self, scyllax::prelude::BuildUpsertQueryError> {
+                #(#checks)*
+
+                Ok(#from_diff)
+            }
+        }
+    };
+
+    quote! {
+        impl #upsert_struct {
+            #[doc = #new_docs]
+            pub fn new(#(#pk_idents: #pk_types,)* #(#using_params),*) -> Self {
+                Self {
+                    #(#pk_idents,)*
+                    #(#field_idents: Default::default(),)*
+                    #(#using_idents,)*
+                }
+            }
+
+            #[doc = #from_entity_docs]
+            pub fn from_entity(entity: #struct_ident, #(#using_params),*) -> Self {
+                Self {
+                    #(#pk_idents: entity.#pk_idents,)*
+                    #(#from_entity,)*
+                    #(#using_idents,)*
+                }
+            }
+
+            #from_diff
+
+            #(#setters)*
+        }
+    }
+}
+
+/// Renders the `set` clause of a field
+fn set_clause(f: &EntityDeriveColumn) -> String {
+    let col = f.name.as_ref().unwrap();
+    let ident = f.ident.as_ref().unwrap();
+
+    match f.collection {
+        _ if f.counter => format!("{col} = {col} + :{ident}"),
+        Some(CollectionOp::Append | CollectionOp::Put) => format!("{col} = {col} + :{ident}"),
+        Some(CollectionOp::Prepend) => format!("{col} = :{ident} + {col}"),
+        Some(CollectionOp::Remove | CollectionOp::RemoveKeys) => {
+            format!("{col} = {col} - :{ident}")
+        }
+        None => format!("{col} = :{ident}"),
     }
 }
 
@@ -172,13 +651,10 @@ fn build_query(
     set_clauses: Vec<String>,
     where_clauses: Vec<(String, String)>,
 ) -> String {
-    let ttl = match args.ttl.unwrap_or(false) {
-        true => " using ttl :set_ttl",
-        _ => "",
-    };
+    let using = using_clause(args.ttl.unwrap_or(false), args.timestamp.unwrap_or(false));
 
     if set_clauses.is_empty() {
-        let mut query = format!("insert into {table}{ttl}");
+        let mut query = format!("insert into {table}{using}");
         let (cols, named_var) = where_clauses.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
         let cols = cols.join(", ");
         let named_var = named_var
@@ -191,7 +667,7 @@ fn build_query(
 
         query
     } else {
-        let mut query = format!("update {table}{ttl} set ");
+        let mut query = format!("update {table}{using} set ");
         let query_set = set_clauses.join(", ");
         query.push_str(&query_set);
 
@@ -236,8 +712,12 @@ mod tests {
         let query = build_query(
             &UpsertQueryOptions {
                 name: syn::parse_str::<syn::Ident>("UpdatePerson").unwrap(),
-                table: "person".to_string(),
+                table: Some("person".to_string()),
+                tables: None,
+                keys: HashMap::new(),
+                delete: None,
                 ttl: None,
+                timestamp: None,
             },
             &"person".to_string(),
             get_set_clauses(),
@@ -255,8 +735,12 @@ mod tests {
         let query = build_query(
             &UpsertQueryOptions {
                 name: syn::parse_str::<syn::Ident>("UpdatePerson").unwrap(),
-                table: "person".to_string(),
+                table: Some("person".to_string()),
+                tables: None,
+                keys: HashMap::new(),
+                delete: None,
                 ttl: Some(true),
+                timestamp: None,
             },
             &"person".to_string(),
             get_set_clauses(),
@@ -269,13 +753,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_update_ttl_timestamp() {
+        let query = build_query(
+            &UpsertQueryOptions {
+                name: syn::parse_str::<syn::Ident>("UpdatePerson").unwrap(),
+                table: Some("person".to_string()),
+                tables: None,
+                keys: HashMap::new(),
+                delete: None,
+                ttl: Some(true),
+                timestamp: Some(true),
+            },
+            &"person".to_string(),
+            get_set_clauses(),
+            get_where_clauses(),
+        );
+
+        assert_eq!(
+            query,
+            "update person using ttl :set_ttl and timestamp :set_timestamp set name = :name, email = :email, \"createdAt\" = :created_at where id = :id and \"orgId\" = :org_id;",
+        );
+    }
+
     #[test]
     fn test_insert() {
         let query = build_query(
             &UpsertQueryOptions {
                 name: syn::parse_str::<syn::Ident>("UpdatePerson").unwrap(),
-                table: "person".to_string(),
+                table: Some("person".to_string()),
+                tables: None,
+                keys: HashMap::new(),
+                delete: None,
                 ttl: Default::default(),
+                timestamp: Default::default(),
             },
             &"person".to_string(),
             vec![],
@@ -293,8 +804,12 @@ mod tests {
         let query = build_query(
             &UpsertQueryOptions {
                 name: syn::parse_str::<syn::Ident>("UpdatePerson").unwrap(),
-                table: "person".to_string(),
+                table: Some("person".to_string()),
+                tables: None,
+                keys: HashMap::new(),
+                delete: None,
                 ttl: Some(true),
+                timestamp: None,
             },
             &"person".to_string(),
             vec![],
@@ -306,4 +821,307 @@ mod tests {
             "insert into person using ttl :set_ttl (id, \"orgId\") values (:id, :org_id);",
         );
     }
+
+    #[test]
+    fn test_set_clause() {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PersonEntity {
+                #[entity(collection = "append")]
+                pub tags: Vec<String>,
+                #[entity(collection = "prepend")]
+                pub history: Vec<String>,
+                #[entity(collection = "remove")]
+                pub emails: HashSet<String>,
+                #[entity(collection = "put", rename = "extraAttrs")]
+                pub attrs: HashMap<String, i32>,
+                #[entity(collection = "remove_keys")]
+                pub flags: HashMap<String, bool>,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let fields = entity.data.as_ref().take_struct().unwrap().fields;
+
+        assert_eq!(
+            fields.iter().map(|f| set_clause(f)).collect::<Vec<_>>(),
+            vec![
+                "\"tags\" = \"tags\" + :tags",
+                "\"history\" = :history + \"history\"",
+                "\"emails\" = \"emails\" - :emails",
+                "\"extraAttrs\" = \"extraAttrs\" + :attrs",
+                "\"flags\" = \"flags\" - :flags",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_type() {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PersonEntity {
+                #[entity(collection = "put")]
+                pub attrs: Option<HashMap<String, i32>>,
+                #[entity(collection = "remove_keys")]
+                pub flags: HashMap<String, bool>,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let fields = entity.data.as_ref().take_struct().unwrap().fields;
+
+        // the spacing of `>>` depends on how the tokens were built
+        let rendered = fields
+            .iter()
+            .map(|f| field_type(f).to_string().replace(' ', ""))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rendered,
+            vec![
+                "scyllax::prelude::MaybeUnset<scyllax::prelude::Put<HashMap<String,i32>>>",
+                "scyllax::prelude::MaybeUnset<scyllax::prelude::RemoveKeys<Vec<String>>>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constructors() {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: uuid::Uuid,
+                pub email: String,
+                pub age: Option<i32>,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let fields = entity.data.as_ref().take_struct().unwrap().fields;
+        let opt = UpsertQueryOptions {
+            name: syn::parse_str::<syn::Ident>("UpsertPerson").unwrap(),
+            table: Some("person".to_string()),
+            tables: None,
+            keys: HashMap::new(),
+            delete: None,
+            ttl: Some(true),
+            timestamp: None,
+        };
+
+        let (primary_keys, fields): (Vec<_>, Vec<_>) =
+            fields.iter().copied().partition(|f| f.is_primary_key());
+
+        let constructors =
+            impl_constructors(&opt, &input.ident, &primary_keys, &fields, &[]).to_string();
+
+        let new = quote! {
+            pub fn new(id: uuid::Uuid, set_ttl: i32) -> Self {
+                Self {
+                    id,
+                    email: Default::default(),
+                    age: Default::default(),
+                    set_ttl,
+                }
+            }
+        };
+        assert!(constructors.contains(&new.to_string()));
+
+        let from_diff = quote! {
+            age: if old.age != new.age {
+                scyllax::prelude::Patch::from(new.age.clone())
+            } else {
+                Default::default()
+            }
+        };
+        assert!(constructors.contains(&from_diff.to_string()));
+
+        let setter = quote! {
+            pub fn email(mut self, email: impl Into<scyllax::prelude::MaybeUnset<String> >) -> Self
+        };
+        assert!(constructors.contains(&setter.to_string()));
+    }
+
+    #[test]
+    fn test_counter_constructors() {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PostViewsEntity {
+                #[entity(primary_key)]
+                pub id: Uuid,
+                #[entity(counter)]
+                pub views: scylla::frame::value::Counter,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let fields = entity.data.as_ref().take_struct().unwrap().fields;
+        let opt = UpsertQueryOptions {
+            name: syn::parse_str::<syn::Ident>("UpsertPostViews").unwrap(),
+            table: Some("post_views".to_string()),
+            tables: None,
+            keys: HashMap::new(),
+            delete: None,
+            ttl: None,
+            timestamp: None,
+        };
+
+        let (primary_keys, fields): (Vec<_>, Vec<_>) =
+            fields.iter().copied().partition(|f| f.is_primary_key());
+
+        let constructors =
+            impl_constructors(&opt, &input.ident, &primary_keys, &fields, &[]).to_string();
+
+        // the counter would be incremented by its own value
+        let from_entity = quote! {
+            pub fn from_entity(entity: PostViewsEntity,) -> Self {
+                Self {
+                    id: entity.id,
+                    views: scyllax::prelude::MaybeUnset::Unset,
+                }
+            }
+        };
+        assert!(constructors.contains(&from_entity.to_string()));
+
+        let from_diff = quote! {
+            views: if old.views != new.views {
+                scyllax::prelude::MaybeUnset::Set(
+                    scylla::frame::value::Counter(new.views.0 - old.views.0)
+                )
+            } else {
+                Default::default()
+            }
+        };
+        assert!(constructors.contains(&from_diff.to_string()));
+    }
+
+    fn build_tables(attr: TokenStream) -> TokenStream {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: Uuid,
+                pub email: String,
+                #[entity(rename = "createdAt")]
+                pub created_at: i64,
+                pub age: Option<i32>,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let opt =
+            UpsertQueryOptions::from_list(&NestedMeta::parse_meta_list(attr).unwrap()).unwrap();
+
+        upsert_impl(&input, &opt, &entity)
+    }
+
+    #[test]
+    fn test_upsert_tables() {
+        let expanded = build_tables(quote! {
+            name = UpsertPerson,
+            tables = ["person", "person_by_email", "\"person_by_createdAt\""],
+            keys(
+                person_by_email(email, id),
+                person_by_createdAt(created_at, id),
+            ),
+            delete = DeletePerson,
+        })
+        .to_string();
+
+        let query = concat!(
+            "begin batch ",
+            "update person set \"email\" = :email, \"createdAt\" = :created_at, \"age\" = :age where \"id\" = :id; ",
+            "update person_by_email set \"createdAt\" = :created_at, \"age\" = :age where \"email\" = :email and \"id\" = :id; ",
+            "update \"person_by_createdAt\" set \"email\" = :email, \"age\" = :age where \"createdAt\" = :created_at and \"id\" = :id; ",
+            "apply batch;"
+        );
+        assert!(expanded.contains(&format!("{query:?}")));
+
+        // the keys of every table are required
+        let upsert = quote! {
+            pub struct UpsertPerson {
+                #[doc = "The id of the PersonEntity"]
+                pub id: Uuid,
+                #[doc = "The email of the PersonEntity"]
+                pub email: String,
+                #[doc = "The created_at of the PersonEntity"]
+                pub created_at: i64,
+                #[doc = "The age of the PersonEntity"]
+                pub age: scyllax::prelude::Patch<i32>,
+            }
+        };
+        assert!(expanded.contains(&upsert.to_string()));
+        let new = quote!(pub fn new(id: Uuid, email: String, created_at: i64,) -> Self);
+        assert!(expanded.contains(&new.to_string()));
+
+        // changing a key would leave the old rows behind
+        let from_diff = quote!(-> Result<Self, scyllax::prelude::BuildUpsertQueryError>);
+        assert!(expanded.contains(&from_diff.to_string()));
+        let check = quote! {
+            if old.id != new.id {
+                return Err(scyllax::prelude::BuildUpsertQueryError::KeyChanged {
+                    field: "id".to_string(),
+                    table: "person".to_string(),
+                });
+            }
+            if old.email != new.email {
+                return Err(scyllax::prelude::BuildUpsertQueryError::KeyChanged {
+                    field: "email".to_string(),
+                    table: "person_by_email".to_string(),
+                });
+            }
+        };
+        assert!(expanded.contains(&check.to_string()), "{expanded}");
+
+        let delete = quote! {
+            #[scyllax::prelude::write_query(
+                query_nocheck = "begin batch delete from person where \"id\" = :id; delete from person_by_email where \"email\" = :email and \"id\" = :id; delete from \"person_by_createdAt\" where \"createdAt\" = :created_at and \"id\" = :id; apply batch;"
+            )]
+            pub struct DeletePerson
+        };
+        assert!(expanded.contains(&delete.to_string()));
+    }
+
+    #[test]
+    fn test_upsert_tables_errors() {
+        let errors = [
+            (
+                quote!(name = UpsertPerson, table = "person", tables = ["person"]),
+                "Either `table` or `tables` must be set",
+            ),
+            (
+                quote!(name = UpsertPerson, tables = ["person"], keys(person_by_email(email))),
+                "`keys` has `person_by_email`, which isn't one of the `tables`",
+            ),
+            (
+                quote!(name = UpsertPerson, tables = ["person", "person_by_email"], keys(person_by_email(mail))),
+                "`mail` isn't a field of the entity",
+            ),
+            (
+                quote!(name = UpsertPerson, tables = ["person", "person_by_age"], keys(person_by_age(age, id))),
+                "`age` can't be a key of `person_by_age`, keys can't be optional, counters or collections",
+            ),
+        ];
+
+        for (attr, error) in errors {
+            let expanded = build_tables(attr).to_string();
+            assert!(expanded.contains(&format!("{error:?}")), "{expanded}");
+        }
+    }
+
+    #[test]
+    fn test_upsert_counter_timestamp() {
+        let input: DeriveInput = syn::parse_quote! {
+            pub struct PostViewsEntity {
+                #[entity(primary_key)]
+                pub id: Uuid,
+                #[entity(counter)]
+                pub views: scylla::frame::value::Counter,
+            }
+        };
+        let entity = EntityDerive::from_derive_input(&input).unwrap();
+        let opt = UpsertQueryOptions::from_list(
+            &NestedMeta::parse_meta_list(quote!(
+                name = UpsertPostViews,
+                table = "post_views",
+                timestamp
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert!(upsert_impl(&input, &opt, &entity)
+            .to_string()
+            .contains("Entities with counter columns can't be upserted with a custom `timestamp`"));
+    }
 }
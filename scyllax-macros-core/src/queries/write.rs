@@ -1,13 +1,25 @@
-use crate::queries::impl_generic_query;
+use crate::{
+    queries::{impl_generic_query, AdmissionArgs},
+    schema,
+};
 use darling::{export::NestedMeta, FromMeta};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
+use scyllax_parser::{
+    parse_write_query, AssignmentOperation, IfClause, Query, UsingOption, Value, Variable,
+    WhereClause,
+};
 use syn::ItemStruct;
 
 #[derive(FromMeta)]
 pub(crate) struct WriteQueryOptions {
     query: Option<String>,
     query_nocheck: Option<String>,
+    rate_limit: Option<u32>,
+    burst: Option<u32>,
+    max_in_flight: Option<usize>,
+    #[darling(default)]
+    reject_when_limited: bool,
 }
 
 pub fn expand(args: TokenStream, item: TokenStream) -> TokenStream {
@@ -26,20 +38,32 @@ pub fn expand(args: TokenStream, item: TokenStream) -> TokenStream {
         Err(e) => return e.to_compile_error(),
     };
 
-    let query = if let Some(query) = args.query {
-        // match parse_query(&input, &query) {
-        //     Ok(_) => (),
-        //     Err(e) => return e.to_compile_error(),
-        // };
+    let (query, schema_check) = if let Some(query) = args.query {
+        let parsed = match parse_query(&input, &query) {
+            Ok(parsed) => parsed,
+            Err(e) => return e.to_compile_error(),
+        };
 
-        query
+        let schema_check = match schema::check(|schema| schema.check_write(&input, &query, &parsed))
+        {
+            Ok(tokens) => tokens,
+            Err(e) => return e.to_compile_error(),
+        };
+
+        (query, schema_check)
     } else if let Some(query) = args.query_nocheck {
-        query
+        (query, quote! {})
     } else {
         unreachable!()
     };
 
-    let impl_query = impl_generic_query(&input, query, None);
+    let admission = AdmissionArgs {
+        rate_limit: args.rate_limit,
+        burst: args.burst,
+        max_in_flight: args.max_in_flight,
+        reject_when_limited: args.reject_when_limited,
+    };
+    let impl_query = impl_generic_query(&input, query, None, &admission);
     let struct_ident = &input.ident;
 
     quote! {
@@ -48,6 +72,203 @@ pub fn expand(args: TokenStream, item: TokenStream) -> TokenStream {
 
         #impl_query
 
+        #schema_check
+
         impl scyllax::prelude::WriteQuery for #struct_ident {}
     }
 }
+
+/// Parses a write query, and checks its bind variables against the struct's fields.
+fn parse_query(input: &ItemStruct, query: &String) -> Result<Query, syn::Error> {
+    let (rest, parsed) = match parse_write_query(query) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Err(syn::Error::new_spanned(
+                query.into_token_stream(),
+                format!("Failed to parse query: {:#?}", e),
+            ))
+        }
+    };
+
+    let rest = rest.trim().trim_end_matches(';');
+    if !rest.is_empty() {
+        return Err(syn::Error::new_spanned(
+            query.into_token_stream(),
+            format!("Failed to parse query, stopped at: {:#?}.\nThe parser's still in development... If you're positive it's valid, rename `query` to `query_nocheck`.", rest),
+        ));
+    }
+
+    let variables = variables(&parsed);
+
+    if variables.iter().any(|v| matches!(v, Variable::Placeholder)) {
+        return Err(syn::Error::new_spanned(
+            query.into_token_stream(),
+            "Cannot use placeholder variables in query",
+        ));
+    }
+
+    let names = variables
+        .iter()
+        .filter_map(|v| match v {
+            Variable::NamedVariable(name) => Some(name.as_str()),
+            Variable::Placeholder => None,
+        })
+        .collect::<Vec<_>>();
+
+    let misses = names
+        .iter()
+        .filter(|name| {
+            !input
+                .fields
+                .iter()
+                .any(|f| f.ident.as_ref().is_some_and(|i| i == *name))
+        })
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+
+    if !misses.is_empty() {
+        return Err(syn::Error::new_spanned(
+            query.into_token_stream(),
+            format!(
+                "Query contains variables that do not match any fields in the struct: {}",
+                misses.join(", ")
+            ),
+        ));
+    }
+
+    let mut unused = input
+        .fields
+        .iter()
+        .filter(|f| {
+            f.ident
+                .as_ref()
+                .is_some_and(|i| !names.iter().any(|name| i == name))
+        })
+        .map(|f| {
+            syn::Error::new_spanned(
+                f,
+                format!(
+                    "Field `{}` is not used in the query",
+                    f.ident.as_ref().unwrap()
+                ),
+            )
+        });
+
+    if let Some(mut error) = unused.next() {
+        for e in unused {
+            error.combine(e);
+        }
+
+        return Err(error);
+    }
+
+    Ok(parsed)
+}
+
+/// Collects the bind variables of a write query, in order.
+fn variables(query: &Query) -> Vec<&Variable> {
+    fn value(value: &Value) -> Option<&Variable> {
+        match value {
+            Value::Variable(variable) => Some(variable),
+            _ => None,
+        }
+    }
+
+    fn using(options: &[UsingOption]) -> impl Iterator<Item = &Variable> {
+        options.iter().filter_map(|option| match option {
+            UsingOption::Ttl(v) | UsingOption::Timestamp(v) => value(v),
+        })
+    }
+
+    fn conditions(clauses: &[WhereClause]) -> impl Iterator<Item = &Variable> {
+        clauses.iter().filter_map(|clause| value(&clause.value))
+    }
+
+    fn if_clause(clause: &Option<IfClause>) -> Vec<&Variable> {
+        match clause {
+            Some(IfClause::Conditions(clauses)) => conditions(clauses).collect(),
+            _ => vec![],
+        }
+    }
+
+    match query {
+        Query::Delete(delete) => using(&delete.using)
+            .chain(conditions(&delete.conditions))
+            .chain(if_clause(&delete.if_clause))
+            .collect(),
+        Query::Update(update) => using(&update.using)
+            .chain(update.assignments.iter().flat_map(|assignment| {
+                let operand = match &assignment.operation {
+                    AssignmentOperation::Set(v)
+                    | AssignmentOperation::Add(v)
+                    | AssignmentOperation::Prepend(v)
+                    | AssignmentOperation::Remove(v) => v,
+                };
+
+                assignment
+                    .index
+                    .as_ref()
+                    .and_then(value)
+                    .into_iter()
+                    .chain(value(operand))
+            }))
+            .chain(conditions(&update.conditions))
+            .chain(if_clause(&update.if_clause))
+            .collect(),
+        Query::Insert(insert) => insert
+            .values
+            .iter()
+            .filter_map(value)
+            .chain(using(&insert.using))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn input() -> ItemStruct {
+        syn::parse_quote! {
+            pub struct UpdatePersonEmail {
+                pub id: Uuid,
+                pub email: String,
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_query() {
+        for query in [
+            "update person set email = :email where id = :id",
+            "delete from person where id = :id and email = :email",
+            "insert into person (id, email) values (:id, :email) if not exists",
+        ] {
+            assert!(parse_query(&input(), &query.to_string()).is_ok(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_parse_query_errors() {
+        let error = |query: &str| {
+            parse_query(&input(), &query.to_string())
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error("update person set email = :emial where id = :id"),
+            "Query contains variables that do not match any fields in the struct: emial"
+        );
+        assert_eq!(
+            error("delete from person where id = :id"),
+            "Field `email` is not used in the query"
+        );
+        assert_eq!(
+            error("update person set email = ? where id = :id"),
+            "Cannot use placeholder variables in query"
+        );
+        assert!(error("upsert person").starts_with("Failed to parse query"));
+    }
+}
@@ -0,0 +1,912 @@
+//! Checks queries against a CQL schema snapshot at compile time.
+//!
+//! The snapshot is a file of CQL statements, eg: a migration or the output of `describe keyspace`,
+//! pointed to by the `SCYLLAX_SCHEMA` environment variable. Relative paths are resolved from the
+//! crate's manifest directory. When the variable isn't set, nothing is checked.
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use scyllax_parser::{
+    Assignment, AssignmentOperation, Column, ColumnDefinition, ComparisonOperator, CqlType,
+    IfClause, Query, Schema, SelectQuery, Table, UsingOption, Value, Variable, WhereClause,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::SystemTime,
+};
+use syn::{GenericArgument, ItemStruct, PathArguments, Type};
+
+use crate::entity::{CollectionOp, EntityDeriveColumn};
+
+/// The environment variable pointing to the schema snapshot
+pub const SCHEMA_ENV: &str = "SCYLLAX_SCHEMA";
+
+/// Parsed schema snapshots, by path, with the time they were last modified
+type Cache = Mutex<HashMap<PathBuf, (SystemTime, Arc<Schema>)>>;
+
+/// A parsed schema snapshot
+pub(crate) struct SchemaSnapshot {
+    path: PathBuf,
+    schema: Arc<Schema>,
+}
+
+/// Loads the schema snapshot, runs `check` against it and returns the tokens that make cargo
+/// rebuild the crate when the snapshot changes.
+pub(crate) fn check(
+    check: impl FnOnce(&SchemaSnapshot) -> Result<(), syn::Error>,
+) -> Result<TokenStream, syn::Error> {
+    match SchemaSnapshot::load()? {
+        Some(snapshot) => {
+            check(&snapshot)?;
+            Ok(snapshot.track())
+        }
+        None => Ok(quote! {}),
+    }
+}
+
+impl SchemaSnapshot {
+    /// Loads the snapshot pointed to by [`SCHEMA_ENV`], if it's set.
+    /// Snapshots are cached for the lifetime of the compiler process, until they're modified.
+    pub(crate) fn load() -> Result<Option<Self>, syn::Error> {
+        static CACHE: OnceLock<Cache> = OnceLock::new();
+
+        let Some(path) = std::env::var_os(SCHEMA_ENV) else {
+            return Ok(None);
+        };
+        let path = match std::env::var_os("CARGO_MANIFEST_DIR") {
+            Some(dir) => Path::new(&dir).join(path),
+            None => PathBuf::from(path),
+        };
+        let error = |message: String| {
+            syn::Error::new(
+                Span::call_site(),
+                format!("Failed to load schema `{}`: {message}", path.display()),
+            )
+        };
+
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| error(e.to_string()))?;
+
+        let mut cache = CACHE
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_at, schema)) = cache.get(&path) {
+            if *cached_at == modified {
+                return Ok(Some(Self {
+                    path,
+                    schema: schema.clone(),
+                }));
+            }
+        }
+
+        let contents = std::fs::read_to_string(&path).map_err(|e| error(e.to_string()))?;
+        let schema = Arc::new(Schema::parse(&contents).map_err(|e| error(e.to_string()))?);
+        cache.insert(path.clone(), (modified, schema.clone()));
+
+        Ok(Some(Self { path, schema }))
+    }
+
+    /// Includes the snapshot in the expansion, so cargo rebuilds the crate when it changes.
+    fn track(&self) -> TokenStream {
+        let path = self.path.to_string_lossy();
+
+        quote! {
+            const _: &[u8] = include_bytes!(#path);
+        }
+    }
+
+    /// Gets a table, or errors on `span` if it isn't in the schema.
+    fn table(&self, name: &str, span: &dyn ToTokens) -> Result<&Table, syn::Error> {
+        self.schema.table(name).ok_or_else(|| {
+            syn::Error::new_spanned(
+                span,
+                format!(
+                    "Table `{name}` does not exist in the schema `{}`",
+                    self.path.display()
+                ),
+            )
+        })
+    }
+
+    /// Checks the table and columns of a read query, and the types of its bound fields.
+    pub(crate) fn check_read(
+        &self,
+        input: &ItemStruct,
+        query: &String,
+        parsed: &SelectQuery,
+        projection: &[&Type],
+    ) -> Result<(), syn::Error> {
+        let span = query.into_token_stream();
+        let mut check = TableCheck::new(self.table(&parsed.table, &span)?, input, &span);
+
+        for (index, column) in parsed.columns.iter().enumerate() {
+            let cql_type = check.selector(column);
+            if let (Some(cql_type), Some(ty)) = (cql_type, projection.get(index)) {
+                if !compatible(ty, &cql_type) {
+                    check.errors.push(syn::Error::new_spanned(
+                        ty,
+                        format!(
+                            "`{}` is not compatible with {cql_type} (`{}`)",
+                            ty.to_token_stream().to_string().replace(' ', ""),
+                            selector(column)
+                        ),
+                    ));
+                }
+            }
+        }
+        check.conditions(&parsed.condition);
+        if let Some(limit) = &parsed.limit {
+            check.bind(limit, &CqlType::Int, |var| format!("limit {var}"));
+        }
+
+        check.finish()
+    }
+
+    /// Checks the table and columns of a write query, and the types of its bound fields.
+    pub(crate) fn check_write(
+        &self,
+        input: &ItemStruct,
+        query: &String,
+        parsed: &Query,
+    ) -> Result<(), syn::Error> {
+        let span = query.into_token_stream();
+
+        match parsed {
+            Query::Delete(delete) => {
+                let mut check = TableCheck::new(self.table(&delete.table, &span)?, input, &span);
+                check.using(&delete.using);
+                for column in &delete.columns {
+                    check.column(column);
+                }
+                check.conditions(&delete.conditions);
+                check.if_clause(&delete.if_clause);
+
+                check.finish()
+            }
+            Query::Update(update) => {
+                let mut check = TableCheck::new(self.table(&update.table, &span)?, input, &span);
+                check.using(&update.using);
+                for assignment in &update.assignments {
+                    check.assignment(assignment);
+                }
+                check.conditions(&update.conditions);
+                check.if_clause(&update.if_clause);
+
+                check.finish()
+            }
+            Query::Insert(insert) => {
+                let mut check = TableCheck::new(self.table(&insert.table, &span)?, input, &span);
+                for (column, value) in insert.columns.iter().zip(&insert.values) {
+                    if let Some(definition) = check.column(column) {
+                        check.bind(value, &definition.cql_type, |var| {
+                            format!("{column} = {var}")
+                        });
+                    }
+                }
+                check.using(&insert.using);
+
+                check.finish()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that every column of an entity exists in the upserted table,
+    /// and that the entity's field types are compatible with them.
+    pub(crate) fn check_upsert(
+        &self,
+        table: &String,
+        columns: &[&EntityDeriveColumn],
+    ) -> Result<(), syn::Error> {
+        let span = table.into_token_stream();
+        let table = self.table(table, &span)?;
+        let mut errors = vec![];
+
+        for column in columns {
+            let name = column.name.as_ref().expect("name is always set");
+            let ident = column.ident.as_ref().expect("named struct");
+            let Some(definition) = table.column(name) else {
+                errors.push(syn::Error::new_spanned(
+                    ident,
+                    format!("Column {name} does not exist in table `{}`", table.name),
+                ));
+                continue;
+            };
+
+            if !compatible(&column.ty, &definition.cql_type) {
+                errors.push(mismatch(
+                    ident,
+                    &column.ty,
+                    &definition.cql_type,
+                    &format!("{name} = :{ident}"),
+                ));
+            }
+
+            // frozen collections can only be overwritten
+            if let Some(op) = column.collection {
+                let supported = matches!(
+                    (&definition.cql_type, op),
+                    (
+                        CqlType::List(_),
+                        CollectionOp::Append | CollectionOp::Prepend | CollectionOp::Remove
+                    ) | (CqlType::Set(_), CollectionOp::Append | CollectionOp::Remove)
+                        | (
+                            CqlType::Map(..),
+                            CollectionOp::Put | CollectionOp::RemoveKeys
+                        )
+                );
+
+                if !supported {
+                    errors.push(syn::Error::new_spanned(
+                        ident,
+                        format!(
+                            "Column {name} ({}) can't be updated with `{}`",
+                            definition.cql_type,
+                            op.name()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        combine(errors)
+    }
+}
+
+/// Collects the errors found while checking a query against a table
+struct TableCheck<'a> {
+    table: &'a Table,
+    input: &'a ItemStruct,
+    span: &'a TokenStream,
+    errors: Vec<syn::Error>,
+}
+
+impl<'a> TableCheck<'a> {
+    fn new(table: &'a Table, input: &'a ItemStruct, span: &'a TokenStream) -> Self {
+        Self {
+            table,
+            input,
+            span,
+            errors: vec![],
+        }
+    }
+
+    fn error(&mut self, message: String) {
+        self.errors
+            .push(syn::Error::new_spanned(self.span, message));
+    }
+
+    /// Gets a column, or records an error if it isn't in the table.
+    fn column(&mut self, name: &str) -> Option<&'a ColumnDefinition> {
+        let column = self.table.column(name);
+        if column.is_none() {
+            self.error(format!(
+                "Column `{name}` does not exist in table `{}`",
+                self.table.name
+            ));
+        }
+
+        column
+    }
+
+    /// Checks the columns of a selector, and returns the type it's read as, if it's known.
+    fn selector(&mut self, column: &Column) -> Option<CqlType> {
+        match column {
+            Column::Identifier(name) => self.column(name).map(|c| c.cql_type.clone()),
+            Column::Asterisk => None,
+            Column::Alias { column, .. } => self.selector(column),
+            Column::Function { name, arguments } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.selector(argument))
+                    .collect::<Vec<_>>();
+
+                match (name.as_str(), arguments.as_slice()) {
+                    ("count" | "writetime" | "token", _) => Some(CqlType::BigInt),
+                    ("ttl", _) => Some(CqlType::Int),
+                    ("min" | "max" | "sum" | "avg", [argument]) => argument.clone(),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// When `value` is a named variable, checks that its field's type is compatible with
+    /// `expected`. `clause` describes where the variable is bound, eg: `id in :ids`.
+    fn bind(&mut self, value: &Value, expected: &CqlType, clause: impl FnOnce(&str) -> String) {
+        let Value::Variable(Variable::NamedVariable(name)) = value else {
+            return;
+        };
+        let Some(field) = self
+            .input
+            .fields
+            .iter()
+            .find(|f| f.ident.as_ref().is_some_and(|i| i == name))
+        else {
+            return;
+        };
+
+        if !compatible(&field.ty, expected) {
+            let clause = clause(&format!(":{name}"));
+            self.errors.push(mismatch(
+                field.ident.as_ref().expect("named struct"),
+                &field.ty,
+                expected,
+                &clause,
+            ));
+        }
+    }
+
+    /// Checks the columns of conditions, and the types of the fields they're compared with.
+    /// - `in` binds a list of the column's type
+    /// - `contains` binds an element of a list or set, or a value of a map
+    /// - `contains key` binds a key of a map
+    fn conditions(&mut self, conditions: &[WhereClause]) {
+        for condition in conditions {
+            let Column::Identifier(column) = &condition.column else {
+                continue;
+            };
+            let Some(definition) = self.column(column) else {
+                continue;
+            };
+
+            let cql_type = unfrozen(&definition.cql_type);
+            let expected = match (&condition.operator, cql_type) {
+                (ComparisonOperator::In, _) => CqlType::List(Box::new(cql_type.clone())),
+                (
+                    ComparisonOperator::Contains,
+                    CqlType::List(element) | CqlType::Set(element) | CqlType::Map(_, element),
+                )
+                | (ComparisonOperator::ContainsKey, CqlType::Map(element, _)) => {
+                    unfrozen(element).clone()
+                }
+                (ComparisonOperator::Contains | ComparisonOperator::ContainsKey, _) => {
+                    self.error(format!(
+                        "Column `{column}` ({cql_type}) cannot be used with `{}`",
+                        condition.operator
+                    ));
+                    continue;
+                }
+                _ => cql_type.clone(),
+            };
+
+            self.bind(&condition.value, &expected, |var| {
+                format!("{column} {} {var}", condition.operator)
+            });
+        }
+    }
+
+    fn if_clause(&mut self, clause: &Option<IfClause>) {
+        if let Some(IfClause::Conditions(conditions)) = clause {
+            self.conditions(conditions);
+        }
+    }
+
+    /// Checks the types of the fields bound to `using ttl` (int) and `using timestamp` (bigint).
+    fn using(&mut self, options: &[UsingOption]) {
+        for option in options {
+            match option {
+                UsingOption::Ttl(value) => {
+                    self.bind(value, &CqlType::Int, |var| format!("using ttl {var}"));
+                }
+                UsingOption::Timestamp(value) => {
+                    self.bind(value, &CqlType::BigInt, |var| {
+                        format!("using timestamp {var}")
+                    });
+                }
+            }
+        }
+    }
+
+    /// Checks the column of an assignment, and the types of the fields it binds.
+    /// - `list[:index] = :value` binds an int and an element
+    /// - `map[:key] = :value` binds a key and a value
+    /// - `map = map - :keys` binds a set of keys
+    /// - everything else binds the column's type
+    fn assignment(&mut self, assignment: &Assignment) {
+        let column = &assignment.column;
+        let Some(definition) = self.column(column) else {
+            return;
+        };
+        let cql_type = unfrozen(&definition.cql_type);
+
+        let (AssignmentOperation::Set(value)
+        | AssignmentOperation::Add(value)
+        | AssignmentOperation::Prepend(value)
+        | AssignmentOperation::Remove(value)) = &assignment.operation;
+
+        if let Some(index) = &assignment.index {
+            let (index_type, value_type) = match cql_type {
+                CqlType::List(element) => (&CqlType::Int, element.as_ref()),
+                CqlType::Map(key, value) => (key.as_ref(), value.as_ref()),
+                _ => {
+                    self.error(format!("Column `{column}` ({cql_type}) cannot be indexed"));
+                    return;
+                }
+            };
+
+            self.bind(index, unfrozen(index_type), |var| {
+                format!("{column}[{var}]")
+            });
+            self.bind(value, unfrozen(value_type), |var| {
+                format!("{column}[..] = {var}")
+            });
+            return;
+        }
+
+        match (&assignment.operation, cql_type) {
+            (AssignmentOperation::Set(_), _) => {
+                self.bind(value, cql_type, |var| format!("{column} = {var}"));
+            }
+            (AssignmentOperation::Add(_), _) => {
+                self.bind(value, cql_type, |var| {
+                    format!("{column} = {column} + {var}")
+                });
+            }
+            (AssignmentOperation::Prepend(_), _) => {
+                self.bind(value, cql_type, |var| {
+                    format!("{column} = {var} + {column}")
+                });
+            }
+            (AssignmentOperation::Remove(_), CqlType::Map(key, _)) => {
+                let keys = CqlType::Set(key.clone());
+                self.bind(value, &keys, |var| format!("{column} = {column} - {var}"));
+            }
+            (AssignmentOperation::Remove(_), _) => {
+                self.bind(value, cql_type, |var| {
+                    format!("{column} = {column} - {var}")
+                });
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), syn::Error> {
+        combine(self.errors)
+    }
+}
+
+/// Formats a selector the way it's written in a query
+pub(crate) fn selector(column: &Column) -> String {
+    match column {
+        Column::Identifier(name) => name.clone(),
+        Column::Asterisk => "*".to_string(),
+        Column::Alias { column, alias } => format!("{} as {alias}", selector(column)),
+        Column::Function { name, arguments } => {
+            let arguments = arguments.iter().map(selector).collect::<Vec<_>>();
+            format!("{name}({})", arguments.join(", "))
+        }
+    }
+}
+
+/// Strips `frozen<..>` from a type
+fn unfrozen(cql_type: &CqlType) -> &CqlType {
+    match cql_type {
+        CqlType::Frozen(inner) => unfrozen(inner),
+        cql_type => cql_type,
+    }
+}
+
+/// Combines errors into one, or returns `Ok` if there are none.
+fn combine(errors: Vec<syn::Error>) -> Result<(), syn::Error> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut error) => {
+            for e in errors {
+                error.combine(e);
+            }
+
+            Err(error)
+        }
+        None => Ok(()),
+    }
+}
+
+/// The error for a field whose type isn't compatible with the type it's bound as
+fn mismatch(ident: &syn::Ident, ty: &Type, expected: &CqlType, clause: &str) -> syn::Error {
+    syn::Error::new_spanned(
+        ty,
+        format!(
+            "Field `{ident}` has type `{}`, which is not compatible with {expected} (`{clause}`)",
+            ty.to_token_stream().to_string().replace(' ', ""),
+        ),
+    )
+}
+
+/// The generic type arguments of a path segment
+fn type_arguments(arguments: &PathArguments) -> Vec<&Type> {
+    match arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Whether a Rust type can be bound to, and read from, a column of the CQL type.
+/// Types that aren't known, eg: enums, json data or user defined types, are always compatible.
+pub(crate) fn compatible(ty: &Type, cql_type: &CqlType) -> bool {
+    if let CqlType::Frozen(inner) = cql_type {
+        return compatible(ty, inner);
+    }
+
+    let path = match ty {
+        Type::Reference(reference) => return compatible(&reference.elem, cql_type),
+        Type::Paren(paren) => return compatible(&paren.elem, cql_type),
+        Type::Group(group) => return compatible(&group.elem, cql_type),
+        Type::Tuple(tuple) => {
+            return match cql_type {
+                CqlType::Tuple(types) => {
+                    tuple.elems.len() == types.len()
+                        && tuple.elems.iter().zip(types).all(|(t, c)| compatible(t, c))
+                }
+                CqlType::UserDefined(_) => true,
+                _ => false,
+            }
+        }
+        Type::Path(path) => path,
+        _ => return true,
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return true;
+    };
+    let arguments = type_arguments(&segment.arguments);
+    let ident = segment.ident.to_string();
+
+    match (ident.as_str(), arguments.as_slice()) {
+        (
+            "Option" | "MaybeUnset" | "Patch" | "Box" | "Arc" | "Append" | "Prepend" | "Remove"
+            | "Put" | "RemoveKeys",
+            [inner],
+        ) => return compatible(inner, cql_type),
+        ("Json", [_]) => return matches!(cql_type, CqlType::Text | CqlType::Ascii),
+        ("CqlValue", _) => return true,
+        _ => {}
+    }
+
+    if let CqlType::UserDefined(_) = cql_type {
+        return true;
+    }
+
+    match (ident.as_str(), arguments.as_slice()) {
+        ("String" | "str", _) => matches!(cql_type, CqlType::Text | CqlType::Ascii),
+        ("i8", _) => cql_type == &CqlType::TinyInt,
+        ("i16", _) => cql_type == &CqlType::SmallInt,
+        ("i32", _) => cql_type == &CqlType::Int,
+        ("i64", _) => cql_type == &CqlType::BigInt,
+        ("f32", _) => cql_type == &CqlType::Float,
+        ("f64", _) => cql_type == &CqlType::Double,
+        ("bool", _) => cql_type == &CqlType::Boolean,
+        ("Counter", _) => cql_type == &CqlType::Counter,
+        ("Uuid", _) => matches!(cql_type, CqlType::Uuid | CqlType::Timeuuid),
+        ("CqlTimeuuid", _) => cql_type == &CqlType::Timeuuid,
+        ("IpAddr" | "Ipv4Addr" | "Ipv6Addr", _) => cql_type == &CqlType::Inet,
+        ("CqlTimestamp" | "DateTime" | "OffsetDateTime", _) => cql_type == &CqlType::Timestamp,
+        ("CqlDate" | "NaiveDate" | "Date", _) => cql_type == &CqlType::Date,
+        ("CqlTime" | "NaiveTime" | "Time", _) => cql_type == &CqlType::Time,
+        ("CqlDuration", _) => cql_type == &CqlType::Duration,
+        ("CqlDecimal" | "BigDecimal", _) => cql_type == &CqlType::Decimal,
+        ("CqlVarint" | "BigInt", _) => cql_type == &CqlType::Varint,
+        ("Bytes", _) => cql_type == &CqlType::Blob,
+        ("Vec", [inner]) if inner.to_token_stream().to_string() == "u8" => {
+            cql_type == &CqlType::Blob
+        }
+        ("Vec" | "HashSet" | "BTreeSet", [inner]) => match cql_type {
+            CqlType::List(element) | CqlType::Set(element) => compatible(inner, element),
+            _ => false,
+        },
+        ("HashMap" | "BTreeMap", [key, value, ..]) => match cql_type {
+            CqlType::Map(k, v) => compatible(key, k) && compatible(value, v),
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use darling::FromDeriveInput;
+    use scyllax_parser::{parse_write_query, select::parse_select};
+
+    fn snapshot() -> SchemaSnapshot {
+        let schema = Schema::parse(
+            r#"
+            create table person (
+                id timeuuid primary key,
+                email text,
+                tags set<text>,
+                attrs map<text, int>,
+                "createdAt" timestamp
+            );
+            create table person_login (
+                id timeuuid,
+                person_id timeuuid,
+                count counter,
+                primary key ((id), person_id)
+            );
+            "#,
+        )
+        .unwrap();
+
+        SchemaSnapshot {
+            path: PathBuf::from("schema.cql"),
+            schema: Arc::new(schema),
+        }
+    }
+
+    fn input() -> ItemStruct {
+        syn::parse_quote! {
+            pub struct Person {
+                pub id: Uuid,
+                pub email: Option<String>,
+                pub tags: MaybeUnset<HashSet<String>>,
+                pub created_at: scylla::frame::value::CqlTimestamp,
+            }
+        }
+    }
+
+    fn read(query: &str) -> Result<(), String> {
+        let parsed = parse_select(query).unwrap().1;
+        snapshot()
+            .check_read(&input(), &query.to_string(), &parsed, &[])
+            .map_err(|e| e.to_string())
+    }
+
+    fn write(query: &str) -> Result<(), String> {
+        let parsed = parse_write_query(query).unwrap().1;
+        snapshot()
+            .check_write(&input(), &query.to_string(), &parsed)
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_compatible() {
+        let ty = |ty: &str| syn::parse_str::<Type>(ty).unwrap();
+
+        assert!(compatible(&ty("Option<String>"), &CqlType::Text));
+        assert!(compatible(&ty("uuid::Uuid"), &CqlType::Timeuuid));
+        assert!(!compatible(&ty("CqlTimeuuid"), &CqlType::Uuid));
+        assert!(compatible(&ty("Vec<u8>"), &CqlType::Blob));
+        assert!(compatible(
+            &ty("HashMap<String, Vec<i32>>"),
+            &CqlType::Map(
+                Box::new(CqlType::Text),
+                Box::new(CqlType::Frozen(Box::new(CqlType::List(Box::new(
+                    CqlType::Int
+                )))))
+            )
+        ));
+        assert!(!compatible(
+            &ty("Vec<i64>"),
+            &CqlType::Set(Box::new(CqlType::Int))
+        ));
+        assert!(compatible(&ty("Json<Vec<LikeData>>"), &CqlType::Text));
+        assert!(compatible(
+            &ty("(Uuid, i64)"),
+            &CqlType::Tuple(vec![CqlType::Uuid, CqlType::BigInt])
+        ));
+        assert!(compatible(&ty("PersonKind"), &CqlType::Int));
+        assert!(!compatible(&ty("i32"), &CqlType::BigInt));
+    }
+
+    #[test]
+    fn test_check_read() {
+        assert_eq!(read("select * from person where id = :id"), Ok(()));
+        assert_eq!(
+            read(r#"select * from person where "createdAt" <= :created_at"#),
+            Ok(())
+        );
+        assert_eq!(
+            read("select * from people where id = :id"),
+            Err("Table `people` does not exist in the schema `schema.cql`".to_string())
+        );
+        assert_eq!(
+            read("select id, name from person where id = :id"),
+            Err("Column `name` does not exist in table `person`".to_string())
+        );
+        assert_eq!(
+            read("select * from person where email = :id"),
+            Err(
+                "Field `id` has type `Uuid`, which is not compatible with text (`email = :id`)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_check_write() {
+        assert_eq!(
+            write(
+                r#"update person set email = :email, tags = tags + :tags, "createdAt" = :created_at where id = :id"#
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            write("insert into person (id, email) values (:id, :email)"),
+            Ok(())
+        );
+        assert_eq!(write("delete tags from person where id = :id"), Ok(()));
+        assert_eq!(
+            write("update person set email = :tags where id = :id"),
+            Err("Field `tags` has type `MaybeUnset<HashSet<String>>`, which is not compatible with text (`email = :tags`)".to_string())
+        );
+        assert_eq!(
+            write("delete from person where createdAt = :created_at"),
+            Err("Column `createdAt` does not exist in table `person`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_projection() {
+        let projection = |query: &str, types: &[&str]| {
+            let parsed = parse_select(query).unwrap().1;
+            let types = types
+                .iter()
+                .map(|ty| syn::parse_str::<Type>(ty).unwrap())
+                .collect::<Vec<_>>();
+
+            snapshot()
+                .check_read(
+                    &input(),
+                    &query.to_string(),
+                    &parsed,
+                    &types.iter().collect::<Vec<_>>(),
+                )
+                .map_err(|e| e.to_string())
+        };
+
+        assert_eq!(
+            projection(
+                "select count(*) as total, max(\"createdAt\") from person",
+                &["i64", "Option<CqlTimestamp>"]
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            projection(
+                "select email, tags from person where id = :id",
+                &["String", "Vec<String>"]
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            projection("select count(*) from person", &["i32"]),
+            Err("`i32` is not compatible with bigint (`count(*)`)".to_string())
+        );
+        assert_eq!(
+            projection("select id, name from person", &["Uuid", "String"]),
+            Err("Column `name` does not exist in table `person`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_binds() {
+        let input: ItemStruct = syn::parse_quote! {
+            pub struct Binds {
+                pub ids: Vec<CqlTimeuuid>,
+                pub tag: String,
+                pub key: String,
+                pub value: i32,
+                pub keys: HashSet<String>,
+                pub rowlimit: i32,
+                pub ttl: i64,
+            }
+        };
+        let read = |query: &str| {
+            let parsed = parse_select(query).unwrap().1;
+            snapshot()
+                .check_read(&input, &query.to_string(), &parsed, &[])
+                .map_err(|e| e.to_string())
+        };
+        let write = |query: &str| {
+            let parsed = parse_write_query(query).unwrap().1;
+            snapshot()
+                .check_write(&input, &query.to_string(), &parsed)
+                .map_err(|e| e.to_string())
+        };
+
+        assert_eq!(
+            read("select * from person where id in :ids and tags contains :tag and attrs contains key :key and attrs contains :value limit :rowlimit"),
+            Ok(())
+        );
+        assert_eq!(
+            write("update person set attrs[:key] = :value, attrs = attrs - :keys where id in :ids"),
+            Ok(())
+        );
+        assert_eq!(
+            read("select * from person where email in :tag"),
+            Err("Field `tag` has type `String`, which is not compatible with list<text> (`email in :tag`)".to_string())
+        );
+        assert_eq!(
+            read("select * from person where tags contains :value"),
+            Err("Field `value` has type `i32`, which is not compatible with text (`tags contains :value`)".to_string())
+        );
+        assert_eq!(
+            read("select * from person where attrs contains key :value"),
+            Err("Field `value` has type `i32`, which is not compatible with text (`attrs contains key :value`)".to_string())
+        );
+        assert_eq!(
+            read("select * from person where email contains :tag"),
+            Err("Column `email` (text) cannot be used with `contains`".to_string())
+        );
+        assert_eq!(
+            read("select * from person where email = :tag limit :ttl"),
+            Err(
+                "Field `ttl` has type `i64`, which is not compatible with int (`limit :ttl`)"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            write("update person using ttl :ttl set attrs[:value] = :key where id in :ids"),
+            Err(
+                "Field `ttl` has type `i64`, which is not compatible with int (`using ttl :ttl`)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_check_upsert() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct PersonLoginEntity {
+                #[entity(primary_key)]
+                pub id: CqlTimeuuid,
+                #[entity(primary_key)]
+                pub person_id: Uuid,
+                #[entity(counter)]
+                pub count: i64,
+                pub last_seen: CqlTimestamp,
+            }
+        };
+        let entity = crate::entity::EntityDerive::from_derive_input(&input).unwrap();
+        let fields = entity.data.as_ref().take_struct().unwrap().fields;
+
+        let errors = snapshot()
+            .check_upsert(&"person_login".to_string(), &fields)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                "Field `count` has type `i64`, which is not compatible with counter (`\"count\" = :count`)",
+                "Column \"last_seen\" does not exist in table `person_login`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_upsert_collections() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct PersonEntity {
+                #[entity(primary_key)]
+                pub id: CqlTimeuuid,
+                #[entity(collection = "append")]
+                pub tags: HashSet<String>,
+                #[entity(collection = "prepend")]
+                pub email: String,
+                #[entity(collection = "put")]
+                pub attrs: HashMap<String, i32>,
+            }
+        };
+        let entity = crate::entity::EntityDerive::from_derive_input(&input).unwrap();
+        let fields = entity.data.as_ref().take_struct().unwrap().fields;
+
+        assert_eq!(
+            snapshot()
+                .check_upsert(&"person".to_string(), &fields)
+                .map_err(|e| e.to_string()),
+            Err("Column \"email\" (text) can't be updated with `prepend`".to_string())
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Enums stored as `text`, generated by `#[derive(TextEnum)]` and `#[text_enum]`
+use darling::{ast, util, FromDeriveInput, FromVariant};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+#[derive(Debug, FromVariant)]
+#[darling(attributes(text_enum))]
+pub struct TextEnumVariant {
+    pub ident: Ident,
+    pub fields: ast::Fields<syn::Type>,
+    /// The text of the variant, if it differs from its name
+    #[darling(default)]
+    pub rename: Option<String>,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(text_enum), supports(enum_unit, enum_tuple))]
+pub struct TextEnumDerive {
+    pub ident: Ident,
+    pub data: ast::Data<TextEnumVariant, util::Ignored>,
+}
+
+pub fn expand_attr(_args: TokenStream, input: TokenStream) -> TokenStream {
+    quote! {
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, scyllax::prelude::TextEnum)]
+        #input
+    }
+}
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let input: syn::DeriveInput = match syn::parse2(input) {
+        Ok(it) => it,
+        Err(e) => return e.to_compile_error(),
+    };
+    let text_enum = match TextEnumDerive::from_derive_input(&input) {
+        Ok(it) => it,
+        Err(e) => return e.write_errors(),
+    };
+
+    match text_enum_impl(&text_enum) {
+        Ok(tokens) => tokens,
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+/// Implements the conversions to and from text, and the scylla traits through them.
+/// A variant holding a single `String` is the catch-all for unknown values.
+fn text_enum_impl(text_enum: &TextEnumDerive) -> Result<TokenStream, syn::Error> {
+    let ident = &text_enum.ident;
+    let enum_name = ident.to_string();
+    let variants = text_enum
+        .data
+        .as_ref()
+        .take_enum()
+        .expect("Should never be struct");
+
+    let (unknown, known): (Vec<_>, Vec<_>) =
+        variants.into_iter().partition(|v| !v.fields.is_unit());
+    let unknown = match unknown.as_slice() {
+        [] => None,
+        [variant] if variant.fields.is_tuple() && variant.fields.len() == 1 => Some(&variant.ident),
+        [variant] => {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "The catch-all variant must hold the unknown value, like `Unknown(String)`",
+            ))
+        }
+        [_, variant, ..] => {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "Only one variant can hold a value, the catch-all for unknown values",
+            ))
+        }
+    };
+
+    let known_idents = known.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let known_texts = known
+        .iter()
+        .map(|v| v.rename.clone().unwrap_or_else(|| v.ident.to_string()))
+        .collect::<Vec<_>>();
+
+    let (as_str_unknown, from_str_unknown) = match unknown {
+        Some(unknown) => (
+            quote!(Self::#unknown(value) => value.as_str(),),
+            quote!(value => Ok(Self::#unknown(value.to_string())),),
+        ),
+        None => (
+            quote!(),
+            quote!(value => Err(scyllax::prelude::InvalidEnumValue::new(#enum_name, value)),),
+        ),
+    };
+
+    Ok(quote! {
+        impl #ident {
+            #[doc = "The text the variant is stored as"]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    #(Self::#known_idents => #known_texts,)*
+                    #as_str_unknown
+                }
+            }
+        }
+
+        impl std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for #ident {
+            type Err = scyllax::prelude::InvalidEnumValue;
+
+            #[doc = "Converts text values to enum values"]
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    #(#known_texts => Ok(Self::#known_idents),)*
+                    #from_str_unknown
+                }
+            }
+        }
+
+        impl scylla::cql_to_rust::FromCqlVal<scylla::frame::response::result::CqlValue> for #ident {
+            fn from_cql(
+                cql_val: scylla::frame::response::result::CqlValue,
+            ) -> Result<Self, scylla::cql_to_rust::FromCqlValError> {
+                let data = <String as scylla::cql_to_rust::FromCqlVal<
+                    scylla::frame::response::result::CqlValue,
+                >>::from_cql(cql_val)?;
+
+                data.parse().map_err(|_| scylla::cql_to_rust::FromCqlValError::BadVal)
+            }
+        }
+
+        impl scylla::frame::value::Value for #ident {
+            fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), scylla::frame::value::ValueTooBig> {
+                <String as scylla::frame::value::Value>::serialize(&self.as_str().to_string(), buf)
+            }
+        }
+
+        impl scylla::serialize::value::SerializeCql for #ident {
+            fn serialize<'b>(
+                &self,
+                typ: &scylla::frame::response::result::ColumnType,
+                writer: scylla::serialize::CellWriter<'b>,
+            ) -> Result<
+                scylla::serialize::writers::WrittenCellProof<'b>,
+                scylla::serialize::SerializationError,
+            > {
+                <String as scylla::serialize::value::SerializeCql>::serialize(
+                    &self.as_str().to_string(),
+                    typ,
+                    writer,
+                )
+            }
+        }
+
+        impl scyllax::prelude::CqlType for #ident {
+            fn cql_type() -> String {
+                "text".to_string()
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn build(input: TokenStream) -> Result<TokenStream, syn::Error> {
+        let text_enum = TextEnumDerive::from_derive_input(&syn::parse2(input).unwrap()).unwrap();
+        text_enum_impl(&text_enum)
+    }
+
+    #[test]
+    fn test_text_enum() {
+        let expanded = build(quote! {
+            pub enum Color {
+                Red,
+                #[text_enum(rename = "dark_blue")]
+                DarkBlue,
+                Unknown(String),
+            }
+        })
+        .unwrap()
+        .to_string();
+
+        let as_str = quote! {
+            match self {
+                Self::Red => "Red",
+                Self::DarkBlue => "dark_blue",
+                Self::Unknown(value) => value.as_str(),
+            }
+        };
+        assert!(expanded.contains(&as_str.to_string()));
+
+        let from_str = quote! {
+            match value {
+                "Red" => Ok(Self::Red),
+                "dark_blue" => Ok(Self::DarkBlue),
+                value => Ok(Self::Unknown(value.to_string())),
+            }
+        };
+        assert!(expanded.contains(&from_str.to_string()));
+    }
+
+    #[test]
+    fn test_text_enum_without_unknown() {
+        let expanded = build(quote! {
+            pub enum Color {
+                Red,
+            }
+        })
+        .unwrap()
+        .to_string();
+
+        let from_str = quote! {
+            value => Err(scyllax::prelude::InvalidEnumValue::new("Color", value)),
+        };
+        assert!(expanded.contains(&from_str.to_string()));
+    }
+
+    #[test]
+    fn test_text_enum_errors() {
+        let error = build(quote! {
+            pub enum Color {
+                Red,
+                Unknown(String, i32),
+            }
+        })
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The catch-all variant must hold the unknown value, like `Unknown(String)`"
+        );
+
+        let error = build(quote! {
+            pub enum Color {
+                Other(String),
+                Unknown(String),
+            }
+        })
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Only one variant can hold a value, the catch-all for unknown values"
+        );
+    }
+}
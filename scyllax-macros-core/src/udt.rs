@@ -0,0 +1,287 @@
+//! User defined types, generated by `#[derive(Udt)]` and `#[udt(...)]`
+use convert_case::{Case, Casing};
+use darling::{ast, ast::NestedMeta, util, FromDeriveInput, FromField, FromMeta};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Ident, Type};
+
+/// The options of the `#[udt(...)]` attribute
+#[derive(Debug, FromMeta)]
+pub struct UdtOptions {
+    /// The name of the type, defaults to the struct's name in snake case
+    pub name: Option<String>,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(udt))]
+pub struct UdtDeriveField {
+    pub ident: Option<Ident>,
+    pub ty: Type,
+    /// The name of the field in the type, if it differs from the struct's field
+    #[darling(default)]
+    pub rename: Option<String>,
+}
+
+impl UdtDeriveField {
+    /// The name of the field in the type
+    fn name(&self) -> String {
+        self.rename
+            .clone()
+            .unwrap_or_else(|| self.ident.as_ref().unwrap().to_string())
+    }
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(udt), supports(struct_named))]
+pub struct UdtDerive {
+    pub ident: Ident,
+    pub data: ast::Data<util::Ignored, UdtDeriveField>,
+    /// The name of the type, defaults to the struct's name in snake case
+    #[darling(default)]
+    pub name: Option<String>,
+}
+
+/// Shorthand for the derives of a UDT, keeping its options for the derive
+pub fn expand_attr(args: TokenStream, input: TokenStream) -> TokenStream {
+    let attr_args = match NestedMeta::parse_meta_list(args) {
+        Ok(args) => args,
+        Err(e) => return darling::Error::from(e).write_errors(),
+    };
+    let options = match UdtOptions::from_list(&attr_args) {
+        Ok(o) => o,
+        Err(e) => return e.write_errors(),
+    };
+    let name = options.name.map(|name| quote!(#[udt(name = #name)]));
+
+    quote! {
+        #[derive(Clone, Debug, PartialEq, scyllax::prelude::Udt)]
+        #name
+        #input
+    }
+}
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = match syn::parse2(input) {
+        Ok(it) => it,
+        Err(e) => return e.to_compile_error(),
+    };
+    let udt = match UdtDerive::from_derive_input(&input) {
+        Ok(udt) => udt,
+        Err(e) => return e.write_errors(),
+    };
+
+    udt_impl(&udt)
+}
+
+/// Implements serialization and deserialization by field name, `CqlType` and `UdtExt`
+fn udt_impl(udt: &UdtDerive) -> TokenStream {
+    let ident = &udt.ident;
+    let udt_name = ident.to_string();
+    let type_name = udt
+        .name
+        .clone()
+        .unwrap_or_else(|| udt_name.to_case(Case::Snake));
+    let fields = udt
+        .data
+        .as_ref()
+        .take_struct()
+        .expect("Should never be enum")
+        .fields;
+
+    let idents = fields
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let names = fields.iter().map(|f| f.name()).collect::<Vec<_>>();
+    let quoted_names = names
+        .iter()
+        .map(|name| format!(r#""{name}""#))
+        .collect::<Vec<_>>();
+
+    quote! {
+        impl scylla::cql_to_rust::FromCqlVal<scylla::frame::response::result::CqlValue> for #ident {
+            fn from_cql(
+                cql_val: scylla::frame::response::result::CqlValue,
+            ) -> Result<Self, scylla::cql_to_rust::FromCqlValError> {
+                let scylla::frame::response::result::CqlValue::UserDefinedType { mut fields, .. } = cql_val else {
+                    return Err(scylla::cql_to_rust::FromCqlValError::BadCqlType);
+                };
+                // fields are matched by name, missing fields are null
+                let mut take = |name: &str| {
+                    fields
+                        .iter_mut()
+                        .find(|(field, _)| field == name)
+                        .and_then(|(_, value)| value.take())
+                };
+
+                Ok(Self {
+                    #(
+                        #idents: <#types as scylla::cql_to_rust::FromCqlVal<
+                            Option<scylla::frame::response::result::CqlValue>,
+                        >>::from_cql(take(#names))?,
+                    )*
+                })
+            }
+        }
+
+        impl scylla::frame::value::Value for #ident {
+            fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), scylla::frame::value::ValueTooBig> {
+                // without the type, the fields are written in the order of the struct
+                let start = buf.len();
+                buf.extend_from_slice(&[0; 4]);
+                #(
+                    <#types as scylla::frame::value::Value>::serialize(&self.#idents, buf)?;
+                )*
+                let len = i32::try_from(buf.len() - start - 4)
+                    .map_err(|_| scylla::frame::value::ValueTooBig)?;
+                buf[start..start + 4].copy_from_slice(&len.to_be_bytes());
+
+                Ok(())
+            }
+        }
+
+        impl scylla::serialize::value::SerializeCql for #ident {
+            fn serialize<'b>(
+                &self,
+                typ: &scylla::frame::response::result::ColumnType,
+                writer: scylla::serialize::CellWriter<'b>,
+            ) -> Result<
+                scylla::serialize::writers::WrittenCellProof<'b>,
+                scylla::serialize::SerializationError,
+            > {
+                let field_types = scyllax::udt::__udt_field_types(#udt_name, typ, &[#(#names),*])?;
+                let mut builder = writer.into_value_builder();
+
+                // fields are written in the order of the type, fields the struct doesn't have are null
+                for (name, field_type) in field_types {
+                    let writer = builder.make_sub_writer();
+                    match name.as_str() {
+                        #(
+                            #names => {
+                                <#types as scylla::serialize::value::SerializeCql>::serialize(
+                                    &self.#idents,
+                                    field_type,
+                                    writer,
+                                )?;
+                            }
+                        )*
+                        _ => {
+                            writer.set_null();
+                        }
+                    }
+                }
+
+                builder
+                    .finish()
+                    .map_err(scylla::serialize::SerializationError::new)
+            }
+        }
+
+        impl scyllax::prelude::CqlType for #ident {
+            fn cql_type() -> String {
+                #type_name.to_string()
+            }
+
+            fn nested_cql_type() -> String {
+                format!("frozen<{}>", #type_name)
+            }
+        }
+
+        impl scyllax::prelude::UdtExt for #ident {
+            fn type_name() -> &'static str {
+                #type_name
+            }
+
+            fn create_type_cql() -> String {
+                // the fields of a type are nested, so their collections are frozen
+                scyllax::ddl::create_type_cql(
+                    #type_name,
+                    &[
+                        #(
+                            scyllax::ddl::Column::new(
+                                #quoted_names,
+                                <#types as scyllax::prelude::CqlType>::nested_cql_type(),
+                            ),
+                        )*
+                    ],
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_udt_names() {
+        let expanded = expand(quote! {
+            pub struct HomeAddress {
+                pub street: String,
+                #[udt(rename = "postCode")]
+                pub post_code: Option<String>,
+            }
+        })
+        .to_string();
+
+        let cql_type = quote! {
+            fn cql_type() -> String {
+                "home_address".to_string()
+            }
+        };
+        assert!(expanded.contains(&cql_type.to_string()));
+
+        let from_cql = quote! {
+            Ok(Self {
+                street: <String as scylla::cql_to_rust::FromCqlVal<
+                    Option<scylla::frame::response::result::CqlValue>,
+                >>::from_cql(take("street"))?,
+                post_code: <Option<String> as scylla::cql_to_rust::FromCqlVal<
+                    Option<scylla::frame::response::result::CqlValue>,
+                >>::from_cql(take("postCode"))?,
+            })
+        };
+        assert!(expanded.contains(&from_cql.to_string()));
+
+        let column = quote! {
+            scyllax::ddl::Column::new(
+                "\"postCode\"",
+                <Option<String> as scyllax::prelude::CqlType>::nested_cql_type(),
+            ),
+        };
+        assert!(expanded.contains(&column.to_string()));
+    }
+
+    #[test]
+    fn test_udt_attr() {
+        let expanded = expand_attr(
+            quote!(name = "address"),
+            quote! {
+                pub struct Address {
+                    pub street: String,
+                }
+            },
+        );
+
+        let expected = quote! {
+            #[derive(Clone, Debug, PartialEq, scyllax::prelude::Udt)]
+            #[udt(name = "address")]
+            pub struct Address {
+                pub street: String,
+            }
+        };
+        assert_eq!(expanded.to_string(), expected.to_string());
+
+        let expanded = expand(expected).to_string();
+        assert!(expanded.contains(
+            &quote!(
+                fn type_name() -> &'static str {
+                    "address"
+                }
+            )
+            .to_string()
+        ));
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! See the [scyllax docs](https://docs.rs/scyllax) for more information.
 use proc_macro::TokenStream;
-use scyllax_macros_core::{entity, json, prepare, queries, r#enum};
+use scyllax_macros_core::{entity, json, partial, prepare, queries, r#enum, text_enum, udt};
 
 /// Apply this attribute to a struct to generate a select query.
 /// ## Single result
@@ -30,15 +30,70 @@ use scyllax_macros_core::{entity, json, prepare, queries, r#enum};
 /// executor.execute_select(GetPeopleByIds { ids, limit }).await?;
 /// // -> Vec<PersonEntity>
 /// ```
+/// ## Projections
+/// When the query selects columns instead of `*`, `return_type` is what each row is read as.
+/// A single column is read as a scalar or a struct with one field, several columns as a tuple
+/// or a struct deriving `Entity` or `PartialEntity`. Tuples have one element per column, and the
+/// fields of structs are checked against the selected columns, in order.
+/// Queries that only select aggregates, like `count(*)`, always return one row,
+/// so their output isn't wrapped in an `Option`.
+/// ```rust,ignore
+/// #[read_query(
+///     query = "select id, email from person where id in :ids limit :rowlimit",
+///     return_type = "Vec<(Uuid, String)>"
+/// )]
+/// pub struct GetEmailsByIds {
+///     pub ids: Vec<Uuid>,
+///     pub rowlimit: i32,
+/// }
+///
+/// #[read_query(query = "select count(*) from person_by_email where email = :email", return_type = "i64")]
+/// pub struct CountPeopleByEmail {
+///     pub email: String,
+/// }
+/// executor.execute_read(CountPeopleByEmail { email }).await?;
+/// // -> i64
+/// ```
+/// ## Exists
+/// `exists` queries return whether any rows match, instead of the rows.
+/// ```rust,ignore
+/// #[read_query(query = "select id from person_by_email where email = :email limit 1", exists)]
+/// pub struct PersonWithEmailExists {
+///     pub email: String,
+/// }
+/// executor.execute_read(PersonWithEmailExists { email }).await?;
+/// // -> bool
+/// ```
+/// ## Admission limits
+/// `rate_limit` (per second), `burst`, `max_in_flight` and `reject_when_limited`
+/// limit how often the query is executed. They're also accepted by [`macro@write_query`].
+/// ```rust,ignore
+/// #[read_query(
+///     query = "select * from person where id = ? limit 1",
+///     return_type = "PersonEntity",
+///     rate_limit = 500,
+///     max_in_flight = 32
+/// )]
+/// pub struct GetPersonById {
+///     pub id: Uuid,
+/// }
+/// ```
+/// ## Schema checks
+/// When `SCYLLAX_SCHEMA` points to a CQL schema snapshot, `read_query`, [`macro@write_query`] and
+/// [`macro@upsert_query`] check that their tables and columns exist, and that field types are
+/// compatible with their columns. Relative paths are resolved from the crate's manifest directory.
 #[proc_macro_derive(ReadQuery, attributes(read_query))]
 pub fn read_query(input: TokenStream) -> TokenStream {
     queries::read::expand(input.into()).into()
 }
 
 /// Apply this attribute to a struct to generate a write query.
+///
+/// `delete`, `update` and `insert` queries are checked at compile time: every named variable
+/// must match a field, and every field must be used. Use `query_nocheck` to skip the checks.
 /// ```rust,ignore
 /// #[write_query(
-///    query = "delete from person where id = ?",
+///    query = "delete from person where id = :id",
 /// )]
 /// pub struct DeletePersonById {
 ///    pub id: Uuid,
@@ -50,6 +105,16 @@ pub fn write_query(args: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 /// Apply this attribute to a entity struct to generate an upsert query.
+///
+/// Options:
+/// * `table`: the table to write to
+/// * `tables`: the tables to write to, in one logged batch, eg: `tables = ["person", "person_by_email"]`
+/// * `keys`: the primary key of the `tables` that don't share the entity's, eg: `keys(person_by_email(email, id))`.
+///   The keys of every table are required fields of the upsert, and `from_diff` fails if one of them changed.
+/// * `delete`: generates a query with this name, deleting the entity from every table
+/// * `ttl`: adds a `set_ttl: i32` field, the ttl of the row in seconds
+/// * `timestamp`: adds a `set_timestamp: i64` field, the write timestamp in microseconds,
+///   can't be used on entities with counter columns
 /// ```rust,ignore
 /// #[upsert_query(table = "person", name = UpsertPerson)]
 /// #[derive(Clone, Debug, FromRow, PartialEq, ValueList, Entity)]
@@ -65,17 +130,140 @@ pub fn upsert_query(args: TokenStream, input: TokenStream) -> TokenStream {
     queries::upsert::expand(args.into(), input.into()).into()
 }
 
+/// Apply this attribute to a entity struct to generate a read query over a range of a clustering key.
+/// The partition keys, and the clustering keys before `column`, must be equal to the fields of the query.
+///
+/// Options:
+/// * `column`: the clustering key field of the range
+/// * `range`: `"gt"` (`>`), `"gte"` (`>=`), `"lt"` (`<`), `"lte"` (`<=`), or `"between"`, which adds
+///   inclusive `{column}_start` and `{column}_end` fields
+/// * `limit`: adds a `rowlimit: i32` field
+/// ```rust,ignore
+/// #[entity]
+/// #[slice_query(table = "comment", name = GetCommentsBefore, column = id, range = "lt", limit)]
+/// pub struct CommentEntity {
+///     #[entity(partition_key)]
+///     pub post_id: uuid::Uuid,
+///     #[entity(clustering_key(order = "desc"))]
+///     pub id: CqlTimeuuid,
+///     pub body: String,
+/// }
+///
+/// executor.execute_read(GetCommentsBefore { post_id, id, rowlimit: 20 }).await?;
+/// // -> Vec<CommentEntity>
+/// ```
+#[proc_macro_attribute]
+pub fn slice_query(args: TokenStream, input: TokenStream) -> TokenStream {
+    queries::slice::expand(args.into(), input.into()).into()
+}
+
+/// Apply this attribute to a entity struct to generate an insert query.
+/// Unlike [`upsert_query`], every column is written, so the fields aren't wrapped in `MaybeUnset`.
+///
+/// Options:
+/// * `if_not_exists`: only inserts the row if it doesn't exist, see `WriteOutcome::applied`
+/// * `ttl`: adds a `set_ttl: i32` field, the ttl of the row in seconds
+/// * `timestamp`: adds a `set_timestamp: i64` field, the write timestamp in microseconds,
+///   can't be combined with `if_not_exists`
+/// ```rust,ignore
+/// #[insert_query(table = "person", name = InsertPerson, if_not_exists, ttl)]
+/// #[derive(Clone, Debug, FromRow, PartialEq, ValueList, Entity)]
+/// pub struct PersonEntity {
+///     #[entity(primary_key)]
+///     pub id: uuid::Uuid,
+///     pub email: String,
+///     pub created_at: i64,
+/// }
+/// // -> insert into person ("id", "email", "created_at") values (:id, :email, :created_at) if not exists using ttl :set_ttl;
+/// ```
+#[proc_macro_attribute]
+pub fn insert_query(args: TokenStream, input: TokenStream) -> TokenStream {
+    queries::insert::expand(args.into(), input.into()).into()
+}
+
 /// Implements [`scyllax::EntityExt`](scyllax::EntityExt) for the struct.
+///
+/// Field attributes:
+/// * `primary_key`: the column is part of the primary key. Without `partition_key` fields, the first
+///   `primary_key` field is the partition key and the others are clustering keys, like `primary key (a, b)`.
+/// * `partition_key`: the column is part of the partition key
+/// * `clustering_key` or `clustering_key(order = "desc")`: the column is a clustering key, in field order
+/// * `static_column`: the column is shared by every row of a partition. Needs a clustering key.
+/// * `counter`: the column is a counter, upserts increment it
+/// * `rename = "..."`: the name of the column, if it differs from the field
+/// * `collection = "..."`: how upserts update a list, set or map column instead of overwriting it,
+///   one of `append`, `prepend`, `remove`, `put` or `remove_keys`
+///
+/// The keys are exposed by `EntityExt::partition_keys` and `EntityExt::clustering_keys`.
 #[proc_macro_derive(Entity, attributes(entity))]
 pub fn entity_derive(input: TokenStream) -> TokenStream {
     entity::expand(input.into()).into()
 }
 
+/// Implements [`scyllax::EntityDdl`](scyllax::EntityDdl) for an entity, next to `Entity`.
+/// `EntityDdl::create_table_cql` renders the table's `create table` statement from the field types,
+/// renames, key roles and static columns. Every field type must implement `scyllax::prelude::CqlType`.
+/// ```rust,ignore
+/// #[derive(Clone, Debug, PartialEq, FromRow, ValueList, Entity, EntityDdl)]
+/// pub struct PostEntity {
+///     #[entity(primary_key)]
+///     pub id: uuid::Uuid,
+///     pub title: String,
+/// }
+///
+/// PostEntity::create_table_cql("post");
+/// ```
+#[proc_macro_derive(EntityDdl, attributes(entity))]
+pub fn entity_ddl_derive(input: TokenStream) -> TokenStream {
+    entity::expand_ddl(input.into()).into()
+}
+
+/// Implements [`scyllax::EntityExt`](scyllax::EntityExt) for a struct holding a subset of an entity's columns,
+/// so `select *` in a read query only selects those columns.
+///
+/// Every field must be a field of the entity, with the same type. Column names and renames are
+/// taken from the entity. A `#[partial(rename = "...")]` on a field is checked against the entity's rename.
+/// ```rust,ignore
+/// #[derive(Clone, Debug, PartialEq, FromRow, ValueList, PartialEntity)]
+/// #[partial(of = PersonEntity)]
+/// pub struct PersonEmail {
+///     pub id: CqlTimeuuid,
+///     pub email: String,
+/// }
+///
+/// #[read_query(query = "select * from person where id = :id limit 1", return_type = "PersonEmail")]
+/// pub struct GetPersonEmailById {
+///     pub id: CqlTimeuuid,
+/// }
+/// // -> select "id", "email" from person where id = :id limit 1
+/// ```
+#[proc_macro_derive(PartialEntity, attributes(partial))]
+pub fn partial_entity_derive(input: TokenStream) -> TokenStream {
+    partial::expand(input.into()).into()
+}
+
 /// Shorthand for applying derive macros on an entity. Essentially:
 /// ```rust,ignore
 /// #[derive(Clone, Debug, FromRow, PartialEq, ValueList, Entity)]
 /// #input
 /// ```
+///
+/// Options:
+/// * `table`: the table of the entity
+/// * `crud`: generates `Get{Name}` and `Delete{Name}` queries by the full primary key, and an
+///   `Upsert{Name}` query, where `{Name}` is the struct's name without the `Entity` suffix.
+///   Requires `table`.
+/// * `ddl`: also derives `EntityDdl`, see [`EntityDdl`](macro@EntityDdl)
+/// ```rust,ignore
+/// #[entity(table = "post", crud)]
+/// pub struct PostEntity {
+///     #[entity(primary_key)]
+///     pub id: uuid::Uuid,
+///     pub title: String,
+/// }
+///
+/// create_query_collection!(PostQueries, [], [], crud = [PostEntity]);
+/// ```
 #[proc_macro_attribute]
 pub fn entity(args: TokenStream, input: TokenStream) -> TokenStream {
     entity::expand_attr(args.into(), input.into()).into()
@@ -104,20 +292,124 @@ pub fn json_data(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Implements
 /// * [`scylla::frame::value::Value`] and
 /// * [`scylla::cql_to_rust::FromCqlVal`]
-/// * [`TryFrom<i32>`]
+/// * [`TryFrom<i32>`], failing with `scyllax::prelude::InvalidEnumValue`
 ///
 /// for an enum. See [`int_enum`] for more.
-#[proc_macro_derive(IntEnum, attributes(rename))]
+#[proc_macro_derive(IntEnum, attributes(rename, int_enum))]
 pub fn int_enum_derive(input: TokenStream) -> TokenStream {
     r#enum::expand(input.into()).into()
 }
 
-/// Sets up a scylla-and-protobuf compatible enum.
+/// Sets up a scylla-and-protobuf compatible enum. Every variant needs an explicit discriminant.
+///
+/// Options:
+/// * `fallback = Variant`: the variant holding the values without a variant, like `Unknown(i32)`,
+///   so rows written by newer versions can still be read. Adds `#[repr(i32)]` to the enum.
+/// * `proto = path::to::Enum`: with the `grpc` feature, converts to and from the matching prost
+///   enumeration, and into the `i32` of prost messages
+/// ```rust,ignore
+/// #[int_enum(fallback = Unknown, proto = crate::proto::PersonKind)]
+/// pub enum PersonKind {
+///     Staff = 0,
+///     Parent = 1,
+///     Unknown(i32),
+/// }
+///
+/// assert_eq!(PersonKind::try_from(7), Ok(PersonKind::Unknown(7)));
+/// ```
 #[proc_macro_attribute]
 pub fn int_enum(args: TokenStream, input: TokenStream) -> TokenStream {
     r#enum::expand_attr(args.into(), input.into()).into()
 }
 
+/// Implements, for a struct mapped to a user defined type:
+/// * [`scylla::serialize::value::SerializeCql`] and [`scylla::cql_to_rust::FromCqlVal`], matching fields by name
+/// * [`scylla::frame::value::Value`], writing the fields in the order of the struct
+/// * `scyllax::prelude::CqlType`, frozen when nested in a collection
+/// * `scyllax::prelude::UdtExt`, which renders the type's `create type` statement
+///
+/// Options:
+/// * `#[udt(name = "...")]`: the name of the type, defaults to the struct's name in snake case
+/// * `#[udt(rename = "...")]` on a field: the name of the field in the type
+///
+/// Fields missing from a row are null, fields of the type the struct doesn't have are written as null.
+#[proc_macro_derive(Udt, attributes(udt))]
+pub fn udt_derive(input: TokenStream) -> TokenStream {
+    udt::expand(input.into()).into()
+}
+
+/// Shorthand for applying derive macros on a user defined type. Essentially:
+/// ```rust,ignore
+/// #[derive(Clone, Debug, PartialEq, scyllax::prelude::Udt)]
+/// #input
+/// ```
+///
+/// ```rust,ignore
+/// #[udt(name = "address")]
+/// pub struct Address {
+///     pub street: String,
+///     #[udt(rename = "postCode")]
+///     pub post_code: Option<String>,
+/// }
+///
+/// #[entity]
+/// pub struct PersonEntity {
+///     #[entity(primary_key)]
+///     pub id: Uuid,
+///     pub address: Option<Address>,
+///     // list<frozen<address>>
+///     pub previous_addresses: Vec<Address>,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn udt(args: TokenStream, input: TokenStream) -> TokenStream {
+    udt::expand_attr(args.into(), input.into()).into()
+}
+
+/// Implements
+/// * [`scylla::frame::value::Value`] and
+/// * [`scylla::cql_to_rust::FromCqlVal`]
+/// * [`std::str::FromStr`], failing with `scyllax::prelude::InvalidEnumValue`, and [`std::fmt::Display`]
+///
+/// for an enum stored as `text`. See [`text_enum`] for more.
+#[proc_macro_derive(TextEnum, attributes(text_enum))]
+pub fn text_enum_derive(input: TokenStream) -> TokenStream {
+    text_enum::expand(input.into()).into()
+}
+
+/// Sets up an enum stored as `text`. Essentially:
+/// ```rust,ignore
+/// #[derive(Clone, Debug, PartialEq, Eq, Hash, scyllax::prelude::TextEnum)]
+/// #input
+/// ```
+///
+/// Variants are stored as their name, or as `#[text_enum(rename = "...")]`.
+/// A variant holding a `String` catches the values without a variant, so rows written by newer
+/// versions can still be read. Without it, reading an unknown value fails.
+/// ```rust,ignore
+/// #[text_enum]
+/// pub enum PostStatus {
+///     #[text_enum(rename = "draft")]
+///     Draft,
+///     #[text_enum(rename = "published")]
+///     Published,
+///     Unknown(String),
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn text_enum(args: TokenStream, input: TokenStream) -> TokenStream {
+    text_enum::expand_attr(args.into(), input.into()).into()
+}
+
+/// Creates a query collection, which prepares its queries when the executor is created.
+/// ```rust,ignore
+/// create_query_collection!(PersonQueries, [GetPersonById], [DeletePersonById, UpsertPerson]);
+/// ```
+///
+/// The queries generated by `#[entity(table = "...", crud)]` are registered with a trailing `crud` list of entities:
+/// ```rust,ignore
+/// create_query_collection!(PostQueries, [], [], crud = [super::model::PostEntity]);
+/// ```
 #[proc_macro]
 pub fn create_query_collection(input: TokenStream) -> TokenStream {
     prepare::expand(input.into()).into()
@@ -4,11 +4,11 @@ use std::fmt::Display;
 use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case},
-    character::complete::{alpha1, alphanumeric1, digit1},
+    character::complete::{alpha1, alphanumeric1, digit1, multispace0, multispace1},
     combinator::{map, recognize},
     error::{ErrorKind, ParseError},
-    multi::many0_count,
-    sequence::{delimited, pair},
+    multi::{many0_count, separated_list1},
+    sequence::{delimited, pair, preceded},
     IResult, InputLength,
 };
 
@@ -22,6 +22,34 @@ pub enum Column {
     Identifier(String),
     /// The column being queried is an asterisk
     Asterisk,
+    /// A function applied to columns, eg: `count(*)` or `writetime(email)`
+    Function {
+        /// The name of the function, lowercased
+        name: String,
+        /// The arguments of the function
+        arguments: Vec<Column>,
+    },
+    /// A selector with an alias, eg: `count(*) as total`
+    Alias {
+        /// The aliased selector
+        column: Box<Column>,
+        /// The alias
+        alias: String,
+    },
+}
+
+impl Column {
+    /// Whether the column is an aggregate function: `count`, `min`, `max`, `sum` or `avg`.
+    /// Queries that only select aggregates always return one row.
+    pub fn is_aggregate(&self) -> bool {
+        match self {
+            Column::Function { name, .. } => {
+                matches!(name.as_str(), "count" | "min" | "max" | "sum" | "avg")
+            }
+            Column::Alias { column, .. } => column.is_aggregate(),
+            Column::Identifier(_) | Column::Asterisk => false,
+        }
+    }
 }
 
 /// Represents a query variable.
@@ -141,6 +169,54 @@ pub fn parse_identifier(input: &str) -> IResult<&str, &str> {
     parse_rust_flavored_variable(input)
 }
 
+/// Parses a table or column name, considering it may be wrapped in quotes.
+pub fn parse_table_name(input: &str) -> IResult<&str, String> {
+    let (input, table) = alt((
+        map(parse_string_escaped_rust_flavored_variable, |x| {
+            format!("\"{x}\"")
+        }),
+        map(parse_rust_flavored_variable, |x: &str| x.to_string()),
+    ))(input)?;
+
+    Ok((input, table.clone()))
+}
+
+/// Represents an option of a `using` clause on a write query
+#[derive(Debug, PartialEq)]
+pub enum UsingOption {
+    /// `ttl <value>`
+    Ttl(Value),
+    /// `timestamp <value>`
+    Timestamp(Value),
+}
+
+/// Parses a using clause
+/// - eg: `using ttl 86400`
+/// - eg: `using ttl :ttl and timestamp :timestamp`
+pub fn parse_using_clause(input: &str) -> IResult<&str, Vec<UsingOption>> {
+    let (input, _) = tag_no_case("using")(input)?;
+    let (input, _) = multispace1(input)?;
+
+    separated_list1(
+        delimited(multispace0, tag_no_case("and"), multispace1),
+        parse_using_option,
+    )(input)
+}
+
+/// Parses a single option of a using clause
+fn parse_using_option(input: &str) -> IResult<&str, UsingOption> {
+    alt((
+        map(
+            preceded(pair(tag_no_case("ttl"), multispace1), parse_value),
+            UsingOption::Ttl,
+        ),
+        map(
+            preceded(pair(tag_no_case("timestamp"), multispace1), parse_value),
+            UsingOption::Timestamp,
+        ),
+    ))(input)
+}
+
 /// Parses a [`Variable::Placeholder`]
 fn parse_placeholder(input: &str) -> IResult<&str, String> {
     let (input, _) = tag("?")(input)?;
@@ -166,6 +242,27 @@ pub(crate) fn eof<I: Copy + InputLength, E: ParseError<I>>(input: I) -> IResult<
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_using_clause() {
+        assert_eq!(
+            parse_using_clause("using ttl 86400"),
+            Ok(("", vec![UsingOption::Ttl(Value::Number(86400))]))
+        );
+        assert_eq!(
+            parse_using_clause("USING TTL :ttl AND TIMESTAMP :timestamp"),
+            Ok((
+                "",
+                vec![
+                    UsingOption::Ttl(Value::Variable(Variable::NamedVariable("ttl".to_string()))),
+                    UsingOption::Timestamp(Value::Variable(Variable::NamedVariable(
+                        "timestamp".to_string()
+                    ))),
+                ]
+            ))
+        );
+    }
     #[test]
     fn test_regular_literal() {
         assert_eq!(super::parse_string("foo"), Ok(("", "foo".to_string())));
@@ -0,0 +1,646 @@
+//! Parses create table and create materialized view queries.
+//! ```ignore
+//! create_table_statement: CREATE ( TABLE | COLUMNFAMILY ) [ IF NOT EXISTS ] `table_name`
+//!                       : '('
+//!                       :     `column_definition` ( ',' `column_definition` )*
+//!                       :     [ ',' PRIMARY KEY '(' `primary_key` ')' ]
+//!                       : ')' [ WITH `table_options` ]
+//! column_definition: `column_name` `cql_type` [ STATIC ] [ PRIMARY KEY]
+//! primary_key: `partition_key` [ ',' `clustering_columns` ]
+//! partition_key: `column_name` | '(' `column_name` ( ',' `column_name` )* ')'
+//! clustering_columns: `column_name` ( ',' `column_name` )*
+//!
+//! create_materialized_view_statement: CREATE MATERIALIZED VIEW [ IF NOT EXISTS ] `view_name` AS
+//!                                   :     `select_statement`
+//!                                   :     PRIMARY KEY '(' `primary_key` ')'
+//!                                   :     [ WITH `table_options` ]
+//! ```
+//! ## Examples
+//! ```cql,ignore
+//! CREATE TABLE IF NOT EXISTS scyllax.person_login (
+//!     id timeuuid,
+//!     person_id timeuuid,
+//!     count counter,
+//!     PRIMARY KEY ((id), person_id)
+//! ) WITH CLUSTERING ORDER BY (person_id DESC);
+//! ```
+use std::fmt::Display;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{multispace0, multispace1},
+    combinator::{map, opt, rest},
+    error::Error,
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    Err, IResult,
+};
+
+use crate::common::parse_table_name;
+
+/// Represents a create table query
+#[derive(Debug, PartialEq)]
+pub struct CreateTableQuery {
+    /// The keyspace of the table, if qualified
+    pub keyspace: Option<String>,
+    /// The name of the table
+    pub table: String,
+    /// Whether the query has `if not exists`
+    pub if_not_exists: bool,
+    /// The columns of the table
+    pub columns: Vec<ColumnDefinition>,
+    /// The primary key of the table
+    pub primary_key: PrimaryKey,
+}
+
+/// Represents a create materialized view query
+#[derive(Debug, PartialEq)]
+pub struct CreateMaterializedViewQuery {
+    /// The keyspace of the view, if qualified
+    pub keyspace: Option<String>,
+    /// The name of the view
+    pub view: String,
+    /// Whether the query has `if not exists`
+    pub if_not_exists: bool,
+    /// The table the view is built from
+    pub base_table: String,
+    /// The columns selected from the base table. Empty when every column is selected.
+    pub columns: Vec<String>,
+    /// The primary key of the view
+    pub primary_key: PrimaryKey,
+}
+
+/// Represents a column of a create table query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    /// The name of the column
+    pub name: String,
+    /// The type of the column
+    pub cql_type: CqlType,
+    /// Whether the column is static
+    pub is_static: bool,
+}
+
+/// Represents the primary key of a table or view
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PrimaryKey {
+    /// The columns of the partition key
+    pub partition_key: Vec<String>,
+    /// The clustering columns
+    pub clustering_columns: Vec<String>,
+}
+
+/// Represents a CQL data type
+#[derive(Debug, Clone, PartialEq)]
+pub enum CqlType {
+    /// `ascii`
+    Ascii,
+    /// `bigint`
+    BigInt,
+    /// `blob`
+    Blob,
+    /// `boolean`
+    Boolean,
+    /// `counter`
+    Counter,
+    /// `date`
+    Date,
+    /// `decimal`
+    Decimal,
+    /// `double`
+    Double,
+    /// `duration`
+    Duration,
+    /// `float`
+    Float,
+    /// `inet`
+    Inet,
+    /// `int`
+    Int,
+    /// `smallint`
+    SmallInt,
+    /// `text` or `varchar`
+    Text,
+    /// `time`
+    Time,
+    /// `timestamp`
+    Timestamp,
+    /// `timeuuid`
+    Timeuuid,
+    /// `tinyint`
+    TinyInt,
+    /// `uuid`
+    Uuid,
+    /// `varint`
+    Varint,
+    /// `list<type>`
+    List(Box<CqlType>),
+    /// `set<type>`
+    Set(Box<CqlType>),
+    /// `map<key, value>`
+    Map(Box<CqlType>, Box<CqlType>),
+    /// `tuple<type, ...>`
+    Tuple(Vec<CqlType>),
+    /// `frozen<type>`
+    Frozen(Box<CqlType>),
+    /// A user defined type
+    UserDefined(String),
+}
+
+impl Display for CqlType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CqlType::Ascii => write!(f, "ascii"),
+            CqlType::BigInt => write!(f, "bigint"),
+            CqlType::Blob => write!(f, "blob"),
+            CqlType::Boolean => write!(f, "boolean"),
+            CqlType::Counter => write!(f, "counter"),
+            CqlType::Date => write!(f, "date"),
+            CqlType::Decimal => write!(f, "decimal"),
+            CqlType::Double => write!(f, "double"),
+            CqlType::Duration => write!(f, "duration"),
+            CqlType::Float => write!(f, "float"),
+            CqlType::Inet => write!(f, "inet"),
+            CqlType::Int => write!(f, "int"),
+            CqlType::SmallInt => write!(f, "smallint"),
+            CqlType::Text => write!(f, "text"),
+            CqlType::Time => write!(f, "time"),
+            CqlType::Timestamp => write!(f, "timestamp"),
+            CqlType::Timeuuid => write!(f, "timeuuid"),
+            CqlType::TinyInt => write!(f, "tinyint"),
+            CqlType::Uuid => write!(f, "uuid"),
+            CqlType::Varint => write!(f, "varint"),
+            CqlType::List(inner) => write!(f, "list<{inner}>"),
+            CqlType::Set(inner) => write!(f, "set<{inner}>"),
+            CqlType::Map(key, value) => write!(f, "map<{key}, {value}>"),
+            CqlType::Tuple(types) => {
+                let types = types
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "tuple<{types}>")
+            }
+            CqlType::Frozen(inner) => write!(f, "frozen<{inner}>"),
+            CqlType::UserDefined(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for CreateTableQuery {
+    type Error = Err<Error<&'a str>>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(parse_create_table(value)?.1)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for CreateMaterializedViewQuery {
+    type Error = Err<Error<&'a str>>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(parse_create_materialized_view(value)?.1)
+    }
+}
+
+/// Parses a comma, with any surrounding whitespace
+fn comma(input: &str) -> IResult<&str, &str> {
+    delimited(multispace0, tag(","), multispace0)(input)
+}
+
+/// Parses `if not exists`
+fn parse_if_not_exists(input: &str) -> IResult<&str, bool> {
+    let (input, exists) = opt(tuple((
+        tag_no_case("if"),
+        multispace1,
+        tag_no_case("not"),
+        multispace1,
+        tag_no_case("exists"),
+        multispace1,
+    )))(input)?;
+
+    Ok((input, exists.is_some()))
+}
+
+/// Parses a table name that may be qualified by a keyspace
+/// - eg: `person`
+/// - eg: `scyllax."person_by_createdAt"`
+fn parse_qualified_name(input: &str) -> IResult<&str, (Option<String>, String)> {
+    let (input, first) = parse_table_name(input)?;
+    let (input, second) = opt(preceded(tag("."), parse_table_name))(input)?;
+
+    Ok((
+        input,
+        match second {
+            Some(table) => (Some(first), table),
+            None => (None, first),
+        },
+    ))
+}
+
+/// Parses the type arguments of a collection, tuple or frozen type
+/// - eg: `<text, int>`
+fn parse_type_arguments(input: &str) -> IResult<&str, Vec<CqlType>> {
+    delimited(
+        pair(multispace0, tag("<")),
+        separated_list1(comma, delimited(multispace0, parse_cql_type, multispace0)),
+        tag(">"),
+    )(input)
+}
+
+/// Parses a [`CqlType`]
+/// - eg: `text`
+/// - eg: `map<text, frozen<list<int>>>`
+pub fn parse_cql_type(input: &str) -> IResult<&str, CqlType> {
+    let (rest, name) = parse_table_name(input)?;
+    let fail = || Err(Err::Error(Error::new(input, nom::error::ErrorKind::Tag)));
+
+    let cql_type = match name.to_lowercase().as_str() {
+        "ascii" => CqlType::Ascii,
+        "bigint" => CqlType::BigInt,
+        "blob" => CqlType::Blob,
+        "boolean" => CqlType::Boolean,
+        "counter" => CqlType::Counter,
+        "date" => CqlType::Date,
+        "decimal" => CqlType::Decimal,
+        "double" => CqlType::Double,
+        "duration" => CqlType::Duration,
+        "float" => CqlType::Float,
+        "inet" => CqlType::Inet,
+        "int" => CqlType::Int,
+        "smallint" => CqlType::SmallInt,
+        "text" | "varchar" => CqlType::Text,
+        "time" => CqlType::Time,
+        "timestamp" => CqlType::Timestamp,
+        "timeuuid" => CqlType::Timeuuid,
+        "tinyint" => CqlType::TinyInt,
+        "uuid" => CqlType::Uuid,
+        "varint" => CqlType::Varint,
+        kind @ ("list" | "set" | "frozen" | "map" | "tuple") => {
+            let (rest, mut types) = parse_type_arguments(rest)?;
+            let cql_type = match (kind, types.len()) {
+                ("list", 1) => CqlType::List(Box::new(types.remove(0))),
+                ("set", 1) => CqlType::Set(Box::new(types.remove(0))),
+                ("frozen", 1) => CqlType::Frozen(Box::new(types.remove(0))),
+                ("map", 2) => {
+                    let value = types.remove(1);
+                    CqlType::Map(Box::new(types.remove(0)), Box::new(value))
+                }
+                ("tuple", _) => CqlType::Tuple(types),
+                _ => return fail(),
+            };
+
+            return Ok((rest, cql_type));
+        }
+        _ => CqlType::UserDefined(name),
+    };
+
+    Ok((rest, cql_type))
+}
+
+/// Parses a column definition
+/// - eg: `id timeuuid primary key`
+/// - eg: `"createdAt" timestamp`
+/// - eg: `owner text static`
+fn parse_column_definition(input: &str) -> IResult<&str, (ColumnDefinition, bool)> {
+    let (input, name) = parse_table_name(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, cql_type) = parse_cql_type(input)?;
+    let (input, is_static) = opt(preceded(multispace1, tag_no_case("static")))(input)?;
+    let (input, primary_key) = opt(tuple((
+        multispace1,
+        tag_no_case("primary"),
+        multispace1,
+        tag_no_case("key"),
+    )))(input)?;
+
+    Ok((
+        input,
+        (
+            ColumnDefinition {
+                name,
+                cql_type,
+                is_static: is_static.is_some(),
+            },
+            primary_key.is_some(),
+        ),
+    ))
+}
+
+/// Parses a primary key definition
+/// - eg: `primary key (id)`
+/// - eg: `primary key ((id, bucket), created_at, email)`
+fn parse_primary_key(input: &str) -> IResult<&str, PrimaryKey> {
+    let columns = |input| separated_list1(comma, parse_table_name)(input);
+
+    let (input, _) = tuple((
+        tag_no_case("primary"),
+        multispace1,
+        tag_no_case("key"),
+        multispace0,
+        tag("("),
+        multispace0,
+    ))(input)?;
+    let (input, partition_key) = alt((
+        delimited(
+            pair(tag("("), multispace0),
+            columns,
+            pair(multispace0, tag(")")),
+        ),
+        map(parse_table_name, |column| vec![column]),
+    ))(input)?;
+    let (input, clustering_columns) = opt(preceded(comma, columns))(input)?;
+    let (input, _) = pair(multispace0, tag(")"))(input)?;
+
+    Ok((
+        input,
+        PrimaryKey {
+            partition_key,
+            clustering_columns: clustering_columns.unwrap_or_default(),
+        },
+    ))
+}
+
+/// Parses the `with` options of a table or view. They're not kept.
+fn parse_table_options(input: &str) -> IResult<&str, ()> {
+    map(
+        opt(tuple((multispace1, tag_no_case("with"), multispace1, rest))),
+        |_| (),
+    )(input)
+}
+
+/// Parses a create table query
+pub fn parse_create_table(input: &str) -> IResult<&str, CreateTableQuery> {
+    enum Definition {
+        Column(ColumnDefinition, bool),
+        PrimaryKey(PrimaryKey),
+    }
+
+    let (input, _) = tuple((
+        tag_no_case("create"),
+        multispace1,
+        alt((tag_no_case("table"), tag_no_case("columnfamily"))),
+        multispace1,
+    ))(input)?;
+    let (input, if_not_exists) = parse_if_not_exists(input)?;
+    let (input, (keyspace, table)) = parse_qualified_name(input)?;
+    let (input, _) = pair(multispace0, tag("("))(input)?;
+    let (input, definitions) = delimited(
+        multispace0,
+        separated_list1(
+            comma,
+            alt((
+                map(parse_primary_key, Definition::PrimaryKey),
+                map(parse_column_definition, |(column, primary_key)| {
+                    Definition::Column(column, primary_key)
+                }),
+            )),
+        ),
+        tuple((multispace0, opt(tag(",")), multispace0)),
+    )(input)?;
+    let (input, _) = tag(")")(input)?;
+    let (input, _) = parse_table_options(input)?;
+
+    let mut columns = vec![];
+    let mut primary_key = PrimaryKey::default();
+    for definition in definitions {
+        match definition {
+            Definition::Column(column, true) => {
+                primary_key.partition_key = vec![column.name.clone()];
+                columns.push(column);
+            }
+            Definition::Column(column, false) => columns.push(column),
+            Definition::PrimaryKey(key) => primary_key = key,
+        }
+    }
+
+    Ok((
+        input,
+        CreateTableQuery {
+            keyspace,
+            table,
+            if_not_exists,
+            columns,
+            primary_key,
+        },
+    ))
+}
+
+/// Parses the selected columns of a materialized view
+/// - eg: `*`
+/// - eg: `id, email`
+fn parse_view_columns(input: &str) -> IResult<&str, Vec<String>> {
+    alt((
+        map(tag("*"), |_| vec![]),
+        separated_list1(comma, parse_table_name),
+    ))(input)
+}
+
+/// Parses the `where` clause of a materialized view
+/// - eg: `where email is not null and id is not null`
+fn parse_view_where_clause(input: &str) -> IResult<&str, Vec<String>> {
+    preceded(
+        pair(tag_no_case("where"), multispace1),
+        separated_list1(
+            tuple((multispace1, tag_no_case("and"), multispace1)),
+            terminated(
+                parse_table_name,
+                tuple((
+                    multispace1,
+                    tag_no_case("is"),
+                    multispace1,
+                    tag_no_case("not"),
+                    multispace1,
+                    tag_no_case("null"),
+                )),
+            ),
+        ),
+    )(input)
+}
+
+/// Parses a create materialized view query
+pub fn parse_create_materialized_view(input: &str) -> IResult<&str, CreateMaterializedViewQuery> {
+    let (input, _) = tuple((
+        tag_no_case("create"),
+        multispace1,
+        tag_no_case("materialized"),
+        multispace1,
+        tag_no_case("view"),
+        multispace1,
+    ))(input)?;
+    let (input, if_not_exists) = parse_if_not_exists(input)?;
+    let (input, (keyspace, view)) = parse_qualified_name(input)?;
+    let (input, _) = tuple((
+        multispace1,
+        tag_no_case("as"),
+        multispace1,
+        tag_no_case("select"),
+        multispace1,
+    ))(input)?;
+    let (input, columns) = parse_view_columns(input)?;
+    let (input, _) = tuple((multispace1, tag_no_case("from"), multispace1))(input)?;
+    let (input, (_, base_table)) = parse_qualified_name(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, _) = parse_view_where_clause(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, primary_key) = parse_primary_key(input)?;
+    let (input, _) = parse_table_options(input)?;
+
+    Ok((
+        input,
+        CreateMaterializedViewQuery {
+            keyspace,
+            view,
+            if_not_exists,
+            base_table,
+            columns,
+            primary_key,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn column(name: &str, cql_type: CqlType) -> ColumnDefinition {
+        ColumnDefinition {
+            name: name.to_string(),
+            cql_type,
+            is_static: false,
+        }
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_cql_type() {
+        assert_eq!(parse_cql_type("varchar"), Ok(("", CqlType::Text)));
+        assert_eq!(
+            parse_cql_type("map<text, frozen<list<int>>>"),
+            Ok((
+                "",
+                CqlType::Map(
+                    Box::new(CqlType::Text),
+                    Box::new(CqlType::Frozen(Box::new(CqlType::List(Box::new(
+                        CqlType::Int
+                    )))))
+                )
+            ))
+        );
+        assert_eq!(
+            parse_cql_type("tuple<uuid, bigint>"),
+            Ok(("", CqlType::Tuple(vec![CqlType::Uuid, CqlType::BigInt])))
+        );
+        assert_eq!(
+            parse_cql_type("address"),
+            Ok(("", CqlType::UserDefined("address".to_string())))
+        );
+        assert_eq!(
+            CqlType::Map(
+                Box::new(CqlType::Text),
+                Box::new(CqlType::Set(Box::new(CqlType::Int)))
+            )
+            .to_string(),
+            "map<text, set<int>>"
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table() {
+        let input = r#"create table if not exists person (
+            id timeuuid primary key,
+            email text,
+            "createdAt" timestamp
+        )"#;
+
+        assert_eq!(
+            parse_create_table(input),
+            Ok((
+                "",
+                CreateTableQuery {
+                    keyspace: None,
+                    table: "person".to_string(),
+                    if_not_exists: true,
+                    columns: vec![
+                        column("id", CqlType::Timeuuid),
+                        column("email", CqlType::Text),
+                        column("\"createdAt\"", CqlType::Timestamp),
+                    ],
+                    primary_key: PrimaryKey {
+                        partition_key: names(&["id"]),
+                        clustering_columns: vec![],
+                    },
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table_compound_key() {
+        let input = "CREATE TABLE scyllax.person_login (
+            id timeuuid,
+            person_id timeuuid,
+            owner text static,
+            count counter,
+            PRIMARY KEY ((id), person_id)
+        ) WITH CLUSTERING ORDER BY (person_id DESC) AND comment = ''";
+
+        assert_eq!(
+            parse_create_table(input),
+            Ok((
+                "",
+                CreateTableQuery {
+                    keyspace: Some("scyllax".to_string()),
+                    table: "person_login".to_string(),
+                    if_not_exists: false,
+                    columns: vec![
+                        column("id", CqlType::Timeuuid),
+                        column("person_id", CqlType::Timeuuid),
+                        ColumnDefinition {
+                            name: "owner".to_string(),
+                            cql_type: CqlType::Text,
+                            is_static: true,
+                        },
+                        column("count", CqlType::Counter),
+                    ],
+                    primary_key: PrimaryKey {
+                        partition_key: names(&["id"]),
+                        clustering_columns: names(&["person_id"]),
+                    },
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_create_materialized_view() {
+        let input = r#"create materialized view if not exists scyllax."person_by_createdAt" as
+            select *
+            from person
+            where "createdAt" is not null and id is not null
+            primary key (("createdAt"), id)"#;
+
+        assert_eq!(
+            parse_create_materialized_view(input),
+            Ok((
+                "",
+                CreateMaterializedViewQuery {
+                    keyspace: Some("scyllax".to_string()),
+                    view: "\"person_by_createdAt\"".to_string(),
+                    if_not_exists: true,
+                    base_table: "person".to_string(),
+                    columns: vec![],
+                    primary_key: PrimaryKey {
+                        partition_key: names(&["\"createdAt\""]),
+                        clustering_columns: names(&["id"]),
+                    },
+                }
+            ))
+        );
+    }
+}
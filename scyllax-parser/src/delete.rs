@@ -1,11 +1,24 @@
 //! delete query
+//! ```cql
+//! delete_statement: DELETE [ `simple_selection` ( ',' `simple_selection` ) ]
+//!                 : FROM `table_name`
+//!                 : [ USING TIMESTAMP `integer` ]
+//!                 : WHERE `where_clause`
+//!                 : [ IF ( EXISTS | `condition` ( AND `condition` )* ) ]
+//! ```
 use nom::{
-    bytes::complete::tag_no_case, character::complete::multispace0, error::Error, Err, IResult,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{multispace0, multispace1},
+    combinator::{opt, peek},
+    error::Error,
+    multi::separated_list1,
+    sequence::{delimited, pair, terminated},
+    Err, IResult,
 };
 
 use crate::{
-    common::parse_identifier,
-    r#where::{parse_where_clause, WhereClause},
+    common::{parse_table_name, parse_using_clause, UsingOption},
+    r#where::{parse_if_clause, parse_where_clause, IfClause, WhereClause},
 };
 
 /// Represents a delete query
@@ -13,8 +26,14 @@ use crate::{
 pub struct DeleteQuery {
     /// The table being queried
     pub table: String,
+    /// The columns being deleted. Empty when the whole row is deleted.
+    pub columns: Vec<String>,
+    /// The options of the `using` clause
+    pub using: Vec<UsingOption>,
     /// The conditions of the query
     pub conditions: Vec<WhereClause>,
+    /// The condition of the lightweight transaction
+    pub if_clause: Option<IfClause>,
 }
 
 impl<'a> TryFrom<&'a str> for DeleteQuery {
@@ -25,18 +44,40 @@ impl<'a> TryFrom<&'a str> for DeleteQuery {
     }
 }
 
+/// Parses the columns being deleted, up to the `from`
+/// - eg: `email, age `
+fn parse_delete_columns(input: &str) -> IResult<&str, Vec<String>> {
+    terminated(
+        separated_list1(
+            delimited(multispace0, tag(","), multispace0),
+            parse_table_name,
+        ),
+        pair(multispace1, peek(tag_no_case("from "))),
+    )(input)
+}
+
 /// Parses a delete query
 pub fn parse_delete(input: &str) -> IResult<&str, DeleteQuery> {
-    let (input, _) = tag_no_case("delete from ")(input)?;
-    let (input, table) = parse_identifier(input)?;
+    let (input, _) = tag_no_case("delete ")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, columns) = opt(parse_delete_columns)(input)?;
+    let (input, _) = tag_no_case("from ")(input)?;
+    let (input, table) = parse_table_name(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, using) = opt(parse_using_clause)(input)?;
     let (input, _) = multispace0(input)?;
     let (input, conditions) = parse_where_clause(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, if_clause) = opt(parse_if_clause)(input)?;
 
     Ok((
         input,
         DeleteQuery {
-            table: table.to_string(),
+            table,
+            columns: columns.unwrap_or_default(),
+            using: using.unwrap_or_default(),
             conditions,
+            if_clause,
         },
     ))
 }
@@ -52,11 +93,36 @@ mod tests {
 
         let expected = DeleteQuery {
             table: "person".to_string(),
+            columns: vec![],
+            using: vec![],
             conditions: vec![WhereClause {
                 column: Column::Identifier("id".to_string()),
                 operator: r#where::ComparisonOperator::Equal,
                 value: Value::Variable(Variable::Placeholder),
             }],
+            if_clause: None,
+        };
+
+        assert_eq!(parse_delete(input), Ok(("", expected)));
+    }
+
+    #[test]
+    fn test_parse_delete_full() {
+        let input =
+            "delete email, age from person using timestamp :timestamp where id = :id if exists";
+
+        let expected = DeleteQuery {
+            table: "person".to_string(),
+            columns: vec!["email".to_string(), "age".to_string()],
+            using: vec![UsingOption::Timestamp(Value::Variable(
+                Variable::NamedVariable("timestamp".to_string()),
+            ))],
+            conditions: vec![WhereClause {
+                column: Column::Identifier("id".to_string()),
+                operator: r#where::ComparisonOperator::Equal,
+                value: Value::Variable(Variable::NamedVariable("id".to_string())),
+            }],
+            if_clause: Some(IfClause::Exists),
         };
 
         assert_eq!(parse_delete(input), Ok(("", expected)));
@@ -0,0 +1,136 @@
+//! insert query
+//! ```cql
+//! insert_statement: INSERT INTO `table_name` `names_values`
+//!                 : [ IF NOT EXISTS ]
+//!                 : [ USING `update_parameter` ( AND `update_parameter` )* ]
+//! names_values: `names` VALUES `tuple_literal`
+//! names: '(' `column_name` ( ',' `column_name` )* ')'
+//! ```
+use nom::{
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{multispace0, multispace1},
+    combinator::opt,
+    error::Error,
+    multi::separated_list1,
+    sequence::{delimited, tuple},
+    Err, IResult,
+};
+
+use crate::{
+    common::{parse_table_name, parse_using_clause, parse_value, UsingOption},
+    Value,
+};
+
+/// Represents an insert query
+#[derive(Debug, PartialEq)]
+pub struct InsertQuery {
+    /// The table being queried
+    pub table: String,
+    /// The columns being inserted
+    pub columns: Vec<String>,
+    /// The values being inserted, in the same order as `columns`
+    pub values: Vec<Value>,
+    /// Whether the insert is a lightweight transaction (`if not exists`)
+    pub if_not_exists: bool,
+    /// The options of the `using` clause
+    pub using: Vec<UsingOption>,
+}
+
+impl<'a> TryFrom<&'a str> for InsertQuery {
+    type Error = Err<Error<&'a str>>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(parse_insert(value)?.1)
+    }
+}
+
+/// Parses a parenthesized, comma separated list
+/// - eg: `(id, email)`
+fn parse_tuple<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    delimited(
+        tuple((tag("("), multispace0)),
+        separated_list1(delimited(multispace0, tag(","), multispace0), item),
+        tuple((multispace0, tag(")"))),
+    )
+}
+
+/// Parses an insert query
+pub fn parse_insert(input: &str) -> IResult<&str, InsertQuery> {
+    let (input, _) = tag_no_case("insert into ")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, table) = parse_table_name(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, columns) = parse_tuple(parse_table_name)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("values")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, values) = parse_tuple(parse_value)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, if_not_exists) = opt(tuple((
+        tag_no_case("if"),
+        multispace1,
+        tag_no_case("not"),
+        multispace1,
+        tag_no_case("exists"),
+    )))(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, using) = opt(parse_using_clause)(input)?;
+
+    Ok((
+        input,
+        InsertQuery {
+            table,
+            columns,
+            values,
+            if_not_exists: if_not_exists.is_some(),
+            using: using.unwrap_or_default(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use pretty_assertions::assert_eq;
+
+    fn var(name: &str) -> Value {
+        Value::Variable(Variable::NamedVariable(name.to_string()))
+    }
+
+    #[test]
+    fn test_parse_insert() {
+        let input = "insert into person (id, email) values (:id, :email)";
+
+        let expected = InsertQuery {
+            table: "person".to_string(),
+            columns: vec!["id".to_string(), "email".to_string()],
+            values: vec![var("id"), var("email")],
+            if_not_exists: false,
+            using: vec![],
+        };
+
+        assert_eq!(parse_insert(input), Ok(("", expected)));
+    }
+
+    #[test]
+    fn test_parse_insert_full() {
+        let input = "INSERT INTO person (id, \"createdAt\") VALUES (:id, :created_at) \
+            IF NOT EXISTS USING TTL :ttl AND TIMESTAMP :timestamp";
+
+        let expected = InsertQuery {
+            table: "person".to_string(),
+            columns: vec!["id".to_string(), "\"createdAt\"".to_string()],
+            values: vec![var("id"), var("created_at")],
+            if_not_exists: true,
+            using: vec![
+                UsingOption::Ttl(var("ttl")),
+                UsingOption::Timestamp(var("timestamp")),
+            ],
+        };
+
+        assert_eq!(parse_insert(input), Ok(("", expected)));
+    }
+}
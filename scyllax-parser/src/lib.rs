@@ -3,17 +3,27 @@
 pub mod comment;
 pub mod common;
 pub mod create_keyspace;
+pub mod create_table;
 pub mod delete;
+pub mod insert;
 pub mod reserved;
+pub mod schema;
 pub mod select;
+pub mod update;
 pub mod r#where;
 
 use comment::parse_comment;
-pub use common::{Column, Value, Variable};
+pub use common::{Column, UsingOption, Value, Variable};
 use create_keyspace::CreateKeyspaceQuery;
+pub use create_table::{
+    ColumnDefinition, CqlType, CreateMaterializedViewQuery, CreateTableQuery, PrimaryKey,
+};
 pub use delete::DeleteQuery;
-pub use r#where::{ComparisonOperator, WhereClause};
+pub use insert::InsertQuery;
+pub use r#where::{ComparisonOperator, IfClause, WhereClause};
+pub use schema::{Schema, SchemaError, Table};
 pub use select::SelectQuery;
+pub use update::{Assignment, AssignmentOperation, UpdateQuery};
 
 use nom::{branch::alt, combinator::map, error::Error, multi::many0, Err, IResult};
 
@@ -47,8 +57,16 @@ pub enum Query {
     Select(SelectQuery),
     /// A delete query
     Delete(DeleteQuery),
+    /// An update query
+    Update(UpdateQuery),
+    /// An insert query
+    Insert(InsertQuery),
     /// A create keyspace query
     CreateKeyspace(CreateKeyspaceQuery),
+    /// A create table query
+    CreateTable(CreateTableQuery),
+    /// A create materialized view query
+    CreateMaterializedView(CreateMaterializedViewQuery),
 }
 
 /// Parse a CQL query.
@@ -63,13 +81,29 @@ pub fn parse_query(input: &str) -> IResult<&str, Query> {
     alt((
         map(select::parse_select, Query::Select),
         map(delete::parse_delete, Query::Delete),
+        map(update::parse_update, Query::Update),
+        map(insert::parse_insert, Query::Insert),
         map(
             create_keyspace::parse_create_keyspace,
             Query::CreateKeyspace,
         ),
+        map(create_table::parse_create_table, Query::CreateTable),
+        map(
+            create_table::parse_create_materialized_view,
+            Query::CreateMaterializedView,
+        ),
     ))(input)
 }
 
+/// Parse a CQL write query: a delete, update or insert.
+pub fn parse_write_query(input: &str) -> IResult<&str, Query> {
+    alt((
+        map(delete::parse_delete, Query::Delete),
+        map(update::parse_update, Query::Update),
+        map(insert::parse_insert, Query::Insert),
+    ))(input.trim())
+}
+
 impl<'a> TryFrom<&'a str> for Query {
     type Error = Err<Error<&'a str>>;
 
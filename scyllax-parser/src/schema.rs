@@ -0,0 +1,301 @@
+//! Builds a [`Schema`] from a file of CQL statements, eg: a migration or the output of
+//! `describe keyspace`.
+//!
+//! Only `create table` and `create materialized view` statements are considered,
+//! every other statement is skipped. Tables are looked up by name, regardless of their keyspace.
+use std::{collections::HashMap, fmt::Display};
+
+use crate::create_table::{
+    parse_create_materialized_view, parse_create_table, ColumnDefinition, PrimaryKey,
+};
+
+/// The tables and views of a schema
+#[derive(Debug, Default, PartialEq)]
+pub struct Schema {
+    tables: HashMap<String, Table>,
+}
+
+/// A table or materialized view of a [`Schema`]
+#[derive(Debug, PartialEq)]
+pub struct Table {
+    /// The name of the table, as written in the schema
+    pub name: String,
+    /// The columns of the table
+    pub columns: Vec<ColumnDefinition>,
+    /// The primary key of the table
+    pub primary_key: PrimaryKey,
+    /// The table the view is built from, if this is a materialized view
+    pub base_table: Option<String>,
+}
+
+/// An error building a [`Schema`]
+#[derive(Debug, PartialEq)]
+pub enum SchemaError {
+    /// A `create table` or `create materialized view` statement couldn't be parsed
+    InvalidStatement(String),
+    /// A materialized view is built from a table that isn't in the schema
+    UnknownBaseTable {
+        /// The name of the view
+        view: String,
+        /// The name of the missing table
+        table: String,
+    },
+    /// A materialized view selects a column that isn't in its base table
+    UnknownViewColumn {
+        /// The name of the view
+        view: String,
+        /// The name of the missing column
+        column: String,
+    },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::InvalidStatement(statement) => {
+                write!(f, "Failed to parse statement: {statement}")
+            }
+            SchemaError::UnknownBaseTable { view, table } => {
+                write!(f, "View `{view}` is built from unknown table `{table}`")
+            }
+            SchemaError::UnknownViewColumn { view, column } => {
+                write!(f, "View `{view}` selects unknown column `{column}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Normalizes a table or column name the way CQL compares them:
+/// quoted names are case sensitive, unquoted names are not.
+/// - `person` -> `person`
+/// - `Person` -> `person`
+/// - `"createdAt"` -> `createdAt`
+pub fn normalize_identifier(name: &str) -> String {
+    match name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+    {
+        Some(quoted) => quoted.to_string(),
+        None => name.to_lowercase(),
+    }
+}
+
+impl Table {
+    /// Gets a column by name. The name is normalized with [`normalize_identifier`].
+    pub fn column(&self, name: &str) -> Option<&ColumnDefinition> {
+        let name = normalize_identifier(name);
+        self.columns
+            .iter()
+            .find(|column| normalize_identifier(&column.name) == name)
+    }
+}
+
+impl Schema {
+    /// Builds a schema from a file of `;` separated CQL statements.
+    pub fn parse(input: &str) -> Result<Self, SchemaError> {
+        let mut schema = Schema::default();
+
+        for statement in split_statements(input) {
+            let words = statement
+                .split_whitespace()
+                .take(3)
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>();
+            let invalid = || SchemaError::InvalidStatement(statement.clone());
+
+            match words.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                ["create", "table" | "columnfamily", ..] => {
+                    let table = match parse_create_table(&statement) {
+                        Ok(("", table)) => table,
+                        _ => return Err(invalid()),
+                    };
+
+                    schema.insert(Table {
+                        name: table.table,
+                        columns: table.columns,
+                        primary_key: table.primary_key,
+                        base_table: None,
+                    });
+                }
+                ["create", "materialized", "view"] => {
+                    let view = match parse_create_materialized_view(&statement) {
+                        Ok(("", view)) => view,
+                        _ => return Err(invalid()),
+                    };
+
+                    let base = schema.table(&view.base_table).ok_or_else(|| {
+                        SchemaError::UnknownBaseTable {
+                            view: view.view.clone(),
+                            table: view.base_table.clone(),
+                        }
+                    })?;
+
+                    let columns = if view.columns.is_empty() {
+                        base.columns.clone()
+                    } else {
+                        view.columns
+                            .iter()
+                            .map(|name| {
+                                base.column(name).cloned().ok_or_else(|| {
+                                    SchemaError::UnknownViewColumn {
+                                        view: view.view.clone(),
+                                        column: name.clone(),
+                                    }
+                                })
+                            })
+                            .collect::<Result<Vec<_>, _>>()?
+                    };
+
+                    schema.insert(Table {
+                        name: view.view,
+                        columns,
+                        primary_key: view.primary_key,
+                        base_table: Some(view.base_table),
+                    });
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Gets a table or view by name. The name is normalized with [`normalize_identifier`].
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.get(&normalize_identifier(name))
+    }
+
+    /// Iterates over the tables and views of the schema.
+    pub fn tables(&self) -> impl Iterator<Item = &Table> {
+        self.tables.values()
+    }
+
+    fn insert(&mut self, table: Table) {
+        self.tables.insert(normalize_identifier(&table.name), table);
+    }
+}
+
+/// Splits a file into its `;` separated statements, removing comments.
+/// Semicolons and comment markers inside of strings and quoted names are kept.
+fn split_statements(input: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                current.push(c);
+                for inner in chars.by_ref() {
+                    current.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            '-' | '/' if chars.peek() == Some(&c) => {
+                for inner in chars.by_ref() {
+                    if inner == '\n' {
+                        current.push(inner);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for inner in chars.by_ref() {
+                    if previous == '*' && inner == '/' {
+                        break;
+                    }
+                    previous = inner;
+                }
+                current.push(' ');
+            }
+            ';' => statements.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    statements.push(current);
+
+    statements
+        .into_iter()
+        .map(|statement| statement.trim().to_string())
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::create_table::CqlType;
+    use pretty_assertions::assert_eq;
+
+    const SCHEMA: &str = r#"
+        create keyspace scyllax with replication = {'class': 'SimpleStrategy','replication_factor': 1};
+        use scyllax;
+
+        /* people; and their emails */
+        create table if not exists scyllax.person (
+            id timeuuid primary key,
+            email text, -- unique
+            "createdAt" timestamp
+        );
+
+        insert into person(id, email) values (e01e84d6-414c-11ee-be56-0242ac120002, 'foo;bar');
+
+        create materialized view if not exists "person_by_createdAt" as
+            select * from person
+            where "createdAt" is not null and id is not null
+            primary key ("createdAt", id);
+    "#;
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("select 1; -- a; comment\nselect 'a;b' /* c; */;"),
+            vec!["select 1".to_string(), "select 'a;b'".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_schema() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+
+        let person = schema.table("Person").unwrap();
+        assert_eq!(person.columns.len(), 3);
+        assert_eq!(person.column("ID").unwrap().cql_type, CqlType::Timeuuid);
+        assert_eq!(
+            person.column("\"createdAt\"").unwrap().cql_type,
+            CqlType::Timestamp
+        );
+        assert!(person.column("createdAt").is_none());
+
+        let view = schema.table("\"person_by_createdAt\"").unwrap();
+        assert_eq!(view.base_table, Some("person".to_string()));
+        assert_eq!(view.columns, person.columns);
+        assert!(schema.table("person_by_createdat").is_none());
+    }
+
+    #[test]
+    fn test_parse_schema_errors() {
+        assert_eq!(
+            Schema::parse("create table person (id timeuuid primary key, email)"),
+            Err(SchemaError::InvalidStatement(
+                "create table person (id timeuuid primary key, email)".to_string()
+            ))
+        );
+        assert_eq!(
+            Schema::parse(
+                "create materialized view person_by_email as select * from person \
+                where email is not null primary key (email, id)"
+            ),
+            Err(SchemaError::UnknownBaseTable {
+                view: "person_by_email".to_string(),
+                table: "person".to_string(),
+            })
+        );
+    }
+}
@@ -30,14 +30,12 @@ use nom::{
     combinator::{map, opt},
     error::Error,
     multi::separated_list0,
+    sequence::{delimited, preceded, tuple},
     Err, IResult,
 };
 
 use crate::{
-    common::{
-        parse_identifier, parse_limit_clause, parse_rust_flavored_variable,
-        parse_string_escaped_rust_flavored_variable,
-    },
+    common::{parse_identifier, parse_limit_clause, parse_table_name},
     r#where::{parse_where_clause, WhereClause},
     Column, Value,
 };
@@ -69,31 +67,68 @@ impl<'a> TryFrom<&'a str> for SelectQuery {
 /// note: allow selection of all columns: `select * from person`
 fn parse_select_clause(input: &str) -> IResult<&str, Vec<Column>> {
     separated_list0(
-        tag(", "),
-        map(parse_identifier, |ident| {
-            Column::Identifier(ident.to_string())
-        }),
+        delimited(multispace0, tag(","), multispace0),
+        parse_selector,
     )(input)
 }
 
+/// Parses a selector, with an optional alias
+/// - eg: `id`
+/// - eg: `"createdAt"`
+/// - eg: `count(*) as total`
+/// - eg: `writetime(email)`
+fn parse_selector(input: &str) -> IResult<&str, Column> {
+    let (input, column) = alt((parse_function, map(parse_table_name, Column::Identifier)))(input)?;
+    let (input, alias) = opt(preceded(
+        tuple((multispace1, tag_no_case("as"), multispace1)),
+        parse_table_name,
+    ))(input)?;
+
+    Ok((
+        input,
+        match alias {
+            Some(alias) => Column::Alias {
+                column: Box::new(column),
+                alias,
+            },
+            None => column,
+        },
+    ))
+}
+
+/// Parses a function call
+/// - eg: `count(*)`
+/// - eg: `token(id, email)`
+fn parse_function(input: &str) -> IResult<&str, Column> {
+    let (input, name) = parse_identifier(input)?;
+    let (input, arguments) = delimited(
+        tuple((multispace0, tag("("), multispace0)),
+        separated_list0(
+            delimited(multispace0, tag(","), multispace0),
+            alt((
+                parse_asterisk,
+                parse_function,
+                map(parse_table_name, Column::Identifier),
+            )),
+        ),
+        tuple((multispace0, tag(")"))),
+    )(input)?;
+
+    Ok((
+        input,
+        Column::Function {
+            name: name.to_lowercase(),
+            arguments,
+        },
+    ))
+}
+
 /// Parses the columns as `*`
 fn parse_asterisk(input: &str) -> IResult<&str, Column> {
     let (input, _) = tag("*")(input)?;
     Ok((input, Column::Asterisk))
 }
 
-/// Parses a table name, considering it may be wrapped in quotes.
-fn parse_table_name(input: &str) -> IResult<&str, String> {
-    let (input, table) = alt((
-        map(parse_string_escaped_rust_flavored_variable, |x| {
-            format!("\"{x}\"")
-        }),
-        map(parse_rust_flavored_variable, |x: &str| x.to_string()),
-    ))(input)?;
-
-    Ok((input, table.clone()))
-}
-
 /// Parses a select query
 pub fn parse_select(input: &str) -> IResult<&str, SelectQuery> {
     let (input, _) = tag_no_case("select ")(input)?;
@@ -179,6 +214,39 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_select_clause_functions() {
+        assert_eq!(
+            parse_select_clause(r#"count(*) as total, writetime("createdAt"), email as address"#),
+            Ok((
+                "",
+                vec![
+                    Column::Alias {
+                        column: Box::new(Column::Function {
+                            name: "count".to_string(),
+                            arguments: vec![Column::Asterisk],
+                        }),
+                        alias: "total".to_string(),
+                    },
+                    Column::Function {
+                        name: "writetime".to_string(),
+                        arguments: vec![Column::Identifier("\"createdAt\"".to_string())],
+                    },
+                    Column::Alias {
+                        column: Box::new(Column::Identifier("email".to_string())),
+                        alias: "address".to_string(),
+                    },
+                ]
+            ))
+        );
+
+        let (_, columns) = parse_select_clause("COUNT(*), max(age), email").unwrap();
+        assert_eq!(
+            columns.iter().map(Column::is_aggregate).collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+    }
+
     #[test]
     fn test_parse_limit_clause() {
         assert_eq!(
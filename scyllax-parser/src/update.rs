@@ -0,0 +1,245 @@
+//! update query
+//! ```cql
+//! update_statement: UPDATE `table_name`
+//!                 : [ USING `update_parameter` ( AND `update_parameter` )* ]
+//!                 : SET `assignment` ( ',' `assignment` )*
+//!                 : WHERE `where_clause`
+//!                 : [ IF ( EXISTS | `condition` ( AND `condition` )* ) ]
+//! update_parameter: ( TIMESTAMP | TTL ) ( `integer` | `bind_marker` )
+//! assignment: `simple_selection` '=' `term`
+//!           : | `column_name` '=' `column_name` ( '+' | '-' ) `term`
+//!           : | `column_name` '=' `list_literal` '+' `column_name`
+//! simple_selection: `column_name`
+//!                 : | `column_name` '[' `term` ']'
+//! ```
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::multispace0,
+    combinator::{map, opt},
+    error::Error,
+    multi::separated_list1,
+    sequence::{delimited, tuple},
+    Err, IResult,
+};
+
+use crate::{
+    common::{parse_table_name, parse_using_clause, parse_value, UsingOption},
+    r#where::{parse_if_clause, parse_where_clause, IfClause, WhereClause},
+    Value,
+};
+
+/// Represents an update query
+#[derive(Debug, PartialEq)]
+pub struct UpdateQuery {
+    /// The table being queried
+    pub table: String,
+    /// The options of the `using` clause
+    pub using: Vec<UsingOption>,
+    /// The assignments of the `set` clause
+    pub assignments: Vec<Assignment>,
+    /// The conditions of the query
+    pub conditions: Vec<WhereClause>,
+    /// The condition of the lightweight transaction
+    pub if_clause: Option<IfClause>,
+}
+
+/// Represents a single assignment in a `set` clause
+#[derive(Debug, PartialEq)]
+pub struct Assignment {
+    /// The column being assigned
+    pub column: String,
+    /// The map key or list index being assigned, eg: `tags[:key] = :value`
+    pub index: Option<Value>,
+    /// The operation being applied to the column
+    pub operation: AssignmentOperation,
+}
+
+/// Represents the operation of an assignment
+#[derive(Debug, PartialEq)]
+pub enum AssignmentOperation {
+    /// `column = <value>`
+    Set(Value),
+    /// `column = column + <value>`
+    Add(Value),
+    /// `column = <value> + column`
+    Prepend(Value),
+    /// `column = column - <value>`
+    Remove(Value),
+}
+
+impl<'a> TryFrom<&'a str> for UpdateQuery {
+    type Error = Err<Error<&'a str>>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(parse_update(value)?.1)
+    }
+}
+
+/// Parses the right hand side of an assignment to `column`
+fn parse_assignment_operation<'a>(
+    column: &str,
+    input: &'a str,
+) -> IResult<&'a str, AssignmentOperation> {
+    let column_name = |input: &'a str| {
+        let (rest, name) = parse_table_name(input)?;
+        if name == column {
+            Ok((rest, name))
+        } else {
+            Err(Err::Error(Error::new(input, nom::error::ErrorKind::Tag)))
+        }
+    };
+    let operator = |op: &'static str| delimited(multispace0, tag(op), multispace0);
+
+    alt((
+        map(
+            tuple((column_name, operator("+"), parse_value)),
+            |(_, _, value)| AssignmentOperation::Add(value),
+        ),
+        map(
+            tuple((column_name, operator("-"), parse_value)),
+            |(_, _, value)| AssignmentOperation::Remove(value),
+        ),
+        map(
+            tuple((parse_value, operator("+"), column_name)),
+            |(value, _, _)| AssignmentOperation::Prepend(value),
+        ),
+        map(parse_value, AssignmentOperation::Set),
+    ))(input)
+}
+
+/// Parses a single assignment
+/// - eg: `email = :email`
+/// - eg: `tags = tags + :tags`
+/// - eg: `tags[:key] = :value`
+fn parse_assignment(input: &str) -> IResult<&str, Assignment> {
+    let (input, column) = parse_table_name(input)?;
+    let (input, index) = opt(delimited(tag("["), parse_value, tag("]")))(input)?;
+    let (input, _) = delimited(multispace0, tag("="), multispace0)(input)?;
+    let (input, operation) = parse_assignment_operation(&column, input)?;
+
+    Ok((
+        input,
+        Assignment {
+            column,
+            index,
+            operation,
+        },
+    ))
+}
+
+/// Parses an update query
+pub fn parse_update(input: &str) -> IResult<&str, UpdateQuery> {
+    let (input, _) = tag_no_case("update ")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, table) = parse_table_name(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, using) = opt(parse_using_clause)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("set ")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, assignments) = separated_list1(
+        delimited(multispace0, tag(","), multispace0),
+        parse_assignment,
+    )(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, conditions) = parse_where_clause(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, if_clause) = opt(parse_if_clause)(input)?;
+
+    Ok((
+        input,
+        UpdateQuery {
+            table,
+            using: using.unwrap_or_default(),
+            assignments,
+            conditions,
+            if_clause,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use pretty_assertions::assert_eq;
+
+    fn var(name: &str) -> Value {
+        Value::Variable(Variable::NamedVariable(name.to_string()))
+    }
+
+    #[test]
+    fn test_parse_update() {
+        let input = "update person set email = :email where id = :id";
+
+        let expected = UpdateQuery {
+            table: "person".to_string(),
+            using: vec![],
+            assignments: vec![Assignment {
+                column: "email".to_string(),
+                index: None,
+                operation: AssignmentOperation::Set(var("email")),
+            }],
+            conditions: vec![WhereClause {
+                column: Column::Identifier("id".to_string()),
+                operator: r#where::ComparisonOperator::Equal,
+                value: var("id"),
+            }],
+            if_clause: None,
+        };
+
+        assert_eq!(parse_update(input), Ok(("", expected)));
+    }
+
+    #[test]
+    fn test_parse_update_full() {
+        let input = "UPDATE person USING TTL :ttl SET email = :email, tags = tags + :tags, \
+            history = :history + history, scores = scores - :scores, attrs[:key] = :value \
+            WHERE id = :id IF email = :old_email";
+
+        let expected = UpdateQuery {
+            table: "person".to_string(),
+            using: vec![UsingOption::Ttl(var("ttl"))],
+            assignments: vec![
+                Assignment {
+                    column: "email".to_string(),
+                    index: None,
+                    operation: AssignmentOperation::Set(var("email")),
+                },
+                Assignment {
+                    column: "tags".to_string(),
+                    index: None,
+                    operation: AssignmentOperation::Add(var("tags")),
+                },
+                Assignment {
+                    column: "history".to_string(),
+                    index: None,
+                    operation: AssignmentOperation::Prepend(var("history")),
+                },
+                Assignment {
+                    column: "scores".to_string(),
+                    index: None,
+                    operation: AssignmentOperation::Remove(var("scores")),
+                },
+                Assignment {
+                    column: "attrs".to_string(),
+                    index: Some(var("key")),
+                    operation: AssignmentOperation::Set(var("value")),
+                },
+            ],
+            conditions: vec![WhereClause {
+                column: Column::Identifier("id".to_string()),
+                operator: r#where::ComparisonOperator::Equal,
+                value: var("id"),
+            }],
+            if_clause: Some(IfClause::Conditions(vec![WhereClause {
+                column: Column::Identifier("email".to_string()),
+                operator: r#where::ComparisonOperator::Equal,
+                value: var("old_email"),
+            }])),
+        };
+
+        assert_eq!(parse_update(input), Ok(("", expected)));
+    }
+}
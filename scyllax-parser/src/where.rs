@@ -8,9 +8,10 @@
 use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case},
-    character::complete::multispace0,
+    character::complete::{multispace0, multispace1},
     combinator::{map, opt},
-    multi::separated_list0,
+    multi::{separated_list0, separated_list1},
+    sequence::tuple,
     IResult,
 };
 
@@ -44,6 +45,38 @@ pub fn parse_where_clause(input: &str) -> IResult<&str, Vec<WhereClause>> {
     separated_list0(tag_no_case("and"), parse_where_condition)(input)
 }
 
+/// Represents the condition of a lightweight transaction
+#[derive(Debug, PartialEq)]
+pub enum IfClause {
+    /// `if exists`
+    Exists,
+    /// `if not exists`
+    NotExists,
+    /// `if <condition> ( and <condition> )*`
+    Conditions(Vec<WhereClause>),
+}
+
+/// Parses the condition of a lightweight transaction
+/// - eg: `if exists`
+/// - eg: `if not exists`
+/// - eg: `if email = :old_email`
+pub fn parse_if_clause(input: &str) -> IResult<&str, IfClause> {
+    let (input, _) = tag_no_case("if")(input)?;
+    let (input, _) = multispace1(input)?;
+
+    alt((
+        map(tag_no_case("exists"), |_| IfClause::Exists),
+        map(
+            tuple((tag_no_case("not"), multispace1, tag_no_case("exists"))),
+            |_| IfClause::NotExists,
+        ),
+        map(
+            separated_list1(tag_no_case("and"), parse_where_condition),
+            IfClause::Conditions,
+        ),
+    ))(input)
+}
+
 /// Represents a single `where` clause on a CQL statement
 #[derive(Debug, PartialEq)]
 pub struct WhereClause {
@@ -77,6 +110,21 @@ pub enum ComparisonOperator {
     ContainsKey,
 }
 
+impl std::fmt::Display for ComparisonOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComparisonOperator::Equal => write!(f, "="),
+            ComparisonOperator::GreaterThan => write!(f, ">"),
+            ComparisonOperator::LessThan => write!(f, "<"),
+            ComparisonOperator::GreaterThanOrEqual => write!(f, ">="),
+            ComparisonOperator::LessThanOrEqual => write!(f, "<="),
+            ComparisonOperator::In => write!(f, "in"),
+            ComparisonOperator::Contains => write!(f, "contains"),
+            ComparisonOperator::ContainsKey => write!(f, "contains key"),
+        }
+    }
+}
+
 /// Parses the column in a where statement, considering it may be wrapped in quotes.
 fn parse_where_column(input: &str) -> IResult<&str, String> {
     let (input, col) = alt((
@@ -227,6 +275,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_if_clause() {
+        assert_eq!(parse_if_clause("if exists"), Ok(("", IfClause::Exists)));
+        assert_eq!(
+            parse_if_clause("IF NOT EXISTS"),
+            Ok(("", IfClause::NotExists))
+        );
+        assert_eq!(
+            parse_if_clause("if email = :old_email"),
+            Ok((
+                "",
+                IfClause::Conditions(vec![WhereClause {
+                    column: Column::Identifier("email".to_string()),
+                    operator: ComparisonOperator::Equal,
+                    value: Value::Variable(Variable::NamedVariable("old_email".to_string()))
+                }])
+            ))
+        );
+    }
+
     #[test]
     fn test_parse_comparison_operator() {
         assert_eq!(
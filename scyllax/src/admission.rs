@@ -0,0 +1,271 @@
+//! Admission control: rate limits and in-flight caps per query type.
+//!
+//! Limits are set in the query's attributes, or with [`crate::builder::ExecutorBuilder::rate_limit_for`],
+//! [`crate::builder::ExecutorBuilder::max_in_flight_for`] and [`crate::builder::ExecutorBuilder::admission_policy_for`].
+//! ```rust,ignore
+//! #[read_query(
+//!     query = "select * from person where id = :id limit 1",
+//!     return_type = "PersonEntity",
+//!     rate_limit = 500,
+//!     max_in_flight = 32,
+//!     reject_when_limited
+//! )]
+//! pub struct GetPersonById {
+//!     pub id: Uuid,
+//! }
+//! ```
+//!
+//! Limits apply to executions, so requestors coalesced onto an execution that's already
+//! in flight don't count against them. Coalesced reads are admitted by their read task before
+//! the execution starts, one at a time and in the order they were received. While an execution
+//! waits to be admitted, requests for the same key are coalesced onto it and the read task keeps
+//! coalescing requests onto executions that are in flight.
+use crate::{builder::ExecutorOptions, error::ScyllaxError, queries::Query};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// What happens to an execution that exceeds its limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdmissionPolicy {
+    /// Wait until the execution is within its limits
+    #[default]
+    Wait,
+    /// Fail the execution with [`ScyllaxError::RateLimited`]
+    Reject,
+}
+
+/// A token bucket rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of executions allowed per second, on average
+    pub per_second: u32,
+    /// The number of executions allowed in a burst
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Creates a rate limit.
+    ///
+    /// # Panics
+    /// If `per_second` or `burst` is 0, since no execution would ever be admitted.
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        assert!(per_second > 0, "a rate limit's `per_second` must be greater than 0");
+        assert!(burst > 0, "a rate limit's `burst` must be greater than 0");
+
+        Self { per_second, burst }
+    }
+}
+
+/// The admission limits of a query type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AdmissionLimits {
+    /// Limits how often the query can be executed
+    pub rate_limit: Option<RateLimit>,
+    /// Limits how many executions of the query can be in flight at once
+    pub max_in_flight: Option<usize>,
+    /// What happens to executions that exceed the limits
+    pub policy: AdmissionPolicy,
+}
+
+/// A token bucket, refilled continuously
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    per_second: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        let capacity = limit.burst as f64;
+
+        Self {
+            capacity,
+            tokens: capacity,
+            per_second: limit.per_second as f64,
+            refilled_at: now,
+        }
+    }
+
+    /// Takes a token, or returns how long it'll be until one is available.
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.per_second).min(self.capacity);
+        self.refilled_at = self.refilled_at.max(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / self.per_second,
+        ))
+    }
+}
+
+/// The admission state of a single query type
+#[derive(Debug)]
+pub(crate) struct Admission {
+    bucket: Option<Mutex<TokenBucket>>,
+    in_flight: Option<Arc<Semaphore>>,
+    policy: AdmissionPolicy,
+}
+
+impl Admission {
+    fn new(limits: &AdmissionLimits) -> Self {
+        Self {
+            bucket: limits
+                .rate_limit
+                .map(|limit| Mutex::new(TokenBucket::new(limit, Instant::now()))),
+            in_flight: limits
+                .max_in_flight
+                .map(|max| Arc::new(Semaphore::new(max))),
+            policy: limits.policy,
+        }
+    }
+
+    /// Admits an execution, waiting or failing if it exceeds the limits.
+    ///
+    /// The execution counts as in flight until the returned permit is dropped.
+    pub(crate) async fn admit(
+        &self,
+        query_type: &'static str,
+    ) -> Result<Option<OwnedSemaphorePermit>, ScyllaxError> {
+        let permit = match &self.in_flight {
+            Some(in_flight) => Some(
+                match self.policy {
+                    AdmissionPolicy::Wait => in_flight.clone().acquire_owned().await.ok(),
+                    AdmissionPolicy::Reject => in_flight.clone().try_acquire_owned().ok(),
+                }
+                .ok_or(ScyllaxError::RateLimited { query_type })?,
+            ),
+            None => None,
+        };
+
+        if let Some(bucket) = &self.bucket {
+            loop {
+                let taken = bucket.lock().unwrap().take(Instant::now());
+
+                match (taken, self.policy) {
+                    (Ok(()), _) => break,
+                    (Err(_), AdmissionPolicy::Reject) => {
+                        return Err(ScyllaxError::RateLimited { query_type })
+                    }
+                    (Err(wait), AdmissionPolicy::Wait) => tokio::time::sleep(wait).await,
+                }
+            }
+        }
+
+        Ok(permit)
+    }
+}
+
+/// The admission state of every query type an executor runs, created on first use
+#[derive(Debug, Default)]
+pub(crate) struct Admissions(RwLock<HashMap<TypeId, Option<Arc<Admission>>>>);
+
+impl Admissions {
+    /// Gets the admission state of `Q`, if it has limits.
+    pub(crate) fn get<Q: Query + 'static>(
+        &self,
+        options: &ExecutorOptions,
+    ) -> Option<Arc<Admission>> {
+        let id = TypeId::of::<Q>();
+        if let Some(admission) = self.0.read().unwrap().get(&id) {
+            return admission.clone();
+        }
+
+        self.0
+            .write()
+            .unwrap()
+            .entry(id)
+            .or_insert_with(|| {
+                options
+                    .get::<Q>()
+                    .and_then(|o| o.admission)
+                    .or_else(Q::admission)
+                    .map(|limits| Arc::new(Admission::new(&limits)))
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(
+            RateLimit {
+                per_second: 10,
+                burst: 2,
+            },
+            now,
+        );
+
+        assert_eq!(bucket.take(now), Ok(()));
+        assert_eq!(bucket.take(now), Ok(()));
+        assert_eq!(bucket.take(now), Err(Duration::from_millis(100)));
+
+        let later = now + Duration::from_millis(100);
+        assert_eq!(bucket.take(later), Ok(()));
+        assert!(bucket.take(later).is_err());
+
+        // the bucket never holds more than its burst
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!(bucket.take(much_later), Ok(()));
+        assert_eq!(bucket.take(much_later), Ok(()));
+        assert!(bucket.take(much_later).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "a rate limit's `per_second` must be greater than 0")]
+    fn test_rate_limit_zero() {
+        RateLimit::new(0, 10);
+    }
+
+    #[tokio::test]
+    async fn test_reject_in_flight() {
+        let admission = Admission::new(&AdmissionLimits {
+            max_in_flight: Some(1),
+            policy: AdmissionPolicy::Reject,
+            ..Default::default()
+        });
+
+        let permit = admission.admit("GetPersonById").await.unwrap();
+        assert!(matches!(
+            admission.admit("GetPersonById").await,
+            Err(ScyllaxError::RateLimited {
+                query_type: "GetPersonById"
+            })
+        ));
+
+        drop(permit);
+        assert!(admission.admit("GetPersonById").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reject_rate_limit() {
+        let admission = Admission::new(&AdmissionLimits {
+            rate_limit: Some(RateLimit {
+                per_second: 1,
+                burst: 1,
+            }),
+            policy: AdmissionPolicy::Reject,
+            ..Default::default()
+        });
+
+        assert!(admission.admit("GetPersonById").await.is_ok());
+        assert!(admission.admit("GetPersonById").await.is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! The [`ExecutorBuilder`] configures an [`Executor`] before it's created.
+use crate::{
+    admission::{AdmissionLimits, AdmissionPolicy, RateLimit},
+    circuit_breaker::CircuitBreakerConfig,
+    collection::QueryCollection,
+    error::ScyllaxError,
+    executor::Executor,
+    interceptor::{Interceptor, Interceptors},
+    queries::Query,
+    timestamp::MonotonicTimestampGenerator,
+};
+use scylla::Session;
+use std::{any::TypeId, collections::HashMap, marker::PhantomData, sync::Arc, time::Duration};
+
+/// Options that apply to a single query type
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QueryOptions {
+    /// The fraction of executions that should be traced, between `0.0` and `1.0`.
+    pub trace_sample_rate: Option<f64>,
+    /// Executions that take longer than this are logged as slow queries.
+    pub slow_query_threshold: Option<Duration>,
+    /// Overrides the admission limits set in the query's attributes.
+    pub admission: Option<AdmissionLimits>,
+    /// Fails executions fast while the query is failing.
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+}
+
+/// Options shared by every query an [`Executor`] runs
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecutorOptions {
+    /// Options applied to every query type, unless overridden in `queries`.
+    pub global: QueryOptions,
+    /// Options for specific query types.
+    pub queries: HashMap<TypeId, QueryOptions>,
+    /// Whether slow queries are logged with their bound values, instead of redacting them.
+    pub log_slow_query_values: bool,
+    /// The interceptors every query runs through, in order.
+    pub interceptors: Interceptors,
+    /// Generates the timestamps of write queries, instead of letting the coordinator pick them.
+    pub timestamp_generator: Option<Arc<MonotonicTimestampGenerator>>,
+}
+
+impl ExecutorOptions {
+    /// Gets the options for `Q`, falling back to the global options.
+    pub(crate) fn get<Q: 'static>(&self) -> Option<&QueryOptions> {
+        self.queries.get(&TypeId::of::<Q>())
+    }
+
+    /// Whether this execution of `Q` should be traced.
+    pub(crate) fn should_trace<Q: 'static>(&self) -> bool {
+        let rate = self
+            .get::<Q>()
+            .and_then(|o| o.trace_sample_rate)
+            .or(self.global.trace_sample_rate);
+
+        match rate {
+            Some(rate) => sample(rate),
+            None => false,
+        }
+    }
+
+    /// The slow query threshold for `Q`, falling back to the global threshold.
+    pub(crate) fn slow_query_threshold<Q: 'static>(&self) -> Option<Duration> {
+        self.get::<Q>()
+            .and_then(|o| o.slow_query_threshold)
+            .or(self.global.slow_query_threshold)
+    }
+
+    /// The circuit breaker configuration for `Q`, falling back to the global configuration.
+    pub(crate) fn circuit_breaker<Q: 'static>(&self) -> Option<CircuitBreakerConfig> {
+        self.get::<Q>()
+            .and_then(|o| o.circuit_breaker)
+            .or(self.global.circuit_breaker)
+    }
+}
+
+/// Returns `true` for roughly `rate` of calls.
+fn sample(rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate <= 0.0 {
+        return false;
+    }
+
+    let mut bytes = [0u8; 4];
+    if getrandom::getrandom(&mut bytes).is_err() {
+        return false;
+    }
+
+    (u32::from_le_bytes(bytes) as f64 / u32::MAX as f64) < rate
+}
+
+/// Builds an [`Executor`].
+///
+/// ```rust,ignore
+/// let executor = Executor::<PersonQueries>::builder(session)
+///     .trace_sample_rate_for::<GetPersonById>(0.01)
+///     .build()
+///     .await?;
+/// ```
+pub struct ExecutorBuilder<T> {
+    session: Arc<Session>,
+    options: ExecutorOptions,
+    _queries: PhantomData<T>,
+}
+
+impl<T: QueryCollection + Clone> ExecutorBuilder<T> {
+    /// Creates a new builder from a [`Session`].
+    pub fn new(session: Arc<Session>) -> Self {
+        Self {
+            session,
+            options: ExecutorOptions::default(),
+            _queries: PhantomData,
+        }
+    }
+
+    fn query_options<Q: Query + 'static>(&mut self) -> &mut QueryOptions {
+        self.options.queries.entry(TypeId::of::<Q>()).or_default()
+    }
+
+    /// Traces a fraction (between `0.0` and `1.0`) of every query's executions.
+    /// The trace summaries are logged once they're fetched from `system_traces`.
+    pub fn trace_sample_rate(mut self, rate: f64) -> Self {
+        self.options.global.trace_sample_rate = Some(rate);
+        self
+    }
+
+    /// Traces a fraction (between `0.0` and `1.0`) of `Q`'s executions.
+    pub fn trace_sample_rate_for<Q: Query + 'static>(mut self, rate: f64) -> Self {
+        self.query_options::<Q>().trace_sample_rate = Some(rate);
+        self
+    }
+
+    /// Logs every query that takes longer than `threshold` as a [`crate::slow_query::SlowQuery`].
+    pub fn slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.options.global.slow_query_threshold = Some(threshold);
+        self
+    }
+
+    /// Logs executions of `Q` that take longer than `threshold`, overriding the global threshold.
+    pub fn slow_query_threshold_for<Q: Query + 'static>(mut self, threshold: Duration) -> Self {
+        self.query_options::<Q>().slow_query_threshold = Some(threshold);
+        self
+    }
+
+    fn admission_limits<Q: Query + 'static>(&mut self) -> &mut AdmissionLimits {
+        self.query_options::<Q>()
+            .admission
+            .get_or_insert_with(|| Q::admission().unwrap_or_default())
+    }
+
+    /// Limits `Q` to `per_second` executions per second on average, and `burst` at once.
+    ///
+    /// Requestors coalesced onto an execution don't count against the limit.
+    ///
+    /// # Panics
+    /// If `per_second` or `burst` is 0.
+    pub fn rate_limit_for<Q: Query + 'static>(mut self, per_second: u32, burst: u32) -> Self {
+        self.admission_limits::<Q>().rate_limit = Some(RateLimit::new(per_second, burst));
+        self
+    }
+
+    /// Limits `Q` to `max` executions in flight at once.
+    ///
+    /// # Panics
+    /// If `max` is 0.
+    pub fn max_in_flight_for<Q: Query + 'static>(mut self, max: usize) -> Self {
+        assert!(max > 0, "`max_in_flight` must be greater than 0");
+        self.admission_limits::<Q>().max_in_flight = Some(max);
+        self
+    }
+
+    /// Whether executions of `Q` that exceed its limits wait or are rejected with [`ScyllaxError::RateLimited`].
+    pub fn admission_policy_for<Q: Query + 'static>(mut self, policy: AdmissionPolicy) -> Self {
+        self.admission_limits::<Q>().policy = policy;
+        self
+    }
+
+    /// Gives every query type its own circuit breaker.
+    pub fn circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.options.global.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Gives `Q` a circuit breaker, overriding the global configuration.
+    pub fn circuit_breaker_for<Q: Query + 'static>(mut self, config: CircuitBreakerConfig) -> Self {
+        self.query_options::<Q>().circuit_breaker = Some(config);
+        self
+    }
+
+    /// Whether slow queries are logged with their bound values. By default, the values are redacted.
+    pub fn log_slow_query_values(mut self, log_values: bool) -> Self {
+        self.options.log_slow_query_values = log_values;
+        self
+    }
+
+    /// Runs every query through `interceptor`, after the interceptors registered before it.
+    pub fn interceptor(mut self, interceptor: impl Interceptor) -> Self {
+        self.options.interceptors.push(interceptor);
+        self
+    }
+
+    /// Timestamps every write query on the client with `generator`, instead of letting the
+    /// coordinator pick the timestamp. Queries with a `using timestamp` clause keep their own.
+    ///
+    /// The generator can be shared to fill `set_timestamp` fields with timestamps from the same sequence.
+    pub fn timestamp_generator(mut self, generator: Arc<MonotonicTimestampGenerator>) -> Self {
+        self.options.timestamp_generator = Some(generator);
+        self
+    }
+
+    /// Prepares the queries and creates the [`Executor`].
+    pub async fn build(self) -> Result<Executor<T>, ScyllaxError> {
+        Executor::from_options(self.session, self.options).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct A;
+    struct B;
+
+    #[test]
+    fn test_sample() {
+        assert!(sample(1.0));
+        assert!(!sample(0.0));
+        assert!(!sample(-1.0));
+    }
+
+    #[test]
+    fn test_should_trace() {
+        let mut options = ExecutorOptions::default();
+        assert!(!options.should_trace::<A>());
+
+        options.global.trace_sample_rate = Some(1.0);
+        assert!(options.should_trace::<A>());
+
+        options
+            .queries
+            .entry(TypeId::of::<B>())
+            .or_default()
+            .trace_sample_rate = Some(0.0);
+        assert!(options.should_trace::<A>());
+        assert!(!options.should_trace::<B>());
+    }
+
+    #[test]
+    fn test_slow_query_threshold() {
+        let mut options = ExecutorOptions::default();
+        assert_eq!(options.slow_query_threshold::<A>(), None);
+
+        options.global.slow_query_threshold = Some(Duration::from_millis(100));
+        options
+            .queries
+            .entry(TypeId::of::<B>())
+            .or_default()
+            .slow_query_threshold = Some(Duration::from_millis(5));
+
+        assert_eq!(
+            options.slow_query_threshold::<A>(),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            options.slow_query_threshold::<B>(),
+            Some(Duration::from_millis(5))
+        );
+    }
+}
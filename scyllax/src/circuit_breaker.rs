@@ -0,0 +1,533 @@
+//! Circuit breakers, which fail executions of a query type fast while it's failing.
+//!
+//! A breaker starts closed, and opens when the error rate over a window or the number of consecutive
+//! timeouts crosses its thresholds. While open, executions fail with [`ScyllaxError::CircuitOpen`].
+//! Once [`CircuitBreakerConfig::open_duration`] has passed, it's half-open: a few probe executions are
+//! let through, closing the breaker if they all succeed and opening it again if any fails.
+//!
+//! Every transition starts a new generation of the breaker. Executions only count towards the
+//! generation they were let through in, so executions that started before the breaker opened
+//! can't close it again, and only the probes decide whether a half-open breaker closes.
+//!
+//! Transitions are logged with the `scyllax::circuit_breaker` target, with a
+//! `monotonic_counter.scyllax_circuit_breaker_transitions` field for metrics layers.
+//! ```rust,ignore
+//! let executor = Executor::<PersonQueries>::builder(session)
+//!     .circuit_breaker_for::<GetPersonById>(CircuitBreakerConfig::default())
+//!     .build()
+//!     .await?;
+//! ```
+use crate::{builder::ExecutorOptions, error::ScyllaxError, queries::Query};
+use scylla::QueryResult;
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// The configuration of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerConfig {
+    /// The fraction of failed executions in a window that opens the breaker, between `0.0` and `1.0`
+    pub failure_rate: f64,
+    /// The number of executions a window needs before its failure rate is considered
+    pub minimum_requests: u32,
+    /// The length of the window the failure rate is measured over
+    pub window: Duration,
+    /// The number of consecutive timeouts that opens the breaker
+    pub consecutive_timeouts: u32,
+    /// How long the breaker stays open before letting probes through
+    pub open_duration: Duration,
+    /// The number of probes that must succeed to close the breaker
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate: 0.5,
+            minimum_requests: 20,
+            window: Duration::from_secs(10),
+            consecutive_timeouts: 5,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+/// The state of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Executions go through
+    Closed,
+    /// Executions fail fast
+    Open,
+    /// Probe executions go through
+    HalfOpen,
+}
+
+impl Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half-open",
+        };
+
+        write!(f, "{state}")
+    }
+}
+
+/// A snapshot of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerStats {
+    /// The current state
+    pub state: CircuitState,
+    /// The number of times the breaker opened
+    pub opened: u64,
+    /// The number of executions that failed fast
+    pub rejected: u64,
+}
+
+/// How an execution went, as far as the breaker is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    Failure,
+    Timeout,
+}
+
+impl Outcome {
+    /// Classifies a result. Only errors that point at an unhealthy cluster are failures.
+    fn of(result: &Result<QueryResult, ScyllaxError>) -> Self {
+        match result {
+            Err(e) if e.is_timeout() => Self::Timeout,
+            Err(e) if e.query_error().is_some() && e.is_retryable() => Self::Failure,
+            _ => Self::Success,
+        }
+    }
+}
+
+/// The state machine of a circuit breaker
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    /// Incremented on every transition
+    generation: u64,
+    window_started: Instant,
+    requests: u32,
+    failures: u32,
+    consecutive_timeouts: u32,
+    opened_at: Instant,
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+impl Circuit {
+    fn new(now: Instant) -> Self {
+        Self {
+            state: CircuitState::Closed,
+            generation: 0,
+            window_started: now,
+            requests: 0,
+            failures: 0,
+            consecutive_timeouts: 0,
+            opened_at: now,
+            probes_in_flight: 0,
+            probe_successes: 0,
+        }
+    }
+
+    fn transition(&mut self, state: CircuitState, now: Instant) {
+        self.state = state;
+        self.generation += 1;
+        self.window_started = now;
+        self.requests = 0;
+        self.failures = 0;
+        self.consecutive_timeouts = 0;
+        self.probes_in_flight = 0;
+        self.probe_successes = 0;
+
+        if state == CircuitState::Open {
+            self.opened_at = now;
+        }
+    }
+
+    /// Whether an execution may go through, returning the generation it goes through in.
+    fn try_acquire(&mut self, config: &CircuitBreakerConfig, now: Instant) -> Option<u64> {
+        if self.state == CircuitState::Open
+            && now.saturating_duration_since(self.opened_at) >= config.open_duration
+        {
+            self.transition(CircuitState::HalfOpen, now);
+        }
+
+        // probes that never reported back, eg: because they were cancelled
+        if self.state == CircuitState::HalfOpen
+            && now.saturating_duration_since(self.window_started) >= config.open_duration
+        {
+            self.transition(CircuitState::HalfOpen, now);
+        }
+
+        let admitted = match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if self.probes_in_flight + self.probe_successes < config.half_open_probes.max(1) {
+                    self.probes_in_flight += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+
+        admitted.then_some(self.generation)
+    }
+
+    /// Records the outcome of an execution that was let through in `generation`.
+    ///
+    /// Outcomes of earlier generations are ignored.
+    fn record(
+        &mut self,
+        config: &CircuitBreakerConfig,
+        generation: u64,
+        outcome: Outcome,
+        now: Instant,
+    ) {
+        if generation != self.generation {
+            return;
+        }
+
+        match self.state {
+            CircuitState::Closed => {
+                if now.saturating_duration_since(self.window_started) >= config.window {
+                    self.window_started = now;
+                    self.requests = 0;
+                    self.failures = 0;
+                }
+
+                self.requests += 1;
+                match outcome {
+                    Outcome::Success => self.consecutive_timeouts = 0,
+                    Outcome::Failure => {
+                        self.failures += 1;
+                        self.consecutive_timeouts = 0;
+                    }
+                    Outcome::Timeout => {
+                        self.failures += 1;
+                        self.consecutive_timeouts += 1;
+                    }
+                }
+
+                let failure_rate = self.failures as f64 / self.requests as f64;
+                if self.consecutive_timeouts >= config.consecutive_timeouts.max(1)
+                    || (self.requests >= config.minimum_requests
+                        && failure_rate >= config.failure_rate)
+                {
+                    self.transition(CircuitState::Open, now);
+                }
+            }
+            // only probes are let through in a half-open generation
+            CircuitState::HalfOpen => {
+                self.probes_in_flight = self.probes_in_flight.saturating_sub(1);
+
+                if outcome == Outcome::Success {
+                    self.probe_successes += 1;
+                    if self.probe_successes >= config.half_open_probes.max(1) {
+                        self.transition(CircuitState::Closed, now);
+                    }
+                } else {
+                    self.transition(CircuitState::Open, now);
+                }
+            }
+            // nothing is let through in an open generation
+            CircuitState::Open => (),
+        }
+    }
+}
+
+/// The circuit breaker of a single query type
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuit: Mutex<Circuit>,
+    opened: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl CircuitBreaker {
+    fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuit: Mutex::new(Circuit::new(Instant::now())),
+            opened: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Lets an execution through, returning the generation of the breaker it went through in,
+    /// or fails it with [`ScyllaxError::CircuitOpen`].
+    ///
+    /// Every execution let through must be followed by a call to [`CircuitBreaker::record`].
+    pub(crate) fn acquire(&self, query_type: &'static str) -> Result<u64, ScyllaxError> {
+        let admitted = self.update(query_type, |circuit, config, now| {
+            circuit.try_acquire(config, now)
+        });
+
+        admitted.ok_or_else(|| {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            ScyllaxError::CircuitOpen { query_type }
+        })
+    }
+
+    /// Records the result of an execution let through in `generation`.
+    pub(crate) fn record(
+        &self,
+        query_type: &'static str,
+        generation: u64,
+        result: &Result<QueryResult, ScyllaxError>,
+    ) {
+        let outcome = Outcome::of(result);
+        self.update(query_type, |circuit, config, now| {
+            circuit.record(config, generation, outcome, now)
+        });
+    }
+
+    /// A snapshot of the breaker.
+    pub(crate) fn stats(&self) -> CircuitBreakerStats {
+        CircuitBreakerStats {
+            state: self.circuit.lock().unwrap().state,
+            opened: self.opened.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Runs `f` on the circuit and publishes the transition, if any.
+    fn update<R>(
+        &self,
+        query_type: &'static str,
+        f: impl FnOnce(&mut Circuit, &CircuitBreakerConfig, Instant) -> R,
+    ) -> R {
+        let (from, to, result) = {
+            let mut circuit = self.circuit.lock().unwrap();
+            let from = circuit.state;
+            let result = f(&mut circuit, &self.config, Instant::now());
+
+            (from, circuit.state, result)
+        };
+
+        if from != to {
+            if to == CircuitState::Open {
+                self.opened.fetch_add(1, Ordering::Relaxed);
+            }
+
+            tracing::warn!(
+                target: "scyllax::circuit_breaker",
+                monotonic_counter.scyllax_circuit_breaker_transitions = 1u64,
+                query = query_type,
+                from = %from,
+                to = %to,
+                "circuit breaker is now {to}"
+            );
+        }
+
+        result
+    }
+}
+
+/// The circuit breakers of every query type an executor runs, created on first use
+#[derive(Debug, Default)]
+pub(crate) struct CircuitBreakers(RwLock<HashMap<TypeId, Option<Arc<CircuitBreaker>>>>);
+
+impl CircuitBreakers {
+    /// Gets the circuit breaker of `Q`, if it has one.
+    pub(crate) fn get<Q: Query + 'static>(
+        &self,
+        options: &ExecutorOptions,
+    ) -> Option<Arc<CircuitBreaker>> {
+        let id = TypeId::of::<Q>();
+        if let Some(breaker) = self.0.read().unwrap().get(&id) {
+            return breaker.clone();
+        }
+
+        self.0
+            .write()
+            .unwrap()
+            .entry(id)
+            .or_insert_with(|| {
+                options
+                    .circuit_breaker::<Q>()
+                    .map(|config| Arc::new(CircuitBreaker::new(config)))
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_rate: 0.5,
+            minimum_requests: 4,
+            window: Duration::from_secs(10),
+            consecutive_timeouts: 3,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 2,
+        }
+    }
+
+    #[test]
+    fn test_failure_rate() {
+        let config = config();
+        let now = Instant::now();
+        let mut circuit = Circuit::new(now);
+
+        for outcome in [Outcome::Failure, Outcome::Failure, Outcome::Success] {
+            let generation = circuit.try_acquire(&config, now).unwrap();
+            circuit.record(&config, generation, outcome, now);
+        }
+        assert_eq!(circuit.state, CircuitState::Closed);
+
+        circuit.record(&config, 0, Outcome::Success, now);
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.try_acquire(&config, now), None);
+    }
+
+    #[test]
+    fn test_window() {
+        let config = config();
+        let now = Instant::now();
+        let mut circuit = Circuit::new(now);
+
+        circuit.record(&config, 0, Outcome::Failure, now);
+        circuit.record(&config, 0, Outcome::Failure, now);
+        circuit.record(&config, 0, Outcome::Success, now);
+
+        // the failures fall out of the window
+        let later = now + Duration::from_secs(11);
+        circuit.record(&config, 0, Outcome::Failure, later);
+        circuit.record(&config, 0, Outcome::Success, later);
+        circuit.record(&config, 0, Outcome::Success, later);
+        circuit.record(&config, 0, Outcome::Success, later);
+        assert_eq!(circuit.state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_consecutive_timeouts() {
+        let config = CircuitBreakerConfig {
+            minimum_requests: 100,
+            ..config()
+        };
+        let now = Instant::now();
+        let mut circuit = Circuit::new(now);
+
+        circuit.record(&config, 0, Outcome::Timeout, now);
+        circuit.record(&config, 0, Outcome::Timeout, now);
+        circuit.record(&config, 0, Outcome::Success, now);
+        circuit.record(&config, 0, Outcome::Timeout, now);
+        circuit.record(&config, 0, Outcome::Timeout, now);
+        assert_eq!(circuit.state, CircuitState::Closed);
+
+        circuit.record(&config, 0, Outcome::Timeout, now);
+        assert_eq!(circuit.state, CircuitState::Open);
+    }
+
+    #[test]
+    fn test_half_open() {
+        let config = config();
+        let now = Instant::now();
+        let mut circuit = Circuit::new(now);
+        circuit.transition(CircuitState::Open, now);
+
+        let later = now + Duration::from_secs(30);
+        let generation = circuit.try_acquire(&config, later).unwrap();
+        assert_eq!(circuit.state, CircuitState::HalfOpen);
+        assert_eq!(circuit.try_acquire(&config, later), Some(generation));
+        // only `half_open_probes` probes are let through
+        assert_eq!(circuit.try_acquire(&config, later), None);
+
+        circuit.record(&config, generation, Outcome::Success, later);
+        assert_eq!(circuit.state, CircuitState::HalfOpen);
+        circuit.record(&config, generation, Outcome::Success, later);
+        assert_eq!(circuit.state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_failure() {
+        let config = config();
+        let now = Instant::now();
+        let mut circuit = Circuit::new(now);
+        circuit.transition(CircuitState::Open, now);
+
+        let later = now + Duration::from_secs(30);
+        let generation = circuit.try_acquire(&config, later).unwrap();
+        circuit.record(&config, generation, Outcome::Timeout, later);
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.try_acquire(&config, later), None);
+    }
+
+    #[test]
+    fn test_stale_outcomes() {
+        let config = CircuitBreakerConfig {
+            half_open_probes: 1,
+            ..config()
+        };
+        let now = Instant::now();
+        let mut circuit = Circuit::new(now);
+
+        // started before the breaker opened
+        let stale = circuit.try_acquire(&config, now).unwrap();
+        circuit.transition(CircuitState::Open, now);
+
+        let later = now + Duration::from_secs(30);
+        let probe = circuit.try_acquire(&config, later).unwrap();
+        assert_eq!(circuit.state, CircuitState::HalfOpen);
+
+        circuit.record(&config, stale, Outcome::Success, later);
+        assert_eq!(circuit.state, CircuitState::HalfOpen);
+        circuit.record(&config, stale, Outcome::Failure, later);
+        assert_eq!(circuit.state, CircuitState::HalfOpen);
+
+        circuit.record(&config, probe, Outcome::Success, later);
+        assert_eq!(circuit.state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_breaker() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            consecutive_timeouts: 1,
+            ..config()
+        });
+
+        let generation = breaker.acquire("GetPersonById").unwrap();
+        breaker.record(
+            "GetPersonById",
+            generation,
+            &Err(ScyllaxError::QueryError(
+                scylla::transport::errors::QueryError::TimeoutError,
+            )),
+        );
+        assert!(matches!(
+            breaker.acquire("GetPersonById"),
+            Err(ScyllaxError::CircuitOpen {
+                query_type: "GetPersonById"
+            })
+        ));
+
+        assert_eq!(
+            breaker.stats(),
+            CircuitBreakerStats {
+                state: CircuitState::Open,
+                opened: 1,
+                rejected: 1,
+            }
+        );
+    }
+}
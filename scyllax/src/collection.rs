@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::{
     error::ScyllaxError,
     executor::{Executor, GetCoalescingSender, GetPreparedStatement, ShardMessage},
+    health::{QueryDescription, QueryStatus},
     prelude::ReadQuery,
     queries::Query,
 };
@@ -23,6 +24,16 @@ pub trait QueryCollection {
     where
         Self: Sized;
 
+    /// Describes every query in the collection.
+    fn describe(&self) -> Vec<QueryDescription> {
+        Vec::new()
+    }
+
+    /// The status of every query in the collection.
+    fn status(&self) -> Vec<QueryStatus> {
+        Vec::new()
+    }
+
     /// Gets a prepared statement from the collection.
     fn get_prepared<T: Query>(&self) -> &PreparedStatement
     where
@@ -40,23 +51,14 @@ pub trait QueryCollection {
     }
 }
 
-/// Prepares a query
-#[tracing::instrument(skip(session))]
-pub async fn prepare_query(
-    session: &Session,
-    query: String,
-    query_type: &str,
-) -> Result<PreparedStatement, ScyllaxError> {
+/// Prepares the statement of a query
+#[tracing::instrument(skip(session), fields(query_type = std::any::type_name::<Q>()))]
+pub async fn prepare_query<Q: Query>(session: &Session) -> Result<PreparedStatement, ScyllaxError> {
     tracing::info!("preparing query");
 
-    let res = session.prepare(query).await;
-
-    match res {
-        Ok(prepared) => Ok(prepared),
-        Err(err) => {
-            tracing::error!("failed to prepare query: {:#?}", err);
+    session.prepare(Q::query()).await.map_err(|err| {
+        tracing::error!("failed to prepare query: {:#?}", err);
 
-            return Err(err.into());
-        }
-    }
+        ScyllaxError::with_query::<Q>(err)
+    })
 }
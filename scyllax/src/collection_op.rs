@@ -0,0 +1,81 @@
+//! Wrappers for updating list, set and map columns in place, instead of overwriting them.
+//!
+//! Fields marked with `#[entity(collection = "...")]` are wrapped in these by the generated upsert queries.
+use scylla::{
+    _macro_internal::{Value, ValueTooBig},
+    frame::response::result::ColumnType,
+    serialize::{value::SerializeCql, writers::WrittenCellProof, CellWriter, SerializationError},
+};
+
+macro_rules! collection_op {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug, Default, PartialEq)]
+        pub struct $name<T>(pub T);
+
+        impl<T: Value> Value for $name<T> {
+            fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+                self.0.serialize(buf)
+            }
+        }
+
+        impl<T: SerializeCql> SerializeCql for $name<T> {
+            fn serialize<'b>(
+                &self,
+                typ: &ColumnType,
+                writer: CellWriter<'b>,
+            ) -> Result<WrittenCellProof<'b>, SerializationError> {
+                self.0.serialize(typ, writer)
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(v: T) -> Self {
+                $name(v)
+            }
+        }
+    };
+}
+
+collection_op!(
+    /// Appends elements to a list or set column: `col = col + :value`
+    Append
+);
+
+collection_op!(
+    /// Prepends elements to a list column: `col = :value + col`
+    Prepend
+);
+
+collection_op!(
+    /// Removes elements from a list or set column: `col = col - :value`
+    Remove
+);
+
+collection_op!(
+    /// Puts entries in a map column, overwriting the keys that are already set: `col = col + :value`.
+    /// The entries are bound as a map, eg: `Put(HashMap::from([("foo".to_string(), 1)]))`.
+    Put
+);
+
+collection_op!(
+    /// Removes keys from a map column: `col = col - :value`.
+    /// The keys are bound as a set, eg: `RemoveKeys(vec!["foo".to_string()])`.
+    RemoveKeys
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let mut append = vec![];
+        Value::serialize(&Append(vec![1, 2, 3]), &mut append).unwrap();
+        let mut inner = vec![];
+        Value::serialize(&vec![1, 2, 3], &mut inner).unwrap();
+
+        assert_eq!(append, inner);
+        assert_eq!(Remove::from(vec![1]), Remove(vec![1]));
+    }
+}
@@ -0,0 +1,304 @@
+//! Generates the `create table` statements of entities, and the `create type` statements of UDTs.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use scylla::{
+    frame::value::{Counter, CqlDate, CqlDuration, CqlTime, CqlTimestamp, CqlTimeuuid, ValueList},
+    FromRow,
+};
+
+use crate::entity::{ClusteringOrder, EntityExt};
+
+/// The CQL type of a column, used to generate the `create table` statements of entities.
+///
+/// Implemented for the types scylla can serialize, and derived by [`crate::prelude::JsonData`],
+/// [`crate::prelude::IntEnum`], [`crate::prelude::TextEnum`] and [`crate::prelude::Udt`].
+/// Implement it for custom column types.
+/// ```rust,ignore
+/// impl CqlType for Email {
+///     fn cql_type() -> String {
+///         "text".to_string()
+///     }
+/// }
+/// ```
+pub trait CqlType {
+    /// The CQL type, eg: `list<text>`
+    fn cql_type() -> String;
+
+    /// The CQL type when nested in a collection, where collections have to be frozen.
+    fn nested_cql_type() -> String {
+        Self::cql_type()
+    }
+
+    /// The CQL type of a `Vec` of this type. `Vec<u8>` is a blob, every other `Vec` is a list.
+    #[doc(hidden)]
+    fn __vec_cql_type() -> String {
+        format!("list<{}>", Self::nested_cql_type())
+    }
+}
+
+macro_rules! cql_type {
+    ($($ty:ty => $cql:literal),* $(,)?) => {
+        $(
+            impl CqlType for $ty {
+                fn cql_type() -> String {
+                    $cql.to_string()
+                }
+            }
+        )*
+    };
+}
+
+cql_type! {
+    bool => "boolean",
+    i8 => "tinyint",
+    i16 => "smallint",
+    i32 => "int",
+    i64 => "bigint",
+    f32 => "float",
+    f64 => "double",
+    String => "text",
+    std::net::IpAddr => "inet",
+    uuid::Uuid => "uuid",
+    CqlTimeuuid => "timeuuid",
+    CqlTimestamp => "timestamp",
+    CqlDate => "date",
+    CqlTime => "time",
+    CqlDuration => "duration",
+    Counter => "counter",
+}
+
+#[cfg(feature = "json")]
+cql_type! {
+    crate::json_blob::JsonBlob => "text",
+}
+
+#[cfg(feature = "json")]
+impl<T> CqlType for crate::json::Json<T>
+where
+    T: std::fmt::Debug + Clone + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn cql_type() -> String {
+        "text".to_string()
+    }
+}
+
+/// Only implemented so `Vec<u8>` is a blob.
+impl CqlType for u8 {
+    fn cql_type() -> String {
+        "tinyint".to_string()
+    }
+
+    fn __vec_cql_type() -> String {
+        "blob".to_string()
+    }
+}
+
+impl<T: CqlType> CqlType for Option<T> {
+    fn cql_type() -> String {
+        T::cql_type()
+    }
+
+    fn nested_cql_type() -> String {
+        T::nested_cql_type()
+    }
+}
+
+impl<T: CqlType> CqlType for Vec<T> {
+    fn cql_type() -> String {
+        T::__vec_cql_type()
+    }
+
+    fn nested_cql_type() -> String {
+        // blobs aren't collections, so they aren't frozen
+        match Self::cql_type() {
+            blob if blob == "blob" => blob,
+            list => frozen(list),
+        }
+    }
+}
+
+macro_rules! collection_cql_type {
+    ($($collection:ident<$($param:ident),+> => $cql:literal),* $(,)?) => {
+        $(
+            impl<$($param: CqlType),+> CqlType for $collection<$($param),+> {
+                fn cql_type() -> String {
+                    format!(
+                        concat!($cql, "<{}>"),
+                        [$($param::nested_cql_type()),+].join(", ")
+                    )
+                }
+
+                fn nested_cql_type() -> String {
+                    frozen(Self::cql_type())
+                }
+            }
+        )*
+    };
+}
+
+collection_cql_type! {
+    HashSet<T> => "set",
+    BTreeSet<T> => "set",
+    HashMap<K, V> => "map",
+    BTreeMap<K, V> => "map",
+}
+
+/// Collections nested in collections have to be frozen
+fn frozen(cql_type: String) -> String {
+    format!("frozen<{cql_type}>")
+}
+
+/// The `create table` statement of an entity, derived by [`crate::prelude::EntityDdl`].
+///
+/// Opt-in, since every column type must implement [`CqlType`].
+pub trait EntityDdl<T: ValueList + FromRow>: EntityExt<T> {
+    /// Returns the `create table if not exists` statement of the entity's table.
+    fn create_table_cql(table: &str) -> String;
+}
+
+/// A column of a `create table` statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    /// The name of the column, quoted
+    pub name: &'static str,
+    /// The CQL type of the column
+    pub cql_type: String,
+    /// Whether the column is shared by every row of a partition
+    pub is_static: bool,
+}
+
+impl Column {
+    /// Creates a column that isn't static
+    pub fn new(name: &'static str, cql_type: String) -> Self {
+        Self {
+            name,
+            cql_type,
+            is_static: false,
+        }
+    }
+
+    /// Makes the column static
+    pub fn static_column(mut self) -> Self {
+        self.is_static = true;
+        self
+    }
+}
+
+/// Renders the `create table` statement of a table.
+pub fn create_table_cql(
+    table: &str,
+    columns: &[Column],
+    partition_keys: &[String],
+    clustering_keys: &[(String, ClusteringOrder)],
+) -> String {
+    let mut definitions = columns
+        .iter()
+        .map(|column| match column.is_static {
+            true => format!("{} {} static", column.name, column.cql_type),
+            false => format!("{} {}", column.name, column.cql_type),
+        })
+        .collect::<Vec<_>>();
+
+    // a single partition key doesn't need its own parentheses
+    let partition_key = match partition_keys {
+        [key] => key.clone(),
+        keys => format!("({})", keys.join(", ")),
+    };
+    let primary_key = std::iter::once(partition_key)
+        .chain(clustering_keys.iter().map(|(key, _)| key.clone()))
+        .collect::<Vec<_>>();
+    definitions.push(format!("primary key ({})", primary_key.join(", ")));
+
+    let mut query = format!(
+        "create table if not exists {table} (\n\t{}\n)",
+        definitions.join(",\n\t")
+    );
+
+    if !clustering_keys.is_empty() {
+        let order = clustering_keys
+            .iter()
+            .map(|(key, order)| format!("{key} {}", order.as_str()))
+            .collect::<Vec<_>>();
+        query.push_str(&format!(" with clustering order by ({})", order.join(", ")));
+    }
+
+    query.push(';');
+    query
+}
+
+/// Renders the `create type` statement of a user defined type.
+pub fn create_type_cql(name: &str, fields: &[Column]) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| format!("{} {}", field.name, field.cql_type))
+        .collect::<Vec<_>>();
+
+    format!(
+        "create type if not exists {name} (\n\t{}\n);",
+        fields.join(",\n\t")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cql_type() {
+        assert_eq!(<Option<i32>>::cql_type(), "int");
+        assert_eq!(<Vec<u8>>::cql_type(), "blob");
+        assert_eq!(<Vec<String>>::cql_type(), "list<text>");
+        assert_eq!(<HashSet<CqlTimeuuid>>::cql_type(), "set<timeuuid>");
+        assert_eq!(
+            <HashMap<String, Vec<i64>>>::cql_type(),
+            "map<text, frozen<list<bigint>>>"
+        );
+        assert_eq!(
+            <Vec<BTreeMap<String, Vec<u8>>>>::cql_type(),
+            "list<frozen<map<text, blob>>>"
+        );
+    }
+
+    #[test]
+    fn test_create_table_cql() {
+        let columns = [
+            Column::new(r#""id""#, "timeuuid".to_string()),
+            Column::new(r#""title""#, "text".to_string()),
+        ];
+        assert_eq!(
+            create_table_cql("post", &columns, &[r#""id""#.to_string()], &[]),
+            "create table if not exists post (\n\t\"id\" timeuuid,\n\t\"title\" text,\n\tprimary key (\"id\")\n);"
+        );
+
+        let columns = [
+            Column::new(r#""bucket""#, "int".to_string()),
+            Column::new(r#""day""#, "date".to_string()),
+            Column::new(r#""createdAt""#, "timestamp".to_string()),
+            Column::new(r#""id""#, "timeuuid".to_string()),
+            Column::new(r#""title""#, "text".to_string()).static_column(),
+            Column::new(r#""body""#, "text".to_string()),
+        ];
+        let partition_keys = [r#""bucket""#.to_string(), r#""day""#.to_string()];
+        let clustering_keys = [
+            (r#""createdAt""#.to_string(), ClusteringOrder::Desc),
+            (r#""id""#.to_string(), ClusteringOrder::Asc),
+        ];
+        assert_eq!(
+            create_table_cql("comment", &columns, &partition_keys, &clustering_keys),
+            "create table if not exists comment (\n\t\"bucket\" int,\n\t\"day\" date,\n\t\"createdAt\" timestamp,\n\t\"id\" timeuuid,\n\t\"title\" text static,\n\t\"body\" text,\n\tprimary key ((\"bucket\", \"day\"), \"createdAt\", \"id\")\n) with clustering order by (\"createdAt\" desc, \"id\" asc);"
+        );
+    }
+
+    #[test]
+    fn test_create_type_cql() {
+        let fields = [
+            Column::new(r#""street""#, "text".to_string()),
+            Column::new(r#""postCode""#, "text".to_string()),
+            Column::new(r#""tags""#, <Vec<String>>::nested_cql_type()),
+        ];
+        assert_eq!(
+            create_type_cql("address", &fields),
+            "create type if not exists address (\n\t\"street\" text,\n\t\"postCode\" text,\n\t\"tags\" frozen<list<text>>\n);"
+        );
+    }
+}
@@ -5,6 +5,127 @@ pub trait EntityExt<T: ValueList + FromRow> {
     /// Returns the keys of the entity as a vector of strings, keeping the order of the keys.
     fn keys() -> Vec<String>;
 
-    /// Returns the primary keys
+    /// Returns the primary keys: the partition keys, then the clustering keys
     fn pks() -> Vec<String>;
+
+    /// Returns the partition keys, which decide the partition a row is stored in.
+    ///
+    /// Defaults to the first primary key, like `primary key (a, b)` in CQL.
+    fn partition_keys() -> Vec<String> {
+        Self::pks().into_iter().take(1).collect()
+    }
+
+    /// Returns the clustering keys and their order, which sort the rows of a partition.
+    ///
+    /// Defaults to the primary keys after the first, in ascending order.
+    fn clustering_keys() -> Vec<(String, ClusteringOrder)> {
+        Self::pks()
+            .into_iter()
+            .skip(1)
+            .map(|key| (key, ClusteringOrder::Asc))
+            .collect()
+    }
+}
+
+/// The order rows of a partition are sorted in by a clustering key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ClusteringOrder {
+    /// Ascending, the default
+    #[default]
+    Asc,
+    /// Descending
+    Desc,
+}
+
+impl ClusteringOrder {
+    /// The order, as written in CQL
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClusteringOrder::Asc => "asc",
+            ClusteringOrder::Desc => "desc",
+        }
+    }
+}
+
+/// Compares two column names in a const context.
+/// Used by `PartialEntity` to check renames against the entity at compile time.
+#[doc(hidden)]
+pub const fn __column_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Compares two lists of column names in a const context.
+/// Used by read queries to check the fields of a struct against the columns they select.
+#[doc(hidden)]
+pub const fn __columns_eq(a: &[&str], b: &[&str]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if !__column_eq(a[i], b[i]) {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(scylla::FromRow, scylla::ValueList)]
+    struct PostComment {
+        post_id: i32,
+        id: i32,
+    }
+
+    // a hand written implementation, without key roles
+    impl EntityExt<PostComment> for PostComment {
+        fn keys() -> Vec<String> {
+            vec!["post_id".to_string(), "id".to_string()]
+        }
+
+        fn pks() -> Vec<String> {
+            Self::keys()
+        }
+    }
+
+    #[test]
+    fn test_columns_eq() {
+        assert!(__columns_eq(
+            &["\"id\"", "\"email\""],
+            &["\"id\"", "\"email\""]
+        ));
+        assert!(!__columns_eq(
+            &["\"id\"", "\"email\""],
+            &["\"email\"", "\"id\""]
+        ));
+        assert!(!__columns_eq(&["\"id\""], &["\"id\"", "\"email\""]));
+    }
+
+    #[test]
+    fn test_default_key_roles() {
+        assert_eq!(PostComment::partition_keys(), vec!["post_id".to_string()]);
+        assert_eq!(
+            PostComment::clustering_keys(),
+            vec![("id".to_string(), ClusteringOrder::Asc)]
+        );
+    }
 }
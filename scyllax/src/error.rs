@@ -1,5 +1,7 @@
 //! ScyllaX error types
 
+use crate::queries::Query;
+use scylla::transport::errors::{DbError, QueryError};
 use tokio::sync::oneshot::error::RecvError;
 
 /// An error from ScyllaX
@@ -7,7 +9,16 @@ use tokio::sync::oneshot::error::RecvError;
 pub enum ScyllaxError {
     /// A query error from Scylla
     #[error("Scylla Query error: {0}")]
-    QueryError(#[from] scylla::transport::errors::QueryError),
+    QueryError(#[from] QueryError),
+
+    /// A query error from Scylla, with the context of the query that failed
+    #[error("Query `{}` failed: {source}", .context.query_type)]
+    Execution {
+        /// The query that failed
+        context: Box<QueryContext>,
+        /// The error returned by Scylla
+        source: QueryError,
+    },
 
     /// A session error from Scylla
     #[error("Scylla error: {0}")]
@@ -29,11 +40,160 @@ pub enum ScyllaxError {
     #[error("Failed to serialize values: {0}")]
     SerializedValues(#[from] scylla::frame::value::SerializeValuesError),
 
+    /// Tracing was requested, but the server didn't return a tracing id
+    #[error("The server didn't return a tracing id")]
+    MissingTracingId,
+
+    /// The query exceeded its rate limit or in-flight cap
+    #[error("Query `{query_type}` was rate limited")]
+    RateLimited {
+        /// The type name of the query
+        query_type: &'static str,
+    },
+
+    /// The query's circuit breaker is open
+    #[error("Query `{query_type}` failed fast, its circuit breaker is open")]
+    CircuitOpen {
+        /// The type name of the query
+        query_type: &'static str,
+    },
+
     /// An error when using receivers
     #[error("Receiver error: {0}")]
     ReceiverError(#[from] RecvError),
 }
 
+impl ScyllaxError {
+    /// Attaches the context of `Q` to a query error.
+    pub fn with_query<Q: Query>(source: QueryError) -> Self {
+        Self::with_context(QueryContext::of::<Q>(), source)
+    }
+
+    /// Attaches a context to a query error.
+    pub fn with_context(context: QueryContext, source: QueryError) -> Self {
+        Self::Execution {
+            context: Box::new(context),
+            source,
+        }
+    }
+
+    /// The context of the query that failed, if any.
+    pub fn context(&self) -> Option<&QueryContext> {
+        match self {
+            Self::Execution { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The underlying [`QueryError`], if any.
+    pub fn query_error(&self) -> Option<&QueryError> {
+        match self {
+            Self::QueryError(e) | Self::Execution { source: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+
+    /// The error returned by the database, if any.
+    pub fn db_error(&self) -> Option<&DbError> {
+        match self.query_error() {
+            Some(QueryError::DbError(e, _)) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Whether the query timed out, either on the client or on the coordinator.
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self.query_error(),
+            Some(QueryError::TimeoutError | QueryError::RequestTimeout(_))
+        ) || matches!(
+            self.db_error(),
+            Some(DbError::ReadTimeout { .. } | DbError::WriteTimeout { .. })
+        )
+    }
+
+    /// Whether there weren't enough replicas alive to satisfy the consistency level.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self.db_error(), Some(DbError::Unavailable { .. }))
+    }
+
+    /// Whether the coordinator was overloaded or rate limited the query.
+    pub fn is_overloaded(&self) -> bool {
+        matches!(
+            self.db_error(),
+            Some(DbError::Overloaded | DbError::RateLimitReached { .. })
+        )
+    }
+
+    /// Whether the query may succeed if it's retried.
+    ///
+    /// Note: writes that aren't idempotent (such as counter updates) may be applied twice.
+    pub fn is_retryable(&self) -> bool {
+        if self.is_timeout()
+            || self.is_unavailable()
+            || self.is_overloaded()
+            || matches!(self, Self::RateLimited { .. })
+        {
+            return true;
+        }
+
+        match self.query_error() {
+            Some(QueryError::DbError(e, _)) => {
+                matches!(e, DbError::IsBootstrapping | DbError::ServerError)
+            }
+            Some(
+                QueryError::IoError(_)
+                | QueryError::UnableToAllocStreamId
+                | QueryError::TooManyOrphanedStreamIds(_),
+            ) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Describes the query an error was raised for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryContext {
+    /// The type name of the query, eg: `example::queries::GetPersonById`
+    pub query_type: &'static str,
+    /// The CQL statement
+    pub statement: String,
+    /// The names of the bound parameters, in order
+    pub bind_names: &'static [&'static str],
+}
+
+impl QueryContext {
+    /// Creates the context of a statement that isn't a [`Query`], eg: `use scyllax`.
+    pub fn statement(query_type: &'static str, statement: impl Into<String>) -> Self {
+        Self {
+            query_type,
+            statement: statement.into(),
+            bind_names: &[],
+        }
+    }
+
+    /// Creates the context for `Q`.
+    pub fn of<Q: Query>() -> Self {
+        Self {
+            query_type: std::any::type_name::<Q>(),
+            statement: Q::query(),
+            bind_names: Q::bind_names(),
+        }
+    }
+}
+
+impl std::fmt::Display for QueryContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) [{}]",
+            self.query_type,
+            self.statement,
+            self.bind_names.join(", ")
+        )
+    }
+}
+
 /// An error when building an upsert query
 #[derive(thiserror::Error, Clone, Debug)]
 pub enum BuildUpsertQueryError {
@@ -62,6 +222,35 @@ pub enum BuildUpsertQueryError {
         /// The field being added when the error was thrown
         field: String,
     },
+
+    /// A key of one of the tables changed, so the upsert would leave the row of the old key behind
+    #[error("Can't change {field}, a key of {table}, delete the old rows first")]
+    KeyChanged {
+        /// The field that changed
+        field: String,
+        /// A table the field is a key of
+        table: String,
+    },
+}
+
+/// A value that isn't a variant of an [`crate::prelude::IntEnum`] or [`crate::prelude::TextEnum`]
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+#[error("Invalid {enum_name} value `{value}`")]
+pub struct InvalidEnumValue {
+    /// The name of the enum
+    pub enum_name: &'static str,
+    /// The value, as it was stored
+    pub value: String,
+}
+
+impl InvalidEnumValue {
+    /// Creates the error of a value of an enum
+    pub fn new(enum_name: &'static str, value: impl ToString) -> Self {
+        Self {
+            enum_name,
+            value: value.to_string(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -94,6 +283,15 @@ mod test {
             .to_string()
         );
 
+        assert_eq!(
+            "Can't change title, a key of post_by_title, delete the old rows first",
+            BuildUpsertQueryError::KeyChanged {
+                field: "title".to_string(),
+                table: "post_by_title".to_string()
+            }
+            .to_string()
+        );
+
         assert_eq!(
             "Can't mix named and unnamed values",
             BuildUpsertQueryError::MixingNamedAndNotNamedValues.to_string()
@@ -112,4 +310,87 @@ mod test {
             .to_string()
         );
     }
+
+    #[derive(Debug, scylla::SerializeRow)]
+    struct GetPersonById {
+        id: i32,
+    }
+
+    impl Query for GetPersonById {
+        fn query() -> String {
+            "select * from person where id = :id".to_string()
+        }
+
+        fn bind_names() -> &'static [&'static str] {
+            &["id"]
+        }
+    }
+
+    #[test]
+    fn test_execution_error() {
+        let err = ScyllaxError::with_query::<GetPersonById>(QueryError::TimeoutError);
+
+        let context = err.context().unwrap();
+        assert!(context.query_type.ends_with("GetPersonById"));
+        assert_eq!(context.statement, "select * from person where id = :id");
+        assert_eq!(context.bind_names, &["id"]);
+
+        assert!(err.to_string().starts_with("Query `"));
+        assert!(err.is_timeout());
+        assert!(err.is_retryable());
+        assert!(!err.is_unavailable());
+    }
+
+    #[test]
+    fn test_statement_context() {
+        let err = ScyllaxError::with_context(
+            QueryContext::statement("use_keyspace", "use \"scyllax\""),
+            QueryError::TimeoutError,
+        );
+
+        assert_eq!(
+            err.context().unwrap().to_string(),
+            "use_keyspace (use \"scyllax\") []"
+        );
+        assert!(err.is_timeout());
+    }
+
+    #[test]
+    fn test_classification() {
+        use scylla::statement::Consistency;
+
+        let unavailable = ScyllaxError::QueryError(QueryError::DbError(
+            DbError::Unavailable {
+                consistency: Consistency::Quorum,
+                required: 2,
+                alive: 1,
+            },
+            "Cannot achieve consistency level".to_string(),
+        ));
+        assert!(unavailable.is_unavailable());
+        assert!(unavailable.is_retryable());
+        assert!(!unavailable.is_timeout());
+
+        let syntax = ScyllaxError::QueryError(QueryError::DbError(
+            DbError::SyntaxError,
+            "line 1:0 no viable alternative".to_string(),
+        ));
+        assert!(!syntax.is_retryable());
+        assert!(!syntax.is_timeout());
+
+        assert!(!ScyllaxError::NoRowsFound.is_retryable());
+        assert!(ScyllaxError::NoRowsFound.context().is_none());
+    }
+
+    #[test]
+    fn test_invalid_enum_value() {
+        assert_eq!(
+            InvalidEnumValue::new("PersonKind", 7).to_string(),
+            "Invalid PersonKind value `7`"
+        );
+        assert_eq!(
+            InvalidEnumValue::new("Color", "purple").to_string(),
+            "Invalid Color value `purple`"
+        );
+    }
 }
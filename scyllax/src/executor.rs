@@ -1,21 +1,39 @@
 //! The `scyllax` [`Executor`] processes queries.
 use crate::{
+    admission::Admissions,
+    builder::{ExecutorBuilder, ExecutorOptions},
+    circuit_breaker::{CircuitBreakerStats, CircuitBreakers},
     collection::QueryCollection,
-    error::ScyllaxError,
+    error::{QueryContext, ScyllaxError},
+    health::{Health, NodeHealth, QueryDescription},
+    interceptor::{InterceptContext, QueryKind},
+    outcome::WriteOutcome,
     prelude::WriteQuery,
     queries::{Query, ReadQuery},
+    slow_query::{BoundValues, SlowQuery},
+    trace::TraceSummary,
 };
 use scylla::{prepared_statement::PreparedStatement, QueryResult, Session, SessionBuilder};
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    future::Future,
     hash::{Hash, Hasher},
-    sync::Arc,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    mpsc::error::TrySendError, oneshot, oneshot::error::RecvError, OwnedSemaphorePermit,
 };
-use tokio::sync::{mpsc::error::TrySendError, oneshot};
 use tokio::{
     sync::mpsc::{Receiver, Sender},
     task::JoinSet,
 };
+use uuid::Uuid;
 
 /// Creates a new [`CachingSession`] and returns it
 pub async fn create_session(
@@ -28,7 +46,13 @@ pub async fn create_session(
         .await?;
 
     if let Some(ks) = default_keyspace {
-        session.use_keyspace(ks, true).await?;
+        let ks: String = ks.into();
+        session.use_keyspace(ks.clone(), true).await.map_err(|e| {
+            ScyllaxError::with_context(
+                QueryContext::statement("use_keyspace", format!("use \"{ks}\"")),
+                e,
+            )
+        })?;
     }
 
     Ok(session)
@@ -51,22 +75,50 @@ pub trait GetCoalescingSender<T: ReadQuery> {
 pub struct Executor<T> {
     pub session: Arc<Session>,
     queries: T,
+    options: Arc<ExecutorOptions>,
+    admissions: Arc<Admissions>,
+    circuit_breakers: Arc<CircuitBreakers>,
 }
 
 /// A message sent to the [`Executor::read_task`] task.
 pub type ShardMessage<Q> = (Q, oneshot::Sender<ReadQueryResult<Q>>);
 
 /// The local HashMap of requests being coalesced in a read task.
-type TaskRequestMap<Q> = HashMap<u64, Vec<oneshot::Sender<ReadQueryResult<Q>>>>;
+type TaskRequestMap<Q> = HashMap<u64, PendingRequest<Q>>;
+
+/// The requestors waiting on a query that's being executed.
+struct PendingRequest<Q: ReadQuery> {
+    /// The number of requestors, shared with the [`Executor::read_query_runner`].
+    waiters: Arc<AtomicUsize>,
+    senders: Vec<oneshot::Sender<ReadQueryResult<Q>>>,
+}
 
 /// The result of a read query.
 type ReadQueryResult<Q> = Arc<Result<<Q as ReadQuery>::Output, ScyllaxError>>;
 
+/// The executions started by a read task, resolving to the hash of the query and its result.
+type TaskJoinSet<Q> = JoinSet<(u64, Result<ReadQueryResult<Q>, RecvError>)>;
+
+/// Admits an execution, resolving to the permit it holds while in flight.
+type AdmitFuture =
+    Pin<Box<dyn Future<Output = Result<Option<OwnedSemaphorePermit>, ScyllaxError>> + Send>>;
+
+/// A new execution in a read task, waiting to be admitted, and the requestors coalesced onto it.
+struct PendingAdmission<Q: ReadQuery> {
+    hash: u64,
+    query: Q,
+    senders: Vec<oneshot::Sender<ReadQueryResult<Q>>>,
+}
+
 /// A message sent to the [`Executor::read_query_runner`] task.
 pub struct QueryRunnerMessage<Q: ReadQuery> {
     pub hash: u64,
     pub query: Q,
     pub response_transmitter: oneshot::Sender<ReadQueryResult<Q>>,
+    /// The number of requestors coalesced onto this query
+    pub waiters: Arc<AtomicUsize>,
+    /// Counts the execution as in flight until it's dropped, if the query has admission limits
+    pub permit: Option<OwnedSemaphorePermit>,
 }
 
 impl<T: QueryCollection + Clone> Executor<T> {
@@ -74,18 +126,95 @@ impl<T: QueryCollection + Clone> Executor<T> {
     // all this is super ugly and inefficient, but its okay because
     // it only happens once per executor
     pub async fn new(session: Arc<Session>) -> Result<Self, ScyllaxError> {
+        Self::builder(session).build().await
+    }
+
+    /// Creates an [`ExecutorBuilder`] to configure the [`Executor`] before creating it.
+    pub fn builder(session: Arc<Session>) -> ExecutorBuilder<T> {
+        ExecutorBuilder::new(session)
+    }
+
+    /// Creates a new [`Executor`] with the options from an [`ExecutorBuilder`].
+    pub(crate) async fn from_options(
+        session: Arc<Session>,
+        options: ExecutorOptions,
+    ) -> Result<Self, ScyllaxError> {
         let queries = T::new(&session).await?;
+        let options = Arc::new(options);
+        let admissions = Arc::new(Admissions::default());
+        let circuit_breakers = Arc::new(CircuitBreakers::default());
         let executor = Arc::new(Self {
             session: session.clone(),
             queries,
+            options: options.clone(),
+            admissions: admissions.clone(),
+            circuit_breakers: circuit_breakers.clone(),
         });
 
         let queries = executor.queries.clone().register_tasks(executor);
-        let executor = Self { session, queries };
+        let executor = Self {
+            session,
+            queries,
+            options,
+            admissions,
+            circuit_breakers,
+        };
 
         Ok(executor)
     }
 
+    /// Checks the health of the cluster and of every registered query.
+    ///
+    /// Every query reports itself as `prepared`, since the executor can't be built unless all of its
+    /// statements were prepared. The statements aren't re-prepared, the driver does it when a node
+    /// no longer knows one.
+    pub async fn health(&self) -> Health {
+        let started = Instant::now();
+        let (reachable, latency, mut error) = match self
+            .session
+            .query("select release_version from system.local", ())
+            .await
+        {
+            Ok(_) => (true, Some(started.elapsed()), None),
+            Err(e) => (false, None, Some(e.to_string())),
+        };
+
+        let nodes = self
+            .session
+            .get_cluster_data()
+            .get_nodes_info()
+            .iter()
+            .map(|node| NodeHealth {
+                address: SocketAddr::new(node.address.ip(), node.address.port()),
+                datacenter: node.datacenter.clone(),
+                rack: node.rack.clone(),
+                up: !node.is_down(),
+            })
+            .collect();
+
+        let schema_version = match self.session.check_schema_agreement().await {
+            Ok(version) => version,
+            Err(e) => {
+                error.get_or_insert_with(|| e.to_string());
+                None
+            }
+        };
+
+        Health {
+            reachable,
+            latency,
+            error,
+            nodes,
+            schema_version,
+            queries: self.queries.status(),
+        }
+    }
+
+    /// Lists every registered query.
+    pub fn describe(&self) -> Vec<QueryDescription> {
+        self.queries.describe()
+    }
+
     /// Executes a read query and returns the result.
     pub async fn execute_read<Q>(&self, query: Q) -> Result<Q::Output, ScyllaxError>
     where
@@ -94,7 +223,8 @@ impl<T: QueryCollection + Clone> Executor<T> {
     {
         let to_coalesce = Q::coalesce();
         if !to_coalesce {
-            return self.perform_read_query(query).await;
+            let _permit = self.admit::<Q>().await?;
+            return self.perform_read_query(query, &AtomicUsize::new(1)).await;
         }
 
         let (tx, rx) = oneshot::channel();
@@ -136,59 +266,89 @@ impl<T: QueryCollection + Clone> Executor<T> {
         Q: ReadQuery,
         T: GetPreparedStatement<Q> + GetCoalescingSender<Q>,
     {
-        let mut join_set: JoinSet<_> = JoinSet::new();
+        let mut join_set: TaskJoinSet<Q> = JoinSet::new();
         let query_runner = Arc::new(query_runner);
 
         let mut requests: TaskRequestMap<Q> = HashMap::new();
+        let query_type = std::any::type_name::<Q>();
+        let admission = self.admissions.get::<Q>(&self.options);
+        // new executions are admitted one at a time, in the order they were received.
+        // requests for a key that's in flight or queued are coalesced without being admitted.
+        let mut admissions: VecDeque<PendingAdmission<Q>> = VecDeque::new();
+        let mut admit: Option<AdmitFuture> = None;
+        // once the queue is full, new requests wait in the channel
+        let max_admissions = request_receiver.max_capacity();
         loop {
+            if admit.is_none() && !admissions.is_empty() {
+                if let Some(admission) = admission.clone() {
+                    admit = Some(Box::pin(async move { admission.admit(query_type).await }));
+                }
+            }
+
             tokio::select! {
-                Some((query, tx)) = request_receiver.recv() => {
+                Some((query, tx)) = request_receiver.recv(), if admissions.len() < max_admissions => {
                     tracing::debug!("recieved a query: {:#?}", query);
-                    let query_type = std::any::type_name::<Q>();
                     let hash = Self::calculate_hash(&query);
 
-                    if let Some(senders) = requests.get_mut(&hash) {
+                    if let Some(pending) = requests.get_mut(&hash) {
                         tracing::info!(key = hash, query = query_type, "coalescing a query");
-                        senders.push(tx);
-                    } else {
-                        requests.insert(hash, vec![tx]);
-                        let (response_transmitter, response_receiver) = oneshot::channel();
-
-                        let query_runner = query_runner.clone();
-                        tokio::spawn(async move {
-                            match query_runner.send(
-                                    QueryRunnerMessage {
-                                        hash,
-                                        query,
-                                        response_transmitter
-                                    }
-                                ).await {
-                                Ok(_) => (),
-                                Err(e) => {
-                                    let err = TrySendError::from(e);
-                                    tracing::error!(
-                                        hash = hash,
-                                        "error sending query to query runner: {:?}",
-                                        err
-                                    );
-                                    // todo: propagate error to requestor
-                                },
-                            };
-                        });
-
-                        join_set.spawn(async move {
-                            let res = response_receiver.await;
-                            tracing::debug!(hash = hash, "joinset handle returned: {:#?}", res);
-
-                            (hash, res)
+                        pending.waiters.fetch_add(1, Ordering::Relaxed);
+                        pending.senders.push(tx);
+                    } else if let Some(pending) = admissions.iter_mut().find(|p| p.hash == hash) {
+                        tracing::info!(
+                            key = hash,
+                            query = query_type,
+                            "coalescing a query waiting to be admitted"
+                        );
+                        pending.senders.push(tx);
+                    } else if admission.is_some() {
+                        admissions.push_back(PendingAdmission {
+                            hash,
+                            query,
+                            senders: vec![tx],
                         });
+                    } else {
+                        Self::start_read(
+                            hash,
+                            query,
+                            vec![tx],
+                            None,
+                            &mut requests,
+                            &mut join_set,
+                            &query_runner,
+                        );
+                    }
+                },
+                // this runs when the oldest new execution is admitted, or rejected
+                admitted = async { admit.as_mut().unwrap().as_mut().await }, if admit.is_some() => {
+                    admit = None;
+                    let PendingAdmission { hash, query, senders } = admissions.pop_front().unwrap();
+
+                    match admitted {
+                        Ok(permit) => {
+                            Self::start_read(
+                                hash,
+                                query,
+                                senders,
+                                permit,
+                                &mut requests,
+                                &mut join_set,
+                                &query_runner,
+                            );
+                        }
+                        Err(e) => {
+                            let res = Arc::new(Err(e));
+                            for sender in senders {
+                                let _ = sender.send(res.clone());
+                            }
+                        }
                     }
                 },
                 // this runs when the query is completed and needs be to dispatched to the requestors
                 Some(join_handle) = join_set.join_next() => {
                     tracing::debug!("join set recieved a result!");
                     if let Ok((hash, result)) = join_handle {
-                        if let Some(mut senders) = requests.remove(&hash) {
+                        if let Some(PendingRequest { mut senders, .. }) = requests.remove(&hash) {
                             let res = result.unwrap();
 
                             let last_sender = senders.pop();
@@ -208,6 +368,63 @@ impl<T: QueryCollection + Clone> Executor<T> {
         }
     }
 
+    /// ## internal
+    ///
+    /// Sends a new execution to the [`Executor::read_query_runner`], and registers its requestors.
+    fn start_read<Q>(
+        hash: u64,
+        query: Q,
+        senders: Vec<oneshot::Sender<ReadQueryResult<Q>>>,
+        permit: Option<OwnedSemaphorePermit>,
+        requests: &mut TaskRequestMap<Q>,
+        join_set: &mut TaskJoinSet<Q>,
+        query_runner: &Arc<Sender<QueryRunnerMessage<Q>>>,
+    ) where
+        Q: ReadQuery,
+    {
+        let waiters = Arc::new(AtomicUsize::new(senders.len()));
+        requests.insert(
+            hash,
+            PendingRequest {
+                waiters: waiters.clone(),
+                senders,
+            },
+        );
+        let (response_transmitter, response_receiver) = oneshot::channel();
+
+        let query_runner = query_runner.clone();
+        tokio::spawn(async move {
+            match query_runner
+                .send(QueryRunnerMessage {
+                    hash,
+                    query,
+                    response_transmitter,
+                    waiters,
+                    permit,
+                })
+                .await
+            {
+                Ok(_) => (),
+                Err(e) => {
+                    let err = TrySendError::from(e);
+                    tracing::error!(
+                        hash = hash,
+                        "error sending query to query runner: {:?}",
+                        err
+                    );
+                    // todo: propagate error to requestor
+                }
+            };
+        });
+
+        join_set.spawn(async move {
+            let res = response_receiver.await;
+            tracing::debug!(hash = hash, "joinset handle returned: {:#?}", res);
+
+            (hash, res)
+        });
+    }
+
     /// ## internal
     ///
     /// This function is repsonsible for receiving query requests, executing them, and sending the result back to the requestor.
@@ -222,10 +439,13 @@ impl<T: QueryCollection + Clone> Executor<T> {
             query,
             response_transmitter,
             hash,
+            waiters,
+            permit,
         }) = query_receiver.recv().await
         {
             tracing::debug!("running query for hash: {hash}");
-            let result = self.perform_read_query(query).await;
+            let result = self.perform_read_query(query, &waiters).await;
+            drop(permit);
             let _ = response_transmitter.send(Arc::new(result));
         }
     }
@@ -233,42 +453,319 @@ impl<T: QueryCollection + Clone> Executor<T> {
     /// ## internal
     ///
     /// Executes a read query and returns the result.
-    pub(self) async fn perform_read_query<Q>(&self, query: Q) -> Result<Q::Output, ScyllaxError>
+    ///
+    /// `waiters` is the number of requestors coalesced onto this execution.
+    pub(self) async fn perform_read_query<Q>(
+        &self,
+        query: Q,
+        waiters: &AtomicUsize,
+    ) -> Result<Q::Output, ScyllaxError>
     where
         Q: Query + ReadQuery + Hash + Send + Sync,
         T: GetPreparedStatement<Q> + GetCoalescingSender<Q>,
     {
-        let statement = self.queries.get_prepared::<Q>();
-        // let variables = query.bind().unwrap();
-        let response = match self.session.execute(statement, query).await {
-            Ok(response) => {
-                tracing::debug!(
-                    "query executed successfully: {:?} rows",
-                    response.rows_num()
-                );
-                response
-            }
-            Err(e) => {
-                tracing::error!("error executing query: {:#?}", e);
-                return Err(e.into());
+        let traced = self.options.should_trace::<Q>();
+        let (response, latency) = self
+            .execute_statement(&query, QueryKind::Read, traced)
+            .await?;
+        let rows = response.rows_num().ok();
+        tracing::debug!("query executed successfully: {:?} rows", rows);
+
+        self.report_slow_query(&query, latency, rows, waiters.load(Ordering::Relaxed));
+
+        if let Some(tracing_id) = response.tracing_id {
+            self.log_trace::<Q>(tracing_id);
+        }
+
+        Q::parse_response(response).await
+    }
+
+    /// Executes a read query with tracing enabled, bypassing coalescing,
+    /// and returns the result with a summary of its trace.
+    ///
+    /// The trace is `None` if it couldn't be fetched, eg: when `system_traces` isn't populated yet.
+    /// The failure is logged instead of returned, since the query was executed.
+    pub async fn execute_read_traced<Q>(
+        &self,
+        query: Q,
+    ) -> Result<(Q::Output, Option<TraceSummary>), ScyllaxError>
+    where
+        Q: ReadQuery,
+        T: GetPreparedStatement<Q>,
+    {
+        let _permit = self.admit::<Q>().await?;
+        let (response, _) = self
+            .execute_statement(&query, QueryKind::Read, true)
+            .await?;
+        let trace = self.fetch_trace::<Q>(response.tracing_id).await;
+        let output = Q::parse_response(response).await?;
+
+        Ok((output, trace))
+    }
+
+    /// Executes a write query and returns its [`WriteOutcome`].
+    ///
+    /// Warnings sent by the server are forwarded to `tracing`.
+    pub async fn execute_write<Q>(&self, query: Q) -> Result<WriteOutcome, ScyllaxError>
+    where
+        Q: WriteQuery + 'static,
+        T: GetPreparedStatement<Q>,
+    {
+        let traced = self.options.should_trace::<Q>();
+        let outcome = self.perform_write_query(query, traced).await?;
+
+        if let Some(tracing_id) = outcome.tracing_id() {
+            self.log_trace::<Q>(tracing_id);
+        }
+
+        Ok(outcome)
+    }
+
+    /// Executes a write query with tracing enabled,
+    /// and returns its [`WriteOutcome`] with a summary of its trace.
+    ///
+    /// The trace is `None` if it couldn't be fetched, eg: when `system_traces` isn't populated yet.
+    /// The failure is logged instead of returned, since the write was applied: retrying it could
+    /// apply a write that isn't idempotent twice.
+    pub async fn execute_write_traced<Q>(
+        &self,
+        query: Q,
+    ) -> Result<(WriteOutcome, Option<TraceSummary>), ScyllaxError>
+    where
+        Q: WriteQuery + 'static,
+        T: GetPreparedStatement<Q>,
+    {
+        let outcome = self.perform_write_query(query, true).await?;
+        let trace = self.fetch_trace::<Q>(outcome.tracing_id()).await;
+        let outcome = outcome.with_coordinator(trace.as_ref().and_then(|t| t.coordinator));
+
+        Ok((outcome, trace))
+    }
+
+    /// ## internal
+    ///
+    /// Executes a write query and forwards the server's warnings to `tracing`.
+    async fn perform_write_query<Q>(
+        &self,
+        query: Q,
+        traced: bool,
+    ) -> Result<WriteOutcome, ScyllaxError>
+    where
+        Q: WriteQuery + 'static,
+        T: GetPreparedStatement<Q>,
+    {
+        let (result, latency) = self
+            .execute_statement(&query, QueryKind::Write, traced)
+            .await?;
+        let outcome = WriteOutcome::new(result);
+        self.report_slow_query(&query, latency, None, 1);
+
+        let query_type = std::any::type_name::<Q>();
+        for warning in outcome.warnings() {
+            tracing::warn!(query = query_type, "server warning: {warning}");
+        }
+
+        Ok(outcome)
+    }
+
+    /// ## internal
+    ///
+    /// Admits an execution of `Q`, if it has admission limits.
+    ///
+    /// The execution counts as in flight until the returned permit is dropped.
+    async fn admit<Q: Query + 'static>(
+        &self,
+    ) -> Result<Option<OwnedSemaphorePermit>, ScyllaxError> {
+        match self.admissions.get::<Q>(&self.options) {
+            Some(admission) => admission.admit(std::any::type_name::<Q>()).await,
+            None => Ok(None),
+        }
+    }
+
+    /// ## internal
+    ///
+    /// Executes the prepared statement of a query through the circuit breaker and the interceptors,
+    /// optionally with tracing enabled. Writes are admitted here, coalesced reads are admitted
+    /// by their read task before they're sent to the query runner.
+    ///
+    /// Returns the result with the time the statement itself took to execute, which excludes
+    /// admission, the circuit breaker and the interceptors. It's `None` when an interceptor
+    /// short-circuited the execution.
+    async fn execute_statement<Q>(
+        &self,
+        query: &Q,
+        kind: QueryKind,
+        traced: bool,
+    ) -> Result<(QueryResult, Option<Duration>), ScyllaxError>
+    where
+        Q: Query + 'static,
+        T: GetPreparedStatement<Q>,
+    {
+        let _permit = match kind {
+            QueryKind::Write => self.admit::<Q>().await?,
+            QueryKind::Read => None,
+        };
+
+        let query_type = std::any::type_name::<Q>();
+        let circuit_breaker = match self.circuit_breakers.get::<Q>(&self.options) {
+            Some(circuit_breaker) => {
+                let generation = circuit_breaker.acquire(query_type)?;
+                Some((circuit_breaker, generation))
             }
+            None => None,
         };
 
-        Q::parse_response(response).await
+        let mut latency = None;
+        let execute = async {
+            let started = Instant::now();
+            let result = self.execute_prepared(query, kind, traced).await;
+            latency = Some(started.elapsed());
+            result
+        };
+
+        let result = if self.options.interceptors.is_empty() {
+            execute.await
+        } else {
+            let statement = Q::query();
+            let ctx = InterceptContext {
+                query_type,
+                statement: &statement,
+                kind,
+                values: query,
+            };
+
+            self.options.interceptors.run(&ctx, execute).await
+        };
+
+        if let Some((circuit_breaker, generation)) = &circuit_breaker {
+            circuit_breaker.record(query_type, *generation, &result);
+        }
+
+        result.map(|result| (result, latency))
+    }
+
+    /// The state of `Q`'s circuit breaker, if it has one.
+    pub fn circuit_breaker_stats<Q: Query + 'static>(&self) -> Option<CircuitBreakerStats> {
+        self.circuit_breakers
+            .get::<Q>(&self.options)
+            .map(|circuit_breaker| circuit_breaker.stats())
     }
 
-    /// Executes a write query and returns the [`scylla::QueryResult`].
-    pub async fn execute_write<Q>(&self, query: Q) -> Result<QueryResult, ScyllaxError>
+    /// ## internal
+    ///
+    /// Executes the prepared statement of a query, optionally with tracing enabled.
+    /// Writes are timestamped by the timestamp generator, if there is one.
+    async fn execute_prepared<Q>(
+        &self,
+        query: &Q,
+        kind: QueryKind,
+        traced: bool,
+    ) -> Result<QueryResult, ScyllaxError>
     where
-        Q: WriteQuery,
+        Q: Query,
         T: GetPreparedStatement<Q>,
     {
         let statement = self.queries.get_prepared::<Q>();
+        let timestamp = match (&self.options.timestamp_generator, kind) {
+            (Some(generator), QueryKind::Write) => Some(generator.next_timestamp()),
+            _ => None,
+        };
+
+        let result = if traced || timestamp.is_some() {
+            let mut statement = statement.clone();
+            statement.set_tracing(traced);
+            statement.set_timestamp(timestamp);
+
+            self.session.execute(&statement, query).await
+        } else {
+            self.session.execute(statement, query).await
+        };
+
+        result.map_err(|e| {
+            let err = ScyllaxError::with_query::<Q>(e);
+            tracing::error!("error executing query: {err}");
+            err
+        })
+    }
+
+    /// ## internal
+    ///
+    /// Logs a [`SlowQuery`] if the execution took longer than the threshold for `Q`.
+    ///
+    /// `latency` is `None` when the query wasn't executed.
+    fn report_slow_query<Q: Query + 'static>(
+        &self,
+        query: &Q,
+        latency: Option<Duration>,
+        rows: Option<usize>,
+        waiters: usize,
+    ) {
+        let (Some(threshold), Some(latency)) = (self.options.slow_query_threshold::<Q>(), latency)
+        else {
+            return;
+        };
+        if latency < threshold {
+            return;
+        }
+
+        SlowQuery {
+            query_type: std::any::type_name::<Q>(),
+            statement: Q::query(),
+            values: BoundValues::new(query, self.options.log_slow_query_values),
+            latency,
+            rows,
+            coalesced_waiters: waiters,
+        }
+        .log();
+    }
 
-        self.session
-            .execute(statement, query)
+    /// ## internal
+    ///
+    /// Fetches the trace of a traced query, logging the failure if it couldn't be fetched.
+    async fn fetch_trace<Q: Query>(&self, tracing_id: Option<Uuid>) -> Option<TraceSummary> {
+        let query_type = std::any::type_name::<Q>();
+        let Some(tracing_id) = tracing_id else {
+            tracing::warn!(query = query_type, "{}", ScyllaxError::MissingTracingId);
+            return None;
+        };
+
+        match TraceSummary::fetch(&self.session, tracing_id)
             .await
-            .map_err(Into::into)
+            .map_err(ScyllaxError::with_query::<Q>)
+        {
+            Ok(trace) => Some(trace),
+            Err(e) => {
+                tracing::warn!(
+                    query = query_type,
+                    tracing_id = %tracing_id,
+                    "failed to fetch query trace: {e}"
+                );
+                None
+            }
+        }
+    }
+
+    /// ## internal
+    ///
+    /// Fetches the trace of a sampled query in the background, then logs it.
+    fn log_trace<Q: Query>(&self, tracing_id: Uuid) {
+        let session = self.session.clone();
+        let query_type = std::any::type_name::<Q>();
+        let context = QueryContext::of::<Q>();
+
+        tokio::spawn(async move {
+            match TraceSummary::fetch(&session, tracing_id)
+                .await
+                .map_err(|e| ScyllaxError::with_context(context, e))
+            {
+                Ok(trace) => trace.log(query_type),
+                Err(e) => tracing::warn!(
+                    query = query_type,
+                    tracing_id = %tracing_id,
+                    "failed to fetch query trace: {e}"
+                ),
+            }
+        });
     }
 }
 
@@ -0,0 +1,142 @@
+//! Health checks and introspection, returned by [`crate::executor::Executor::health`]
+//! and [`crate::executor::Executor::describe`].
+use crate::interceptor::QueryKind;
+use std::{net::SocketAddr, time::Duration};
+use uuid::Uuid;
+
+/// A query registered in a [`crate::collection::QueryCollection`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryDescription {
+    /// The type name of the query
+    pub query_type: &'static str,
+    /// The CQL statement
+    pub statement: String,
+    /// Whether the query reads or writes
+    pub kind: QueryKind,
+    /// Whether executions of the query are coalesced. Always `false` for writes.
+    pub coalesce: bool,
+    /// The capacity of the coalescing task's queue, for read queries
+    pub queue_capacity: Option<usize>,
+}
+
+/// The status of a query registered in a [`crate::collection::QueryCollection`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryStatus {
+    /// The type name of the query
+    pub query_type: &'static str,
+    /// Whether the query reads or writes
+    pub kind: QueryKind,
+    /// Whether the statement is prepared.
+    /// Always `true` on a built executor, since building the collection fails if a statement can't be prepared.
+    pub prepared: bool,
+    /// Whether the coalescing task and its query runner are both alive, for read queries
+    pub task_alive: Option<bool>,
+    /// The number of requests waiting in the coalescing task's queue, for read queries
+    pub queued: Option<usize>,
+}
+
+impl QueryStatus {
+    /// Whether the query can be executed.
+    pub fn is_healthy(&self) -> bool {
+        self.prepared && self.task_alive != Some(false)
+    }
+}
+
+/// The state of a node, as known by the driver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeHealth {
+    /// The address the driver connects to
+    pub address: SocketAddr,
+    /// The node's datacenter
+    pub datacenter: Option<String>,
+    /// The node's rack
+    pub rack: Option<String>,
+    /// Whether the driver has a working connection to the node
+    pub up: bool,
+}
+
+/// The health of an [`crate::executor::Executor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Health {
+    /// Whether the cluster answered a query
+    pub reachable: bool,
+    /// How long the cluster took to answer
+    pub latency: Option<Duration>,
+    /// The error returned while checking the cluster, if any
+    pub error: Option<String>,
+    /// The nodes of the cluster
+    pub nodes: Vec<NodeHealth>,
+    /// The schema version every node agrees on, if they agree
+    pub schema_version: Option<Uuid>,
+    /// The status of every registered query
+    pub queries: Vec<QueryStatus>,
+}
+
+impl Health {
+    /// Whether every node agrees on the schema.
+    pub fn schema_agreement(&self) -> bool {
+        self.schema_version.is_some()
+    }
+
+    /// Whether the cluster is reachable, its schema is in agreement,
+    /// and every registered query can be executed.
+    pub fn is_healthy(&self) -> bool {
+        self.reachable
+            && self.schema_agreement()
+            && self.nodes.iter().any(|node| node.up)
+            && self.queries.iter().all(QueryStatus::is_healthy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn health() -> Health {
+        Health {
+            reachable: true,
+            latency: Some(Duration::from_millis(1)),
+            error: None,
+            nodes: vec![NodeHealth {
+                address: "127.0.0.1:9042".parse().unwrap(),
+                datacenter: Some("datacenter1".to_string()),
+                rack: Some("rack1".to_string()),
+                up: true,
+            }],
+            schema_version: Some(Uuid::nil()),
+            queries: vec![
+                QueryStatus {
+                    query_type: "GetPersonById",
+                    kind: QueryKind::Read,
+                    prepared: true,
+                    task_alive: Some(true),
+                    queued: Some(0),
+                },
+                QueryStatus {
+                    query_type: "DeletePersonById",
+                    kind: QueryKind::Write,
+                    prepared: true,
+                    task_alive: None,
+                    queued: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_is_healthy() {
+        assert!(health().is_healthy());
+
+        let mut unhealthy = health();
+        unhealthy.schema_version = None;
+        assert!(!unhealthy.is_healthy());
+
+        let mut unhealthy = health();
+        unhealthy.queries[0].task_alive = Some(false);
+        assert!(!unhealthy.is_healthy());
+
+        let mut unhealthy = health();
+        unhealthy.nodes[0].up = false;
+        assert!(!unhealthy.is_healthy());
+    }
+}
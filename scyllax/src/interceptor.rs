@@ -0,0 +1,250 @@
+//! Interceptors see every query the [`crate::executor::Executor`] executes, before and after it runs.
+//!
+//! They're registered with [`crate::builder::ExecutorBuilder::interceptor`] and run in the order they
+//! were registered. `after` runs in reverse order, so the first interceptor sees the final result.
+//! ```rust,ignore
+//! struct Audit;
+//!
+//! #[async_trait]
+//! impl Interceptor for Audit {
+//!     async fn before(&self, ctx: &InterceptContext<'_>) -> Result<Option<QueryResult>, ScyllaxError> {
+//!         tracing::info!("executing {} with {:?}", ctx.query_type, ctx.values);
+//!         Ok(None)
+//!     }
+//! }
+//!
+//! let executor = Executor::<PersonQueries>::builder(session)
+//!     .interceptor(Audit)
+//!     .build()
+//!     .await?;
+//! ```
+use crate::error::ScyllaxError;
+use async_trait::async_trait;
+use scylla::QueryResult;
+use std::{fmt::Debug, future::Future, sync::Arc};
+
+/// Whether a query reads or writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryKind {
+    /// A [`crate::queries::ReadQuery`]
+    Read,
+    /// A [`crate::queries::WriteQuery`]
+    Write,
+}
+
+/// The query an [`Interceptor`] is called for
+#[derive(Debug)]
+pub struct InterceptContext<'a> {
+    /// The type name of the query
+    pub query_type: &'static str,
+    /// The CQL statement
+    pub statement: &'a str,
+    /// Whether the query reads or writes
+    pub kind: QueryKind,
+    /// The query itself, holding the bound values
+    pub values: &'a (dyn Debug + Send + Sync),
+}
+
+/// Adds behavior around every query executed by an [`crate::executor::Executor`].
+#[async_trait]
+pub trait Interceptor: Send + Sync + 'static {
+    /// Called before the query is executed.
+    ///
+    /// Return `Ok(Some(result))` to skip the execution and use `result` instead,
+    /// or an error to fail the query.
+    async fn before(
+        &self,
+        _ctx: &InterceptContext<'_>,
+    ) -> Result<Option<QueryResult>, ScyllaxError> {
+        Ok(None)
+    }
+
+    /// Called with the result of the query, which it may replace.
+    ///
+    /// This is called even when the query was short-circuited, as long as
+    /// this interceptor's `before` was called.
+    async fn after(
+        &self,
+        _ctx: &InterceptContext<'_>,
+        result: Result<QueryResult, ScyllaxError>,
+    ) -> Result<QueryResult, ScyllaxError> {
+        result
+    }
+}
+
+/// The interceptors registered on an executor, in order
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl Interceptors {
+    /// Registers an interceptor after the existing ones.
+    pub(crate) fn push(&mut self, interceptor: impl Interceptor) {
+        self.0.push(Arc::new(interceptor));
+    }
+
+    /// Whether there are no interceptors.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Runs `execute` through the interceptors.
+    pub(crate) async fn run<F>(
+        &self,
+        ctx: &InterceptContext<'_>,
+        execute: F,
+    ) -> Result<QueryResult, ScyllaxError>
+    where
+        F: Future<Output = Result<QueryResult, ScyllaxError>>,
+    {
+        let mut called = 0;
+        let mut short_circuit = None;
+
+        for interceptor in &self.0 {
+            called += 1;
+
+            match interceptor.before(ctx).await {
+                Ok(None) => (),
+                Ok(Some(result)) => {
+                    short_circuit = Some(Ok(result));
+                    break;
+                }
+                Err(e) => {
+                    short_circuit = Some(Err(e));
+                    break;
+                }
+            }
+        }
+
+        let mut result = match short_circuit {
+            Some(result) => result,
+            None => execute.await,
+        };
+
+        for interceptor in self.0[..called].iter().rev() {
+            result = interceptor.after(ctx, result).await;
+        }
+
+        result
+    }
+}
+
+impl Debug for Interceptors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Interceptors({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the order in which it's called
+    struct Recorder {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+        reject: bool,
+    }
+
+    #[async_trait]
+    impl Interceptor for Recorder {
+        async fn before(
+            &self,
+            ctx: &InterceptContext<'_>,
+        ) -> Result<Option<QueryResult>, ScyllaxError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("before {} {}", self.name, ctx.query_type));
+
+            if self.reject {
+                return Err(ScyllaxError::NoRowsFound);
+            }
+
+            Ok(None)
+        }
+
+        async fn after(
+            &self,
+            _ctx: &InterceptContext<'_>,
+            result: Result<QueryResult, ScyllaxError>,
+        ) -> Result<QueryResult, ScyllaxError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("after {} {}", self.name, result.is_ok()));
+
+            result
+        }
+    }
+
+    fn ctx() -> InterceptContext<'static> {
+        InterceptContext {
+            query_type: "GetPersonById",
+            statement: "select * from person where id = :id",
+            kind: QueryKind::Read,
+            values: &"values",
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut interceptors = Interceptors::default();
+        for name in ["a", "b"] {
+            interceptors.push(Recorder {
+                name,
+                calls: calls.clone(),
+                reject: false,
+            });
+        }
+
+        let executed = Mutex::new(false);
+        let result = interceptors
+            .run(&ctx(), async {
+                *executed.lock().unwrap() = true;
+                Ok(QueryResult::default())
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert!(*executed.lock().unwrap());
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "before a GetPersonById",
+                "before b GetPersonById",
+                "after b true",
+                "after a true"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_circuit() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut interceptors = Interceptors::default();
+        for (name, reject) in [("a", false), ("b", true), ("c", false)] {
+            interceptors.push(Recorder {
+                name,
+                calls: calls.clone(),
+                reject,
+            });
+        }
+
+        let result = interceptors
+            .run(&ctx(), async { panic!("the query should not be executed") })
+            .await;
+
+        assert!(matches!(result, Err(ScyllaxError::NoRowsFound)));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "before a GetPersonById",
+                "before b GetPersonById",
+                "after b false",
+                "after a false"
+            ]
+        );
+    }
+}
@@ -36,17 +36,30 @@
 //!     pub created_at: i64,
 //! }
 //! ```
+pub mod admission;
+pub mod builder;
+pub mod circuit_breaker;
 pub mod collection;
+pub mod collection_op;
+pub mod ddl;
 pub mod entity;
 pub mod error;
 pub mod executor;
+pub mod health;
+pub mod interceptor;
 #[cfg(feature = "json")]
 pub mod json;
 #[cfg(feature = "json")]
 pub mod json_blob;
 pub mod maybe_unset;
+pub mod outcome;
+pub mod patch;
 // mod playground;
 pub mod prelude;
 pub mod queries;
 pub mod rows;
+pub mod slow_query;
+pub mod timestamp;
+pub mod trace;
+pub mod udt;
 pub mod util;
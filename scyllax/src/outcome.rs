@@ -0,0 +1,135 @@
+//! The typed outcome of a write query
+use scylla::{frame::response::result::CqlValue, QueryResult};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// The name of the column Scylla returns for lightweight transactions
+const APPLIED_COLUMN: &str = "[applied]";
+
+/// The outcome of a write query executed by the [`crate::executor::Executor`].
+#[derive(Debug)]
+pub struct WriteOutcome {
+    result: QueryResult,
+    coordinator: Option<IpAddr>,
+}
+
+impl WriteOutcome {
+    /// Wraps a raw [`QueryResult`].
+    pub fn new(result: QueryResult) -> Self {
+        Self {
+            result,
+            coordinator: None,
+        }
+    }
+
+    /// Whether the write was applied.
+    ///
+    /// This is only ever `false` for lightweight transactions (eg: `if not exists`)
+    /// whose condition didn't hold.
+    pub fn applied(&self) -> bool {
+        let is_lwt = self
+            .result
+            .col_specs
+            .first()
+            .is_some_and(|spec| spec.name == APPLIED_COLUMN);
+        if !is_lwt {
+            return true;
+        }
+
+        let applied = self
+            .result
+            .rows
+            .as_ref()
+            .and_then(|rows| rows.first())
+            .and_then(|row| row.columns.first());
+
+        matches!(applied, Some(Some(CqlValue::Boolean(true))))
+    }
+
+    /// Sets the node that coordinated the write, once it's known from the write's trace.
+    pub(crate) fn with_coordinator(mut self, coordinator: Option<IpAddr>) -> Self {
+        self.coordinator = coordinator;
+        self
+    }
+
+    /// The warnings sent by the server, eg: tombstone or batch size warnings.
+    pub fn warnings(&self) -> &[String] {
+        &self.result.warnings
+    }
+
+    /// The tracing id of the write, if tracing was enabled.
+    pub fn tracing_id(&self) -> Option<Uuid> {
+        self.result.tracing_id
+    }
+
+    /// The node that coordinated the write.
+    ///
+    /// Only known for writes executed with [`crate::executor::Executor::execute_write_traced`].
+    /// Sampled writes are traced too, but their traces are fetched and logged in the background,
+    /// after the outcome is returned.
+    pub fn coordinator(&self) -> Option<IpAddr> {
+        self.coordinator
+    }
+
+    /// A reference to the raw [`QueryResult`].
+    pub fn raw(&self) -> &QueryResult {
+        &self.result
+    }
+
+    /// Returns the raw [`QueryResult`].
+    pub fn into_inner(self) -> QueryResult {
+        self.result
+    }
+}
+
+impl From<QueryResult> for WriteOutcome {
+    fn from(result: QueryResult) -> Self {
+        Self::new(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scylla::frame::response::result::{ColumnSpec, ColumnType, Row, TableSpec};
+
+    fn col(name: &str, typ: ColumnType) -> ColumnSpec {
+        ColumnSpec {
+            table_spec: TableSpec {
+                ks_name: "ks".to_string(),
+                table_name: "tbl".to_string(),
+            },
+            name: name.to_string(),
+            typ,
+        }
+    }
+
+    fn lwt(applied: bool) -> QueryResult {
+        QueryResult {
+            col_specs: vec![col(APPLIED_COLUMN, ColumnType::Boolean)],
+            rows: Some(vec![Row {
+                columns: vec![Some(CqlValue::Boolean(applied))],
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_applied() {
+        assert!(WriteOutcome::new(QueryResult::default()).applied());
+        assert!(WriteOutcome::new(lwt(true)).applied());
+        assert!(!WriteOutcome::new(lwt(false)).applied());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let outcome = WriteOutcome::new(QueryResult {
+            warnings: vec!["Batch is too large".to_string()],
+            ..Default::default()
+        });
+
+        assert_eq!(outcome.warnings(), &["Batch is too large".to_string()]);
+        assert_eq!(outcome.tracing_id(), None);
+        assert_eq!(outcome.coordinator(), None);
+    }
+}
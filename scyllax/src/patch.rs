@@ -0,0 +1,168 @@
+//! A tri-state value for updating nullable columns: leave as is, clear, or set
+use scylla::{
+    _macro_internal::{Value, ValueTooBig},
+    frame::response::result::ColumnType,
+    serialize::{value::SerializeCql, writers::WrittenCellProof, CellWriter, SerializationError},
+    BufMut,
+};
+
+use crate::maybe_unset::{MaybeUnset, Unset};
+
+/// An update to a nullable column.
+///
+/// Upsert queries use it for `Option<T>` entity fields.
+///
+/// With the `serde` feature, it deserializes from request bodies:
+/// a missing field is [`Patch::Unset`] (the field needs `#[serde(default)]`),
+/// an explicit `null` is [`Patch::Null`], and any other value is [`Patch::Set`].
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum Patch<V> {
+    /// The column is left as is
+    #[default]
+    Unset,
+    /// The column is cleared
+    Null,
+    /// The column is set to the value
+    Set(V),
+}
+
+impl<V> Patch<V> {
+    /// Whether the column is left as is.
+    /// Use it with `#[serde(skip_serializing_if = "Patch::is_unset")]`.
+    pub fn is_unset(&self) -> bool {
+        matches!(self, Patch::Unset)
+    }
+
+    /// Whether the column is cleared
+    pub fn is_null(&self) -> bool {
+        matches!(self, Patch::Null)
+    }
+
+    /// The value the column is set to, if it's set
+    pub fn as_set(&self) -> Option<&V> {
+        match self {
+            Patch::Set(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl<V: Value> Value for Patch<V> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        match self {
+            Patch::Set(v) => v.serialize(buf),
+            Patch::Null => {
+                // null serializes itself to empty value with length = -1
+                buf.put_i32(-1);
+                Ok(())
+            }
+            Patch::Unset => Unset.serialize(buf),
+        }
+    }
+}
+
+impl<V: SerializeCql> SerializeCql for Patch<V> {
+    fn serialize<'b>(
+        &self,
+        typ: &ColumnType,
+        writer: CellWriter<'b>,
+    ) -> Result<WrittenCellProof<'b>, SerializationError> {
+        match self {
+            Patch::Set(v) => v.serialize(typ, writer),
+            Patch::Null => Ok(writer.set_null()),
+            Patch::Unset => Ok(writer.set_unset()),
+        }
+    }
+}
+
+impl<V> From<V> for Patch<V> {
+    fn from(v: V) -> Self {
+        Patch::Set(v)
+    }
+}
+
+/// `None` clears the column, unlike `MaybeUnset` which leaves it as is
+impl<V> From<Option<V>> for Patch<V> {
+    fn from(v: Option<V>) -> Self {
+        match v {
+            Some(v) => Patch::Set(v),
+            None => Patch::Null,
+        }
+    }
+}
+
+impl<V> From<MaybeUnset<Option<V>>> for Patch<V> {
+    fn from(v: MaybeUnset<Option<V>>) -> Self {
+        match v {
+            MaybeUnset::Unset => Patch::Unset,
+            MaybeUnset::Set(v) => v.into(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: serde::Deserialize<'de>> serde::Deserialize<'de> for Patch<V> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // a missing field never reaches here, it's `Default::default()` with `#[serde(default)]`
+        Option::<V>::deserialize(deserializer).map(Patch::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<V: serde::Serialize> serde::Serialize for Patch<V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Set(v) => serializer.serialize_some(v),
+            Patch::Null | Patch::Unset => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Patch::<i32>::Set(1), Patch::from(1));
+        assert_eq!(Patch::<i32>::Set(1), Patch::from(Some(1)));
+        assert_eq!(Patch::<i32>::Null, Patch::from(None));
+        assert_eq!(Patch::<i32>::Unset, Patch::from(MaybeUnset::Unset));
+        assert_eq!(Patch::<i32>::Null, Patch::from(MaybeUnset::Set(None)));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut buf = vec![];
+        Value::serialize(&Patch::<i32>::Null, &mut buf).unwrap();
+        assert_eq!(buf, (-1i32).to_be_bytes());
+
+        let mut buf = vec![];
+        Value::serialize(&Patch::<i32>::Unset, &mut buf).unwrap();
+        assert_eq!(buf, (-2i32).to_be_bytes());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_deserialize() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Body {
+            #[serde(default)]
+            age: Patch<i32>,
+            #[serde(default)]
+            name: Patch<String>,
+            #[serde(default)]
+            email: Patch<String>,
+        }
+
+        let body: Body = serde_json::from_str(r#"{"age": 21, "name": null}"#).unwrap();
+        assert_eq!(
+            body,
+            Body {
+                age: Patch::Set(21),
+                name: Patch::Null,
+                email: Patch::Unset,
+            }
+        );
+    }
+}
@@ -1,11 +1,23 @@
 //! Re-exports of the most commonly used types and traits.
 pub use crate::{
+    admission::{AdmissionLimits, AdmissionPolicy, RateLimit},
+    builder::ExecutorBuilder,
+    circuit_breaker::{CircuitBreakerConfig, CircuitBreakerStats, CircuitState},
     collection::{prepare_query, QueryCollection},
-    entity::EntityExt,
-    error::{BuildUpsertQueryError, ScyllaxError},
+    collection_op::{Append, Prepend, Put, Remove, RemoveKeys},
+    ddl::{CqlType, EntityDdl},
+    entity::{ClusteringOrder, EntityExt},
+    error::{BuildUpsertQueryError, InvalidEnumValue, QueryContext, ScyllaxError},
     executor::{create_session, Executor, GetCoalescingSender, GetPreparedStatement},
+    health::{Health, QueryDescription},
+    interceptor::{InterceptContext, Interceptor, QueryKind},
     maybe_unset::MaybeUnset,
+    outcome::WriteOutcome,
+    patch::Patch,
     queries::{Query, ReadQuery, SerializedValuesResult, WriteQuery},
+    timestamp::MonotonicTimestampGenerator,
+    trace::TraceSummary,
+    udt::UdtExt,
     util::v1_uuid,
 };
 pub use async_trait::async_trait;
@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 
-use crate::error::ScyllaxError;
+use crate::{admission::AdmissionLimits, error::ScyllaxError};
 use async_trait::async_trait;
 use scylla::{
     frame::value::{LegacySerializedValues, SerializeValuesError},
@@ -17,6 +17,16 @@ where
 {
     /// Returns the query as a string
     fn query() -> String;
+
+    /// Returns the names of the bound parameters, in order
+    fn bind_names() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Returns the admission limits set in the query's attributes
+    fn admission() -> Option<AdmissionLimits> {
+        None
+    }
 }
 
 /// The trait that's implemented on read queries, which return an output which demands a parser.
@@ -1,4 +1,11 @@
 //! Macros for matching rows from a [`scylla::QueryResult`]
+use scylla::{
+    cql_to_rust::{FromCqlVal, FromRowError},
+    frame::response::result::{CqlValue, Row},
+    FromRow,
+};
+use std::marker::PhantomData;
+
 #[macro_export]
 /// Take a QueryResult and return a `Result<Option<T>>`
 /// Example:
@@ -56,3 +63,127 @@ macro_rules! match_rows {
         }
     };
 }
+
+#[macro_export]
+/// Take a QueryResult and return a `Result<Option<T>>` of the first column of the first row,
+/// or of the first row if `T` is a struct with one field
+/// Example:
+/// ```rust,ignore
+/// match_scalar!(res, String)
+/// ```
+macro_rules! match_scalar {
+    ($res:ident, $type:ty) => {
+        match $res.single_row() {
+            Ok(row) => {
+                use scyllax::rows::{__ReadRow, __ReadValue};
+
+                (&scyllax::rows::__Column::<$type>(std::marker::PhantomData))
+                    .read(row)
+                    .map(Some)
+                    .map_err(|e| {
+                        use scylla::transport::query_result::SingleRowTypedError;
+
+                        tracing::error!("err: {:?}", e);
+                        scyllax::error::ScyllaxError::SingleRowTyped(
+                            SingleRowTypedError::FromRowError(e),
+                        )
+                    })
+            }
+            Err(err) => {
+                use scylla::transport::query_result::{SingleRowError, SingleRowTypedError};
+                match err {
+                    // tried to parse into type, but there are no rows
+                    SingleRowError::BadNumberOfRows(_) => Ok(None),
+                    SingleRowError::RowsExpected(e) => {
+                        tracing::error!("err: {:?}", e);
+                        Err(scyllax::error::ScyllaxError::SingleRowTyped(
+                            SingleRowTypedError::RowsExpected(e),
+                        ))
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[macro_export]
+/// Take a QueryResult and return a `Result<Vec<T>>` of the first column of every row,
+/// or of every row if `T` is a struct with one field
+/// Example:
+/// ```rust,ignore
+/// match_scalars!(res, String)
+/// ```
+macro_rules! match_scalars {
+    ($res:ident, $type:ty) => {
+        match $res.rows() {
+            Ok(data) => {
+                use scyllax::rows::{__ReadRow, __ReadValue};
+
+                let column = &scyllax::rows::__Column::<$type>(std::marker::PhantomData);
+                let mut rows: Vec<$type> = Vec::with_capacity(data.len());
+                for (index, row) in data.into_iter().enumerate() {
+                    match column.read(row) {
+                        Ok(row) => rows.push(row),
+                        Err(e) => {
+                            tracing::error!(
+                                "failed to parse row {}: {}. will be excluded from result.",
+                                index,
+                                e
+                            );
+                        }
+                    }
+                }
+
+                Ok(rows)
+            }
+            Err(e) => unreachable!("infallible, only used on read queries."),
+        }
+    };
+}
+
+/// Reads a row with one column, as the value of the column or as a struct with one field.
+///
+/// A read query can't tell them apart from its `return_type`, so the type decides:
+/// `(&column).read(row)` picks [`__ReadValue`] for types read from a column, and [`__ReadRow`] for the rest.
+#[doc(hidden)]
+pub struct __Column<T>(pub PhantomData<T>);
+
+/// Reads the first column of a row
+#[doc(hidden)]
+pub trait __ReadValue<T> {
+    fn read(&self, row: Row) -> Result<T, FromRowError>;
+}
+
+impl<T: FromCqlVal<Option<CqlValue>>> __ReadValue<T> for __Column<T> {
+    fn read(&self, row: Row) -> Result<T, FromRowError> {
+        <(T,)>::from_row(row).map(|(value,)| value)
+    }
+}
+
+/// Reads a row with [`FromRow`]
+#[doc(hidden)]
+pub trait __ReadRow<T> {
+    fn read(&self, row: Row) -> Result<T, FromRowError>;
+}
+
+// implemented on a reference, so it's only picked when `__ReadValue` isn't implemented
+impl<T: FromRow> __ReadRow<T> for &__Column<T> {
+    fn read(&self, row: Row) -> Result<T, FromRowError> {
+        T::from_row(row)
+    }
+}
+
+#[macro_export]
+/// Take a QueryResult and return a `Result<bool>` of whether it has any rows
+/// Example:
+/// ```rust,ignore
+/// match_exists!(res)
+/// ```
+macro_rules! match_exists {
+    ($res:ident) => {
+        match $res.rows_num() {
+            Ok(rows) => Ok(rows > 0),
+            Err(e) => unreachable!("infallible, only used on read queries."),
+        }
+    };
+}
@@ -0,0 +1,113 @@
+//! Slow query events, logged by the [`crate::executor::Executor`] when an execution exceeds its threshold.
+//!
+//! The thresholds are configured with [`crate::builder::ExecutorBuilder::slow_query_threshold`]
+//! and [`crate::builder::ExecutorBuilder::slow_query_threshold_for`].
+use crate::queries::Query;
+use std::{fmt::Display, time::Duration};
+
+/// A view of the values bound to a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoundValues {
+    /// Only the names of the bound values are kept
+    Redacted(&'static [&'static str]),
+    /// The debug representation of the query
+    Values(String),
+}
+
+impl BoundValues {
+    /// Creates the view of `query`'s values, redacting them unless `log_values` is set.
+    pub fn new<Q: Query>(query: &Q, log_values: bool) -> Self {
+        if log_values {
+            Self::Values(format!("{query:?}"))
+        } else {
+            Self::Redacted(Q::bind_names())
+        }
+    }
+}
+
+impl Display for BoundValues {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Redacted(names) => {
+                let names = names
+                    .iter()
+                    .map(|name| format!("{name}: <redacted>"))
+                    .collect::<Vec<_>>();
+
+                write!(f, "{{ {} }}", names.join(", "))
+            }
+            Self::Values(values) => write!(f, "{values}"),
+        }
+    }
+}
+
+/// An execution that took longer than its slow query threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowQuery {
+    /// The type name of the query
+    pub query_type: &'static str,
+    /// The CQL statement
+    pub statement: String,
+    /// The values bound to the statement
+    pub values: BoundValues,
+    /// How long the statement took to execute, excluding admission waits and interceptors
+    pub latency: Duration,
+    /// The number of rows returned, for read queries
+    pub rows: Option<usize>,
+    /// The number of requestors waiting on the execution, including the one that started it
+    pub coalesced_waiters: usize,
+}
+
+impl SlowQuery {
+    /// Emits the event, with the `scyllax::slow_query` target.
+    pub fn log(&self) {
+        tracing::warn!(
+            target: "scyllax::slow_query",
+            query = self.query_type,
+            statement = %self.statement,
+            values = %self.values,
+            latency_ms = self.latency.as_millis() as u64,
+            rows = ?self.rows,
+            coalesced_waiters = self.coalesced_waiters,
+            "slow query"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, scylla::SerializeRow)]
+    struct GetPersonByEmail {
+        email: String,
+        rowlimit: i32,
+    }
+
+    impl Query for GetPersonByEmail {
+        fn query() -> String {
+            "select * from person_by_email where email = :email limit :rowlimit".to_string()
+        }
+
+        fn bind_names() -> &'static [&'static str] {
+            &["email", "rowlimit"]
+        }
+    }
+
+    #[test]
+    fn test_bound_values() {
+        let query = GetPersonByEmail {
+            email: "foo@scyllax.local".to_string(),
+            rowlimit: 10,
+        };
+
+        assert_eq!(
+            BoundValues::new(&query, false).to_string(),
+            "{ email: <redacted>, rowlimit: <redacted> }"
+        );
+        assert_eq!(
+            BoundValues::new(&query, true).to_string(),
+            r#"GetPersonByEmail { email: "foo@scyllax.local", rowlimit: 10 }"#
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Client-side write timestamps.
+use std::{
+    sync::atomic::{AtomicI64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Generates write timestamps, in microseconds since the unix epoch, that always increase
+/// within the process, even when several writes happen in the same microsecond or the clock goes back.
+///
+/// Set it on an [`crate::executor::Executor`] with [`crate::builder::ExecutorBuilder::timestamp_generator`]
+/// to timestamp every write, or use it to fill the `set_timestamp` field of upsert queries.
+/// Timestamps in the query itself (`using timestamp`) take precedence over the generated ones.
+#[derive(Debug, Default)]
+pub struct MonotonicTimestampGenerator {
+    last: AtomicI64,
+}
+
+impl MonotonicTimestampGenerator {
+    /// Creates a new generator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next timestamp: the current time, or one microsecond after the last timestamp
+    /// if the clock hasn't moved past it.
+    pub fn next_timestamp(&self) -> i64 {
+        let now = now_micros();
+        let mut last = self.last.load(Ordering::Relaxed);
+
+        loop {
+            let next = now.max(last + 1);
+            match self
+                .last
+                .compare_exchange_weak(last, next, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return next,
+                Err(actual) => last = actual,
+            }
+        }
+    }
+}
+
+/// The current time in microseconds since the unix epoch
+fn now_micros() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_monotonic() {
+        let generator = MonotonicTimestampGenerator::new();
+        let first = generator.next_timestamp();
+        assert!(first >= now_micros() - 1_000_000);
+
+        let mut last = first;
+        for _ in 0..1000 {
+            let next = generator.next_timestamp();
+            assert!(next > last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn test_clock_behind() {
+        let generator = MonotonicTimestampGenerator::new();
+        let future = now_micros() + 60_000_000;
+        generator.last.store(future, Ordering::Relaxed);
+
+        assert_eq!(generator.next_timestamp(), future + 1);
+        assert_eq!(generator.next_timestamp(), future + 2);
+    }
+}
@@ -0,0 +1,206 @@
+//! Summaries of server-side query traces, fetched from `system_traces`.
+use scylla::{tracing::TracingInfo, transport::errors::QueryError, Session};
+use std::{collections::HashMap, fmt::Display, net::IpAddr, time::Duration};
+use uuid::Uuid;
+
+/// A single step of a trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    /// What the node was doing, eg: `Read 1 live rows and 0 tombstone cells`
+    pub activity: String,
+    /// The node that recorded the step
+    pub source: Option<IpAddr>,
+    /// The time elapsed on `source` since it started working on the request
+    pub elapsed: Option<Duration>,
+    /// The time elapsed on `source` since its previous step
+    pub duration: Option<Duration>,
+}
+
+/// A structured summary of a query trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSummary {
+    /// The tracing session id
+    pub tracing_id: Uuid,
+    /// The node that coordinated the request
+    pub coordinator: Option<IpAddr>,
+    /// Every other node involved in the request
+    pub replicas: Vec<IpAddr>,
+    /// The total duration of the request, as measured by the coordinator
+    pub duration: Option<Duration>,
+    /// The steps of the request, in order
+    pub steps: Vec<TraceStep>,
+    /// The number of tombstones read while serving the request
+    pub tombstones_read: u64,
+}
+
+impl TraceSummary {
+    /// Fetches the trace session and its events from `system_traces`.
+    ///
+    /// The error has no context, attach the traced query's with [`crate::error::ScyllaxError::with_query`].
+    pub async fn fetch(session: &Session, tracing_id: Uuid) -> Result<Self, QueryError> {
+        let info = session.get_tracing_info(&tracing_id).await?;
+
+        Ok(Self::from_tracing_info(tracing_id, &info))
+    }
+
+    /// Summarizes a [`TracingInfo`].
+    pub fn from_tracing_info(tracing_id: Uuid, info: &TracingInfo) -> Self {
+        let steps = steps(
+            info.events
+                .iter()
+                .map(|e| (e.activity.clone(), e.source, e.source_elapsed)),
+        );
+
+        let mut replicas = Vec::new();
+        for source in steps.iter().filter_map(|s| s.source) {
+            if Some(source) != info.coordinator && !replicas.contains(&source) {
+                replicas.push(source);
+            }
+        }
+
+        let tombstones_read = steps.iter().map(|s| tombstones_in(&s.activity)).sum();
+
+        Self {
+            tracing_id,
+            coordinator: info.coordinator,
+            replicas,
+            duration: info.duration.map(micros),
+            steps,
+            tombstones_read,
+        }
+    }
+
+    /// Logs the summary.
+    pub fn log(&self, query_type: &str) {
+        tracing::info!(
+            query = query_type,
+            tracing_id = %self.tracing_id,
+            coordinator = ?self.coordinator,
+            replicas = ?self.replicas,
+            duration = ?self.duration,
+            tombstones_read = self.tombstones_read,
+            "query trace:\n{self}"
+        );
+    }
+}
+
+impl Display for TraceSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            let source = step
+                .source
+                .map_or_else(|| "?".to_string(), |s| s.to_string());
+            let elapsed = step.elapsed.map_or(0, |d| d.as_micros());
+            let duration = step.duration.map_or(0, |d| d.as_micros());
+
+            writeln!(
+                f,
+                "[{source}] {elapsed:>8}µs (+{duration}µs) {}",
+                step.activity
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn micros(us: i32) -> Duration {
+    Duration::from_micros(us.max(0) as u64)
+}
+
+/// Builds the steps of a trace, computing the time spent on each step from the time elapsed on its node.
+fn steps(
+    events: impl Iterator<Item = (Option<String>, Option<IpAddr>, Option<i32>)>,
+) -> Vec<TraceStep> {
+    let mut previous: HashMap<Option<IpAddr>, Duration> = HashMap::new();
+
+    events
+        .map(|(activity, source, elapsed)| {
+            let elapsed = elapsed.map(micros);
+            let duration = elapsed.map(|elapsed| {
+                let last = previous.insert(source, elapsed).unwrap_or_default();
+                elapsed.saturating_sub(last)
+            });
+
+            TraceStep {
+                activity: activity.unwrap_or_default(),
+                source,
+                elapsed,
+                duration,
+            }
+        })
+        .collect()
+}
+
+/// Counts the tombstones reported in a trace activity.
+///
+/// Understands Cassandra's `Read 1 live rows and 3 tombstone cells` and
+/// ScyllaDB's `... 1 clustering row(s) (1 live, 0 dead) and 2 range tombstone(s)`.
+fn tombstones_in(activity: &str) -> u64 {
+    let words = activity.split_whitespace().collect::<Vec<_>>();
+
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| word.starts_with("tombstone"))
+        .filter_map(|(i, _)| {
+            let count = match words.get(i.checked_sub(1)?) {
+                Some(&"range") => words.get(i.checked_sub(2)?)?,
+                Some(count) => count,
+                None => return None,
+            };
+
+            count.parse::<u64>().ok()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tombstones_in() {
+        assert_eq!(tombstones_in("Read 1 live rows and 3 tombstone cells"), 3);
+        assert_eq!(
+            tombstones_in("Page stats: 1 partition(s), 0 static row(s) (0 live, 0 dead), 1 clustering row(s) (1 live, 0 dead) and 2 range tombstone(s)"),
+            2
+        );
+        assert_eq!(tombstones_in("Parsing a statement"), 0);
+        assert_eq!(tombstones_in("tombstone"), 0);
+    }
+
+    #[test]
+    fn test_steps() {
+        let a: IpAddr = "10.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let steps = steps(
+            vec![
+                (Some("Parsing a statement".to_string()), Some(a), Some(10)),
+                (
+                    Some("Sending a read request".to_string()),
+                    Some(a),
+                    Some(25),
+                ),
+                (Some("Reading data".to_string()), Some(b), Some(5)),
+                (Some("Read 1 live rows".to_string()), Some(b), Some(45)),
+                (None, Some(a), None),
+            ]
+            .into_iter(),
+        );
+
+        let durations = steps.iter().map(|s| s.duration).collect::<Vec<_>>();
+        assert_eq!(
+            durations,
+            vec![
+                Some(Duration::from_micros(10)),
+                Some(Duration::from_micros(15)),
+                Some(Duration::from_micros(5)),
+                Some(Duration::from_micros(40)),
+                None,
+            ]
+        );
+        assert_eq!(steps[4].activity, "");
+    }
+}
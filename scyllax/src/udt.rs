@@ -0,0 +1,105 @@
+//! User defined types, derived by [`crate::prelude::Udt`].
+use scylla::frame::response::result::ColumnType;
+use scylla::serialize::SerializationError;
+
+/// The traits of a user defined type
+pub trait UdtExt {
+    /// The name of the type, eg: `address`
+    fn type_name() -> &'static str;
+
+    /// Returns the `create type if not exists` statement of the type.
+    /// Every field type must implement [`crate::ddl::CqlType`].
+    fn create_type_cql() -> String;
+}
+
+/// Why a user defined type couldn't be serialized
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum UdtSerializationError {
+    /// The column isn't a user defined type
+    #[error("{udt} can only be serialized as a user defined type, not {column_type}")]
+    NotUdt {
+        /// The struct being serialized
+        udt: &'static str,
+        /// The type of the column
+        column_type: String,
+    },
+    /// The struct has a field the type doesn't
+    #[error("Field `{field}` of {udt} isn't a field of the `{type_name}` type")]
+    NoSuchField {
+        /// The struct being serialized
+        udt: &'static str,
+        /// The field missing from the type
+        field: &'static str,
+        /// The name of the type
+        type_name: String,
+    },
+}
+
+/// Returns the fields of a user defined type column, checking that it has every field of the struct.
+/// Used by the `SerializeCql` implementation of `#[derive(Udt)]`.
+#[doc(hidden)]
+pub fn __udt_field_types<'t>(
+    udt: &'static str,
+    typ: &'t ColumnType,
+    fields: &[&'static str],
+) -> Result<&'t [(String, ColumnType)], SerializationError> {
+    let ColumnType::UserDefinedType {
+        type_name,
+        field_types,
+        ..
+    } = typ
+    else {
+        return Err(SerializationError::new(UdtSerializationError::NotUdt {
+            udt,
+            column_type: format!("{typ:?}"),
+        }));
+    };
+
+    if let Some(field) = fields
+        .iter()
+        .copied()
+        .find(|field| !field_types.iter().any(|(name, _)| name == field))
+    {
+        return Err(SerializationError::new(
+            UdtSerializationError::NoSuchField {
+                udt,
+                field,
+                type_name: type_name.clone(),
+            },
+        ));
+    }
+
+    Ok(field_types)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_udt_field_types() {
+        let typ = ColumnType::UserDefinedType {
+            type_name: "address".to_string(),
+            keyspace: "scyllax".to_string(),
+            field_types: vec![
+                ("street".to_string(), ColumnType::Text),
+                ("city".to_string(), ColumnType::Text),
+            ],
+        };
+
+        let field_types = __udt_field_types("Address", &typ, &["city"]).unwrap();
+        assert_eq!(field_types.len(), 2);
+
+        let error = __udt_field_types("Address", &typ, &["street", "zip"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Field `zip` of Address isn't a field of the `address` type"
+        );
+
+        let error = __udt_field_types("Address", &ColumnType::Text, &["street"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Address can only be serialized as a user defined type, not Text"
+        );
+    }
+}